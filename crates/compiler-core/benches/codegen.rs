@@ -0,0 +1,83 @@
+//! Benchmarks of transforming and generating code for representative
+//! templates. Run with `cargo bench -p vue-compiler-core --bench codegen`.
+
+mod fixtures;
+
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use vue_compiler_core::{
+    CodegenMode, CodegenOptions, CompilerOptions, ParserOptions, RootNode, TransformOptions,
+    base_parse, generate, get_base_transform_preset, transform,
+};
+
+fn options() -> (ParserOptions, TransformOptions, CodegenOptions) {
+    let (parser_options, mut transform_options, codegen_options) = CompilerOptions {
+        mode: Some(CodegenMode::Module),
+        prefix_identifiers: Some(true),
+        hoist_static: Some(true),
+        ..Default::default()
+    }
+    .into();
+    let (node_transforms, directive_transforms) = get_base_transform_preset(true);
+    transform_options.node_transforms = Some(node_transforms);
+    transform_options.directive_transforms = Some(directive_transforms);
+    (parser_options, transform_options, codegen_options)
+}
+
+fn parse_and_transform(template: &str) -> RootNode {
+    let (parser_options, transform_options, _) = options();
+    let mut ast = base_parse(template, Some(parser_options));
+    transform(&mut ast, transform_options);
+    ast
+}
+
+fn bench_template(c: &mut Criterion, name: &str, template: &str) {
+    c.bench_function(&format!("transform {name}"), |b| {
+        b.iter_batched(
+            || {
+                let (parser_options, transform_options, _) = options();
+                (
+                    base_parse(template, Some(parser_options)),
+                    transform_options,
+                )
+            },
+            |(mut ast, transform_options)| {
+                transform(&mut ast, transform_options);
+                ast
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    c.bench_function(&format!("generate {name}"), |b| {
+        b.iter_batched(
+            || (parse_and_transform(template), options().2),
+            |(ast, codegen_options)| generate(ast, codegen_options),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn bench_static_table(c: &mut Criterion) {
+    bench_template(c, "static table", &fixtures::static_table(200, 10));
+}
+
+fn bench_nested_components(c: &mut Criterion) {
+    // the codegen node of an element embeds a copy of its transformed
+    // children, so the transformed AST doubles in size with every level
+    bench_template(c, "nested components", &fixtures::nested_components(8));
+}
+
+fn bench_attribute_heavy(c: &mut Criterion) {
+    bench_template(
+        c,
+        "attribute heavy element",
+        &fixtures::attribute_heavy(500),
+    );
+}
+
+criterion_group!(
+    benches,
+    bench_static_table,
+    bench_nested_components,
+    bench_attribute_heavy
+);
+criterion_main!(benches);
@@ -0,0 +1,44 @@
+//! Generators for representative templates of a given size.
+
+/// A table of static cells, mostly text and plain elements.
+pub fn static_table(rows: usize, cols: usize) -> String {
+    let mut template = String::from("<table>\n");
+    for row in 0..rows {
+        template.push_str("  <tr>");
+        for col in 0..cols {
+            template.push_str(&format!("<td class=\"cell\">row {row} col {col}</td>"));
+        }
+        template.push_str("</tr>\n");
+    }
+    template.push_str("</table>\n");
+    template
+}
+
+/// Components nested `depth` levels deep, each with a few bindings and an
+/// interpolation.
+pub fn nested_components(depth: usize) -> String {
+    let mut template = String::new();
+    for level in 0..depth {
+        template.push_str(&format!(
+            "<Level{level} :item=\"items[{level}]\" v-if=\"show\" @update=\"onUpdate\">{{{{ item.label }}}}"
+        ));
+    }
+    for level in (0..depth).rev() {
+        template.push_str(&format!("</Level{level}>"));
+    }
+    template
+}
+
+/// A single element carrying `count` attributes, directives and event
+/// listeners.
+pub fn attribute_heavy(count: usize) -> String {
+    let attrs: Vec<String> = (0..count)
+        .map(|i| match i % 4 {
+            0 => format!("data-attr-{i}=\"value {i}\""),
+            1 => format!(":prop{i}=\"state.values[{i}]\""),
+            2 => format!("@event{i}.stop=\"handle({i}, $event)\""),
+            _ => format!("aria-flag-{i}"),
+        })
+        .collect();
+    format!("<div {}></div>", attrs.join("\n  "))
+}
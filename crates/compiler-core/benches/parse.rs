@@ -0,0 +1,37 @@
+//! Benchmarks of `base_parse` on representative templates. Run with
+//! `cargo bench -p vue-compiler-core --bench parse`.
+
+mod fixtures;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+use vue_compiler_core::base_parse;
+
+fn bench_static_table(c: &mut Criterion) {
+    let template = fixtures::static_table(200, 10);
+    c.bench_function("parse static table", |b| {
+        b.iter(|| base_parse(black_box(&template), None))
+    });
+}
+
+fn bench_nested_components(c: &mut Criterion) {
+    let template = fixtures::nested_components(100);
+    c.bench_function("parse nested components", |b| {
+        b.iter(|| base_parse(black_box(&template), None))
+    });
+}
+
+fn bench_attribute_heavy(c: &mut Criterion) {
+    let template = fixtures::attribute_heavy(500);
+    c.bench_function("parse attribute heavy element", |b| {
+        b.iter(|| base_parse(black_box(&template), None))
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_static_table,
+    bench_nested_components,
+    bench_attribute_heavy
+);
+criterion_main!(benches);
@@ -0,0 +1,82 @@
+//! Generates `src/entities/named.rs` from the WHATWG list of named character
+//! references:
+//!
+//! ```sh
+//! curl -O https://html.spec.whatwg.org/entities.json
+//! cargo run -p vue-compiler-core --example gen_entities -- entities.json
+//! rustfmt --edition 2024 crates/compiler-core/src/entities/named.rs
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .expect("usage: gen_entities <entities.json>");
+    let json = std::fs::read_to_string(&path).expect("failed to read the entities list");
+    let entities: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(&json).expect("invalid entities list");
+
+    // `&amp;` and `&amp` share one entry, the latter is also a legacy name
+    let mut named = BTreeMap::new();
+    let mut legacy = BTreeSet::new();
+    for (key, value) in &entities {
+        let name = key.trim_start_matches('&');
+        let characters = value["characters"]
+            .as_str()
+            .expect("missing the characters of an entity");
+        match name.strip_suffix(';') {
+            Some(name) => {
+                named.insert(name.to_string(), characters.to_string());
+            }
+            None => {
+                legacy.insert(name.to_string());
+            }
+        }
+    }
+
+    let mut out = String::new();
+    out.push_str(
+        "//! Named character references, generated from the WHATWG list at\n\
+         //! <https://html.spec.whatwg.org/entities.json> by `examples/gen_entities.rs`.\n\
+         //! Do not edit by hand.\n\n",
+    );
+    out.push_str("/// Named references that are allowed to omit the trailing semicolon, sorted.\n");
+    writeln!(
+        out,
+        "pub(super) const LEGACY_ENTITIES: [&str; {}] = [",
+        legacy.len()
+    )
+    .unwrap();
+    for name in &legacy {
+        writeln!(out, "    {name:?},").unwrap();
+    }
+    out.push_str("];\n\n");
+    out.push_str("/// All named references without their trailing semicolon, sorted by name.\n");
+    writeln!(
+        out,
+        "pub(super) static NAMED_ENTITIES: [(&str, &str); {}] = [",
+        named.len()
+    )
+    .unwrap();
+    for (name, characters) in &named {
+        writeln!(out, "    ({name:?}, \"{}\"),", escape(characters)).unwrap();
+    }
+    out.push_str("];\n");
+
+    let target = concat!(env!("CARGO_MANIFEST_DIR"), "/src/entities/named.rs");
+    std::fs::write(target, out).expect("failed to write the entities table");
+}
+
+/// Keeps printable ASCII as is and writes everything else as `\u{...}`.
+fn escape(characters: &str) -> String {
+    characters
+        .chars()
+        .map(|c| match c {
+            '"' | '\\' => format!("\\{c}"),
+            ' '..='~' => c.to_string(),
+            c => format!("\\u{{{:x}}}", c as u32),
+        })
+        .collect()
+}
@@ -1,7 +1,9 @@
 use crate::{
     OpenBlock,
     codegen::CodegenNode,
-    runtime_helpers::{CreateBlock, CreateElementBlock, CreateElementVNode, CreateVNode},
+    runtime_helpers::{
+        CreateBlock, CreateElementBlock, CreateElementVNode, CreateVNode, WithDirectives,
+    },
     transform::TransformContext,
     utils::{find_dir, find_prop},
 };
@@ -42,6 +44,7 @@ pub enum NodeTypes {
     JSObjectExpression,
     JSProperty,
     JSArrayExpression,
+    JSFunctionExpression,
     JSCacheExpression,
 
     // ssr codegen
@@ -58,7 +61,7 @@ pub enum ElementTypes {
 
 /// The node's range. The `start` is inclusive and `end` is exclusive.
 /// [start, end)
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct SourceLocation {
     pub start: Position,
     pub end: Position,
@@ -86,11 +89,16 @@ impl SourceLocation {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// A position in the template. Offsets and columns count UTF-8 bytes, not
+/// characters, so they can be used to slice the template directly; JavaScript
+/// tooling expecting UTF-16 positions has to convert them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Position {
-    /// from start of file
+    /// from start of file, in bytes
     pub offset: usize,
+    /// 1-based
     pub line: usize,
+    /// 1-based, in bytes from the start of the line
     pub column: usize,
 }
 
@@ -196,6 +204,10 @@ impl TemplateChildNode {
         Self::Text(TextNode::new(content, loc))
     }
 
+    pub fn empty_text(loc: SourceLocation) -> Self {
+        Self::new_text(String::new(), loc)
+    }
+
     pub fn new_comment(content: impl Into<String>, loc: SourceLocation) -> Self {
         Self::Comment(CommentNode::new(content, loc))
     }
@@ -213,6 +225,20 @@ impl TemplateChildNode {
             Self::TextCall(node) => node.type_(),
         }
     }
+
+    pub fn loc(&self) -> &SourceLocation {
+        match self {
+            Self::Element(node) => node.loc(),
+            Self::Interpolation(node) => &node.loc,
+            Self::Compound(node) => &node.loc,
+            Self::Text(node) => &node.loc,
+            Self::Comment(node) => &node.loc,
+            Self::If(node) => &node.loc,
+            Self::IfBranch(node) => &node.loc,
+            Self::For(node) => &node.loc,
+            Self::TextCall(node) => &node.loc,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -290,6 +316,24 @@ impl ElementNode {
         }
     }
 
+    pub fn inner_loc(&self) -> Option<&SourceLocation> {
+        match self {
+            Self::PlainElement(el) => el.inner_loc.as_ref(),
+            Self::Component(node) => node.inner_loc.as_ref(),
+            Self::SlotOutlet(node) => node.inner_loc.as_ref(),
+            Self::Template(el) => el.inner_loc.as_ref(),
+        }
+    }
+
+    pub fn inner_loc_mut(&mut self) -> &mut Option<SourceLocation> {
+        match self {
+            Self::PlainElement(el) => &mut el.inner_loc,
+            Self::Component(node) => &mut node.inner_loc,
+            Self::SlotOutlet(node) => &mut node.inner_loc,
+            Self::Template(el) => &mut el.inner_loc,
+        }
+    }
+
     pub fn ns(&self) -> &Namespace {
         match self {
             Self::PlainElement(el) => &el.ns,
@@ -382,6 +426,7 @@ impl ElementNode {
                 codegen_node: None,
                 ssr_codegen_node: None,
                 loc: node.loc.clone(),
+                inner_loc: node.inner_loc.clone(),
             }),
             Self::Component(node) => Self::Component(node.clone()),
             Self::SlotOutlet(node) => Self::Component(ComponentNode {
@@ -393,6 +438,7 @@ impl ElementNode {
                 codegen_node: None,
                 ssr_codegen_node: None,
                 loc: node.loc.clone(),
+                inner_loc: node.inner_loc.clone(),
             }),
             Self::Template(node) => Self::Component(ComponentNode {
                 ns: node.ns.clone(),
@@ -403,10 +449,22 @@ impl ElementNode {
                 codegen_node: None,
                 ssr_codegen_node: None,
                 loc: node.loc.clone(),
+                inner_loc: node.inner_loc.clone(),
             }),
         }
     }
 
+    /// Like [`to_component`](Self::to_component), but moves the props and
+    /// children instead of cloning them.
+    pub fn into_component(self) -> Self {
+        match self {
+            Self::PlainElement(node) => Self::Component(node.into_element_type()),
+            Self::Component(_) => self,
+            Self::SlotOutlet(node) => Self::Component(node.into_element_type()),
+            Self::Template(node) => Self::Component(node.into_element_type()),
+        }
+    }
+
     pub fn to_slot_outlet(&self) -> Self {
         match &self {
             Self::PlainElement(node) => Self::SlotOutlet(SlotOutletNode {
@@ -418,6 +476,7 @@ impl ElementNode {
                 codegen_node: None,
                 ssr_codegen_node: None,
                 loc: node.loc.clone(),
+                inner_loc: node.inner_loc.clone(),
             }),
             Self::Component(node) => Self::SlotOutlet(SlotOutletNode {
                 ns: node.ns.clone(),
@@ -428,6 +487,7 @@ impl ElementNode {
                 codegen_node: None,
                 ssr_codegen_node: None,
                 loc: node.loc.clone(),
+                inner_loc: node.inner_loc.clone(),
             }),
             Self::SlotOutlet(node) => Self::SlotOutlet(node.clone()),
             Self::Template(node) => Self::SlotOutlet(SlotOutletNode {
@@ -439,6 +499,7 @@ impl ElementNode {
                 codegen_node: None,
                 ssr_codegen_node: None,
                 loc: node.loc.clone(),
+                inner_loc: node.inner_loc.clone(),
             }),
         }
     }
@@ -454,6 +515,7 @@ impl ElementNode {
                 codegen_node: None,
                 ssr_codegen_node: None,
                 loc: node.loc.clone(),
+                inner_loc: node.inner_loc.clone(),
             }),
             Self::Component(node) => Self::Template(TemplateNode {
                 ns: node.ns.clone(),
@@ -464,6 +526,7 @@ impl ElementNode {
                 codegen_node: None,
                 ssr_codegen_node: None,
                 loc: node.loc.clone(),
+                inner_loc: node.inner_loc.clone(),
             }),
             Self::SlotOutlet(node) => Self::Template(TemplateNode {
                 ns: node.ns.clone(),
@@ -474,6 +537,7 @@ impl ElementNode {
                 codegen_node: None,
                 ssr_codegen_node: None,
                 loc: node.loc.clone(),
+                inner_loc: node.inner_loc.clone(),
             }),
             Self::Template(node) => Self::Template(node.clone()),
         }
@@ -519,6 +583,9 @@ pub struct BaseElementNode<C, S> {
     pub codegen_node: Option<C>,
     pub ssr_codegen_node: Option<S>,
     pub loc: SourceLocation,
+    /// Only for SFC root level elements: the location of the content between
+    /// the open and the close tags.
+    pub inner_loc: Option<SourceLocation>,
 }
 
 impl<C, S> PartialEq for BaseElementNode<C, S>
@@ -535,6 +602,7 @@ where
             && self.codegen_node == other.codegen_node
             && self.ssr_codegen_node == other.ssr_codegen_node
             && self.loc == other.loc
+            && self.inner_loc == other.inner_loc
     }
 }
 
@@ -553,6 +621,7 @@ where
             codegen_node: self.codegen_node.clone(),
             ssr_codegen_node: self.ssr_codegen_node.clone(),
             loc: self.loc.clone(),
+            inner_loc: self.inner_loc.clone(),
         }
     }
 }
@@ -562,11 +631,31 @@ impl<C, S> BaseElementNode<C, S> {
     pub fn type_(&self) -> NodeTypes {
         NodeTypes::Element
     }
+
+    /// Moves the parsed fields into another element type, dropping the codegen
+    /// nodes which are specific to the current one.
+    fn into_element_type<T, U>(self) -> BaseElementNode<T, U> {
+        BaseElementNode {
+            ns: self.ns,
+            tag: self.tag,
+            props: self.props,
+            children: self.children,
+            is_self_closing: self.is_self_closing,
+            codegen_node: None,
+            ssr_codegen_node: None,
+            loc: self.loc,
+            inner_loc: self.inner_loc,
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum PlainElementNodeCodegenNode {
     VNodeCall(VNodeCall),
+    /// `v-once`
+    Cache(CacheExpression),
+    /// A hoisted static element
+    Simple(SimpleExpressionNode),
 }
 
 pub type PlainElementNode = BaseElementNode<PlainElementNodeCodegenNode, ()>;
@@ -581,6 +670,8 @@ impl PlainElementNode {
 #[derive(Debug, PartialEq, Clone)]
 pub enum ComponentNodeCodegenNode {
     VNodeCall(VNodeCall),
+    /// `v-once`
+    Cache(CacheExpression),
 }
 
 pub type ComponentNode = BaseElementNode<ComponentNodeCodegenNode, ()>;
@@ -593,7 +684,11 @@ impl ComponentNode {
 }
 
 #[derive(Debug, PartialEq, Clone)]
-pub enum SlotOutletNodeCodegenNode {}
+pub enum SlotOutletNodeCodegenNode {
+    RenderSlotCall(CallExpression),
+    /// `v-once`
+    Cache(Box<CacheExpression>),
+}
 
 pub type SlotOutletNode = BaseElementNode<SlotOutletNodeCodegenNode, ()>;
 
@@ -604,8 +699,16 @@ impl SlotOutletNode {
     }
 }
 
-// TemplateNode is a container type that always gets compiled away
-pub type TemplateNode = BaseElementNode<(), ()>;
+#[derive(Debug, PartialEq, Clone)]
+pub enum TemplateNodeCodegenNode {
+    VNodeCall(VNodeCall),
+    /// `v-once`
+    Cache(CacheExpression),
+}
+
+// TemplateNode is a container type that usually gets compiled away by the
+// structural directives, otherwise its children are rendered in place.
+pub type TemplateNode = BaseElementNode<TemplateNodeCodegenNode, ()>;
 
 impl TemplateNode {
     #[inline]
@@ -691,7 +794,7 @@ impl DirectiveNode {
 /// Static types have several levels.
 /// Higher levels implies lower levels. e.g. a node that can be stringified
 /// can always be hoisted and skipped for patch.
-#[derive(Debug, PartialEq, PartialOrd, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub enum ConstantTypes {
     NotConstant,
     CanSkipPatch,
@@ -709,6 +812,11 @@ pub struct SimpleExpressionNode {
     /// the identifiers declared inside the function body.
     identifiers: Option<Vec<String>>,
     is_handler_key: Option<bool>,
+    /// Whether the expression passed the syntax check of the parser; `None` if
+    /// it wasn't checked, e.g. when not prefixing identifiers.
+    pub ast_valid: Option<bool>,
+    /// The expression a `_hoisted_N` reference stands for.
+    pub hoisted: Option<Box<JSChildNode>>,
     pub loc: SourceLocation,
 }
 
@@ -728,10 +836,17 @@ impl SimpleExpressionNode {
             const_type: const_type.unwrap_or(ConstantTypes::NotConstant),
             identifiers: None,
             is_handler_key: None,
+            ast_valid: None,
+            hoisted: None,
             loc: loc.unwrap_or_else(|| SourceLocation::loc_stub()),
         }
     }
 
+    /// A static string expression, e.g. a prop key synthesized by a transform.
+    pub fn constant(content: impl Into<String>) -> Self {
+        Self::new(content, Some(true), None, None)
+    }
+
     pub fn type_(&self) -> NodeTypes {
         NodeTypes::SimpleExpression
     }
@@ -854,7 +969,11 @@ pub struct ForNode {
     pub object_index_alias: Option<ExpressionNode>,
     pub parse_result: ForParseResult,
     pub children: Vec<TemplateChildNode>,
-    pub codegen_node: Option<ForCodegenNode>,
+    /// The `key` of a `<template v-for>`, which is compiled away.
+    pub user_key: Option<BaseElementProps>,
+    /// `v-once` on the `v-for` element caches the whole list.
+    pub is_once: bool,
+    pub codegen_node: Option<ForNodeCodegenNode>,
     pub loc: SourceLocation,
 }
 
@@ -864,6 +983,13 @@ impl ForNode {
     }
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub enum ForNodeCodegenNode {
+    ForCodegen(Box<ForCodegenNode>),
+    /// `v-once`
+    Cache(Box<CacheExpression>),
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct ForParseResult {
     pub source: ExpressionNode,
@@ -935,6 +1061,7 @@ pub enum VNodeCallChildren {
 pub enum VNodeCallTag {
     String(String),
     Symbol(String),
+    Call(CallExpression),
 }
 
 impl From<String> for VNodeCallTag {
@@ -955,6 +1082,7 @@ pub struct VNodeCall {
     pub props: Option<PropsExpression>,
     pub children: Option<VNodeCallChildren>,
     pub patch_flag: Option<PatchFlags>,
+    pub directives: Option<DirectiveArguments>,
     pub is_block: bool,
     pub disable_tracking: bool,
     pub is_component: bool,
@@ -962,38 +1090,19 @@ pub struct VNodeCall {
 }
 
 impl VNodeCall {
-    pub fn new(
-        context: Option<&mut TransformContext>,
-        tag: impl Into<VNodeCallTag>,
-        props: Option<PropsExpression>,
-        children: Option<VNodeCallChildren>,
-        patch_flag: Option<PatchFlags>,
-        is_block: Option<bool>,
-        disable_tracking: Option<bool>,
-        is_component: Option<bool>,
-        loc: Option<SourceLocation>,
-    ) -> Self {
-        let is_block = is_block.unwrap_or_default();
-        let is_component = is_component.unwrap_or_default();
-
-        if let Some(context) = context {
-            if is_block {
-                context.helper(OpenBlock.to_string());
-                context.helper(get_vnode_block_helper(context.in_ssr, is_component));
-            } else {
-                context.helper(get_vnode_helper(context.in_ssr, is_component));
-            }
-        }
-
-        Self {
-            tag: tag.into(),
-            props,
-            children,
-            patch_flag,
-            is_block,
-            disable_tracking: disable_tracking.unwrap_or_default(),
-            is_component,
-            loc: loc.unwrap_or_else(|| SourceLocation::loc_stub()),
+    pub fn builder(tag: impl Into<VNodeCallTag>) -> VNodeCallBuilder {
+        VNodeCallBuilder {
+            call: Self {
+                tag: tag.into(),
+                props: None,
+                children: None,
+                patch_flag: None,
+                directives: None,
+                is_block: false,
+                disable_tracking: false,
+                is_component: false,
+                loc: SourceLocation::loc_stub(),
+            },
         }
     }
 
@@ -1002,6 +1111,70 @@ impl VNodeCall {
     }
 }
 
+/// Chainable construction of a [`VNodeCall`], e.g.
+/// `VNodeCall::builder(tag).children(children).block(true).build(context)`.
+#[derive(Debug)]
+pub struct VNodeCallBuilder {
+    call: VNodeCall,
+}
+
+impl VNodeCallBuilder {
+    pub fn props(mut self, props: Option<PropsExpression>) -> Self {
+        self.call.props = props;
+        self
+    }
+
+    pub fn children(mut self, children: Option<VNodeCallChildren>) -> Self {
+        self.call.children = children;
+        self
+    }
+
+    pub fn patch_flag(mut self, patch_flag: Option<PatchFlags>) -> Self {
+        self.call.patch_flag = patch_flag;
+        self
+    }
+
+    pub fn directives(mut self, directives: Option<DirectiveArguments>) -> Self {
+        self.call.directives = directives;
+        self
+    }
+
+    pub fn block(mut self, is_block: bool) -> Self {
+        self.call.is_block = is_block;
+        self
+    }
+
+    pub fn disable_tracking(mut self, disable_tracking: bool) -> Self {
+        self.call.disable_tracking = disable_tracking;
+        self
+    }
+
+    pub fn component(mut self, is_component: bool) -> Self {
+        self.call.is_component = is_component;
+        self
+    }
+
+    pub fn loc(mut self, loc: SourceLocation) -> Self {
+        self.call.loc = loc;
+        self
+    }
+
+    /// Registers the runtime helpers the call needs.
+    pub fn build(self, context: &mut TransformContext) -> VNodeCall {
+        let call = self.call;
+        if call.directives.is_some() {
+            context.helper(WithDirectives.to_string());
+        }
+        if call.is_block {
+            context.helper(OpenBlock.to_string());
+            context.helper(get_vnode_block_helper(context.in_ssr, call.is_component));
+        } else {
+            context.helper(get_vnode_helper(context.in_ssr, call.is_component));
+        }
+        call
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct ForCodegenNode {
     pub tag: String,
@@ -1024,6 +1197,23 @@ impl ForCodegenNode {
     }
 }
 
+impl From<ForCodegenNode> for VNodeCall {
+    fn from(node: ForCodegenNode) -> Self {
+        let is_block = node.is_block();
+        Self {
+            tag: VNodeCallTag::Symbol(node.tag),
+            props: None,
+            children: Some(VNodeCallChildren::ForRenderListExpression(node.children)),
+            patch_flag: Some(node.patch_flag),
+            directives: None,
+            is_block,
+            disable_tracking: node.disable_tracking,
+            is_component: node.is_component,
+            loc: node.loc,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum ForRenderListArgument {
     Expression(ExpressionNode),
@@ -1070,6 +1260,36 @@ pub struct ForIteratorExpression {
     pub newline: bool,
 }
 
+/// A function expression returning an array of template children, e.g. the
+/// fallback content of a `<slot>`: `() => [...]`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct FunctionExpression {
+    pub params: Option<FunctionParams>,
+    pub returns: Option<Vec<TemplateChildNode>>,
+    pub newline: bool,
+    pub loc: SourceLocation,
+}
+
+impl FunctionExpression {
+    pub fn new(
+        params: Option<FunctionParams>,
+        returns: Option<Vec<TemplateChildNode>>,
+        newline: Option<bool>,
+        loc: Option<SourceLocation>,
+    ) -> Self {
+        Self {
+            params,
+            returns,
+            newline: newline.unwrap_or_default(),
+            loc: loc.unwrap_or_else(SourceLocation::loc_stub),
+        }
+    }
+
+    pub fn type_(&self) -> NodeTypes {
+        NodeTypes::JSFunctionExpression
+    }
+}
+
 // JS Node Types ---------------------------------------------------------------
 
 // We also include a number of JavaScript AST nodes for code generation.
@@ -1086,6 +1306,7 @@ pub enum JSChildNode {
     Compound(CompoundExpressionNode),
     IfConditional(Box<IfConditionalExpression>),
     Cache(Box<CacheExpression>),
+    Function(FunctionExpression),
 }
 
 impl JSChildNode {
@@ -1203,6 +1424,9 @@ impl Property {
 
 pub type ArrayExpressionElement = CodegenNode;
 
+/// `[[_directive_focus, value, arg, modifiers], ...]` passed to `withDirectives`
+pub type DirectiveArguments = ArrayExpression;
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct ArrayExpression {
     pub elements: Vec<ArrayExpressionElement>,
@@ -1289,7 +1513,9 @@ impl TemplateLiteral {
 // Codegen Node Types ----------------------------------------------------------
 #[derive(Debug, PartialEq, Clone)]
 pub enum BlockCodegenNode {
-    VNodeCall(VNodeCall),
+    VNodeCall(Box<VNodeCall>),
+    /// a `<slot>` item, rendered by its `renderSlot` call
+    RenderSlotCall(CallExpression),
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -4,19 +4,22 @@ use crate::{
         ArrayExpression, BlockCodegenNode, CacheExpression, CallArgument, CallCallee,
         CallExpression, CommentNode, ComponentNode, ComponentNodeCodegenNode,
         CompoundExpressionNode, CompoundExpressionNodeChild, ElementNode, ExpressionNode,
-        ForCodegenNode, ForIteratorExpression, ForNode, ForRenderListArgument,
-        ForRenderListExpression, FunctionParams, IfBranchNode, IfCodegenNode,
+        ForCodegenNode, ForIteratorExpression, ForNode, ForNodeCodegenNode, ForRenderListArgument,
+        ForRenderListExpression, FunctionExpression, FunctionParams, IfBranchNode, IfCodegenNode,
         IfConditionalExpression, IfNode, InterpolationNode, JSChildNode, ObjectExpression,
         PlainElementNode, PlainElementNodeCodegenNode, Property, PropsExpression, RootCodegenNode,
-        RootNode, SSRCodegenNode, SimpleExpressionNode, SlotOutletNode, TemplateChildNode,
-        TemplateLiteral, TemplateLiteralElement, TemplateTextChildNode, TextCallNode, TextNode,
-        VNodeCall, VNodeCallChildren, get_vnode_helper,
+        RootNode, SSRCodegenNode, SimpleExpressionNode, SlotOutletNode, SlotOutletNodeCodegenNode,
+        SourceLocation, TemplateChildNode, TemplateLiteral, TemplateLiteralElement, TemplateNode,
+        TemplateNodeCodegenNode, TemplateTextChildNode, TextCallNode, TextNode, VNodeCall,
+        VNodeCallChildren, get_vnode_helper,
     },
+    errors::{CompilerError, ErrorCodes},
     get_vnode_block_helper,
     options::{CodegenMode, CodegenOptions},
     runtime_helpers::{
-        CreateComment, CreateElementVNode, CreateStatic, CreateText, CreateVNode, OpenBlock,
-        ResolveComponent, ResolveDirective, SetBlockTracking, ToDisplayString,
+        CreateBlock, CreateComment, CreateElementBlock, CreateElementVNode, CreateStatic,
+        CreateText, CreateVNode, OpenBlock, ResolveComponent, ResolveDirective, SetBlockTracking,
+        ToDisplayString, WithDirectives,
     },
     utils::{GlobalCompileTimeConstants, is_simple_identifier, to_valid_asset_id},
 };
@@ -41,6 +44,9 @@ pub trait CodegenSourceMapGenerator: std::fmt::Debug {
 }
 
 const PURE_ANNOTATION: &'static str = "/*@__PURE__*/";
+/// The generated code is usually a few times longer than its template, the
+/// code buffer is sized from it to avoid most reallocations.
+const CODE_CAPACITY_FACTOR: usize = 4;
 
 fn alias_helper(s: String) -> String {
     format!("{s}: _{s}")
@@ -67,6 +73,7 @@ pub enum CodegenNode {
     Array(ArrayExpression),
     Simple(SimpleExpressionNode),
     ForIterator(ForIteratorExpression),
+    Function(FunctionExpression),
     IfConditional(IfConditionalExpression),
     Cache(CacheExpression),
     // SSRCodegenNode,
@@ -100,6 +107,7 @@ impl From<JSChildNode> for CodegenNode {
             JSChildNode::Compound(node) => Self::Compound(node),
             JSChildNode::IfConditional(node) => Self::IfConditional(*node),
             JSChildNode::Cache(node) => Self::Cache(*node),
+            JSChildNode::Function(node) => Self::Function(node),
         }
     }
 }
@@ -125,6 +133,8 @@ impl From<PlainElementNodeCodegenNode> for CodegenNode {
     fn from(node: PlainElementNodeCodegenNode) -> Self {
         match node {
             PlainElementNodeCodegenNode::VNodeCall(node) => Self::VNodeCall(node),
+            PlainElementNodeCodegenNode::Cache(node) => Self::Cache(node),
+            PlainElementNodeCodegenNode::Simple(node) => Self::Simple(node),
         }
     }
 }
@@ -133,6 +143,34 @@ impl From<ComponentNodeCodegenNode> for CodegenNode {
     fn from(node: ComponentNodeCodegenNode) -> Self {
         match node {
             ComponentNodeCodegenNode::VNodeCall(node) => Self::VNodeCall(node),
+            ComponentNodeCodegenNode::Cache(node) => Self::Cache(node),
+        }
+    }
+}
+
+impl From<SlotOutletNodeCodegenNode> for CodegenNode {
+    fn from(node: SlotOutletNodeCodegenNode) -> Self {
+        match node {
+            SlotOutletNodeCodegenNode::RenderSlotCall(node) => Self::Call(node),
+            SlotOutletNodeCodegenNode::Cache(node) => Self::Cache(*node),
+        }
+    }
+}
+
+impl From<TemplateNodeCodegenNode> for CodegenNode {
+    fn from(node: TemplateNodeCodegenNode) -> Self {
+        match node {
+            TemplateNodeCodegenNode::VNodeCall(node) => Self::VNodeCall(node),
+            TemplateNodeCodegenNode::Cache(node) => Self::Cache(node),
+        }
+    }
+}
+
+impl From<ForNodeCodegenNode> for CodegenNode {
+    fn from(node: ForNodeCodegenNode) -> Self {
+        match node {
+            ForNodeCodegenNode::ForCodegen(node) => Self::ForCodegen(*node),
+            ForNodeCodegenNode::Cache(node) => Self::Cache(*node),
         }
     }
 }
@@ -168,6 +206,9 @@ pub struct CodegenResult {
     pub code: String,
     pub preamble: String,
     pub ast: RootNode,
+    /// Problems found while generating the code, e.g. nodes that were not
+    /// transformed. Only collected in dev builds.
+    pub diagnostics: Vec<CompilerError>,
 }
 
 enum NewlineType {
@@ -190,17 +231,20 @@ struct CodegenContext {
     optimize_imports: bool,
     runtime_module_name: String,
     runtime_global_name: String,
+    use_create_vnode_only: bool,
+    separate_preamble: bool,
 
     code: String,
     indent_level: usize,
     pure: bool,
     map: Option<Box<dyn CodegenSourceMapGenerator>>,
+    diagnostics: Vec<CompilerError>,
 
     global_compile_time_constants: GlobalCompileTimeConstants,
 }
 
 impl CodegenContext {
-    fn new(options: &CodegenOptions) -> Self {
+    fn new(options: &CodegenOptions, source_len: usize) -> Self {
         Self {
             prefix_identifiers: options
                 .prefix_identifiers
@@ -220,11 +264,14 @@ impl CodegenContext {
                 .runtime_global_name
                 .clone()
                 .unwrap_or_else(|| "Vue".to_string()),
+            use_create_vnode_only: options.use_create_vnode_only.unwrap_or_default(),
+            separate_preamble: options.separate_preamble.unwrap_or_default(),
 
-            code: String::new(),
+            code: String::with_capacity(source_len * CODE_CAPACITY_FACTOR),
             indent_level: 0,
             pure: false,
             map: None,
+            diagnostics: Vec::new(),
 
             global_compile_time_constants: options.global_compile_time_constants,
         }
@@ -273,8 +320,28 @@ fn newline(context: &mut CodegenContext, n: usize) {
     );
 }
 
-pub fn generate(ast: RootNode, options: CodegenOptions) -> CodegenResult {
-    let mut context = CodegenContext::new(&options);
+pub fn generate(mut ast: RootNode, options: CodegenOptions) -> CodegenResult {
+    let source_len = ast
+        .children
+        .last()
+        .map_or(0, |child| child.loc().end.offset);
+    let mut context = CodegenContext::new(&options, source_len);
+    if context.use_create_vnode_only {
+        // the transforms registered the element fast paths
+        ast.helpers = ast
+            .helpers
+            .into_iter()
+            .map(|helper| {
+                if helper == CreateElementVNode.to_string() {
+                    CreateVNode.to_string()
+                } else if helper == CreateElementBlock.to_string() {
+                    CreateBlock.to_string()
+                } else {
+                    helper
+                }
+            })
+            .collect();
+    }
     let mode = context.mode.clone();
     let prefix_identifiers = context.prefix_identifiers;
     let ssr = context.ssr;
@@ -289,11 +356,28 @@ pub fn generate(ast: RootNode, options: CodegenOptions) -> CodegenResult {
     let is_setup_inlined =
         !options.global_compile_time_constants.__browser__ && options.inline.unwrap_or_default();
 
-    if !options.global_compile_time_constants.__browser__ && mode == CodegenMode::Module {
-        gen_module_preamble(&ast, &mut context, gen_scope_id, Some(is_setup_inlined));
+    let is_module =
+        !options.global_compile_time_constants.__browser__ && mode == CodegenMode::Module;
+    if is_module {
+        gen_module_preamble(&ast, &mut context, gen_scope_id);
     } else {
         gen_function_preamble(&ast, &mut context);
     }
+    let preamble = if context.separate_preamble {
+        let capacity = context.code.capacity();
+        std::mem::replace(&mut context.code, String::with_capacity(capacity))
+    } else {
+        // only separate the render function from a preamble that was emitted
+        if !context.code.is_empty() {
+            context.newline();
+        }
+        String::new()
+    };
+    if !is_module {
+        context.push("return ", None, None);
+    } else if !is_setup_inlined {
+        context.push("export ", None, None);
+    }
     // enter render function
     let function_name = if ssr { "ssrRender" } else { "render" };
     let mut args = if ssr {
@@ -402,8 +486,9 @@ pub fn generate(ast: RootNode, options: CodegenOptions) -> CodegenResult {
 
     CodegenResult {
         code: context.code,
-        preamble: String::new(),
+        preamble,
         ast,
+        diagnostics: context.diagnostics,
     }
 }
 
@@ -426,6 +511,18 @@ fn gen_function_preamble(ast: &RootNode, context: &mut CodegenContext) {
     // with block so it doesn't incur the `in` check cost for every helper access.
     if ast.helpers.len() > 0 {
         if !context.global_compile_time_constants.__browser__ && prefix_identifiers {
+            let helpers = ast
+                .helpers
+                .iter()
+                .cloned()
+                .map(alias_helper)
+                .collect::<Vec<String>>()
+                .join(", ");
+            context.push(
+                &format!("const {{ {helpers} }} = {vue_binding}\n"),
+                Some(NewlineType::End),
+                None,
+            );
         } else {
             // "with" mode.
             // save Vue in a separate variable to avoid collision
@@ -459,16 +556,9 @@ fn gen_function_preamble(ast: &RootNode, context: &mut CodegenContext) {
         }
     }
     gen_hoists(&ast.hoists, context);
-    context.newline();
-    context.push("return ", None, None);
 }
 
-fn gen_module_preamble(
-    ast: &RootNode,
-    context: &mut CodegenContext,
-    _gen_scope_id: bool,
-    inline: Option<bool>,
-) {
+fn gen_module_preamble(ast: &RootNode, context: &mut CodegenContext, _gen_scope_id: bool) {
     let runtime_module_name = context.runtime_module_name.clone();
 
     if ast.helpers.len() != 0 {
@@ -515,11 +605,6 @@ fn gen_module_preamble(
     }
 
     gen_hoists(&ast.hoists, context);
-    context.newline();
-
-    if !inline.unwrap_or_default() {
-        context.push("export ", None, None);
-    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -591,9 +676,7 @@ fn gen_node_list_as_array(nodes: Vec<GenNodeListNode>, context: &mut CodegenCont
         true
     } else if (!context.global_compile_time_constants.__browser__
         || context.global_compile_time_constants.__dev__)
-        && nodes
-            .iter()
-            .any(|n| matches!(n, GenNodeListNode::CodegenNode(_)))
+        && nodes.iter().any(|n| !is_text(n))
     {
         true
     } else {
@@ -610,6 +693,21 @@ fn gen_node_list_as_array(nodes: Vec<GenNodeListNode>, context: &mut CodegenCont
     context.push("]", None, None);
 }
 
+/// Whether `node` is printed as a plain text-like value, which keeps short
+/// arrays on a single line.
+fn is_text(node: &GenNodeListNode) -> bool {
+    matches!(
+        node,
+        GenNodeListNode::String(_)
+            | GenNodeListNode::CodegenNode(
+                CodegenNode::Simple(_)
+                    | CodegenNode::Text(_)
+                    | CodegenNode::Interpolation(_)
+                    | CodegenNode::Compound(_)
+            )
+    )
+}
+
 #[derive(Debug)]
 enum GenNodeListNode {
     String(String),
@@ -623,6 +721,7 @@ impl From<VNodeCallTag> for GenNodeListNode {
         match value {
             VNodeCallTag::String(value) => Self::String(value),
             VNodeCallTag::Symbol(value) => Self::Symbol(value),
+            VNodeCallTag::Call(value) => Self::CodegenNode(CodegenNode::Call(value)),
         }
     }
 }
@@ -651,6 +750,8 @@ impl From<PropsExpression> for GenNodeListNode {
     fn from(value: PropsExpression) -> Self {
         match value {
             PropsExpression::Object(node) => Self::CodegenNode(CodegenNode::Object(node)),
+            PropsExpression::Call(node) => Self::CodegenNode(CodegenNode::Call(node)),
+            PropsExpression::Expression(node) => Self::CodegenNode(CodegenNode::from(node)),
         }
     }
 }
@@ -729,16 +830,20 @@ fn gen_node_list(
     }
 }
 
+fn check_codegen_node(has_codegen_node: bool, loc: &SourceLocation, context: &mut CodegenContext) {
+    if context.global_compile_time_constants.__dev__ && !has_codegen_node {
+        context.diagnostics.push(CompilerError::new(
+            ErrorCodes::XCodegenNodeMissing,
+            Some(loc.clone()),
+        ));
+    }
+}
+
 fn gen_node(node: CodegenNode, context: &mut CodegenContext) {
     match node {
         CodegenNode::Element(node) => match node {
             ElementNode::PlainElement(node) => {
-                if context.global_compile_time_constants.__dev__ && node.codegen_node.is_none() {
-                    println!(
-                        "Codegen node is missing for element/if/for node. ` +
-                          `Apply appropriate transforms first."
-                    );
-                }
+                check_codegen_node(node.codegen_node.is_some(), &node.loc, context);
 
                 let PlainElementNode { codegen_node, .. } = node;
                 if let Some(codegen_node) = codegen_node {
@@ -746,12 +851,7 @@ fn gen_node(node: CodegenNode, context: &mut CodegenContext) {
                 }
             }
             ElementNode::Component(node) => {
-                if context.global_compile_time_constants.__dev__ && node.codegen_node.is_none() {
-                    println!(
-                        "Codegen node is missing for element/if/for node. ` +
-                          `Apply appropriate transforms first."
-                    );
-                }
+                check_codegen_node(node.codegen_node.is_some(), &node.loc, context);
 
                 let ComponentNode { codegen_node, .. } = node;
                 if let Some(codegen_node) = codegen_node {
@@ -759,35 +859,24 @@ fn gen_node(node: CodegenNode, context: &mut CodegenContext) {
                 }
             }
             ElementNode::SlotOutlet(node) => {
-                if context.global_compile_time_constants.__dev__ && node.codegen_node.is_none() {
-                    println!(
-                        "Codegen node is missing for element/if/for node. ` +
-                          `Apply appropriate transforms first."
-                    );
-                }
+                check_codegen_node(node.codegen_node.is_some(), &node.loc, context);
 
                 let SlotOutletNode { codegen_node, .. } = node;
-                if let Some(_codegen_node) = codegen_node {
-                    todo!()
-                    // gen_node(CodegenNode::from(codegen_node), context);
+                if let Some(codegen_node) = codegen_node {
+                    gen_node(CodegenNode::from(codegen_node), context);
                 }
             }
             ElementNode::Template(node) => {
-                if context.global_compile_time_constants.__dev__ && node.codegen_node.is_none() {
-                    println!(
-                        "Codegen node is missing for element/if/for node. ` +
-                          `Apply appropriate transforms first."
-                    );
+                check_codegen_node(node.codegen_node.is_some(), &node.loc, context);
+
+                let TemplateNode { codegen_node, .. } = node;
+                if let Some(codegen_node) = codegen_node {
+                    gen_node(CodegenNode::from(codegen_node), context);
                 }
             }
         },
         CodegenNode::If(node) => {
-            if context.global_compile_time_constants.__dev__ && node.codegen_node.is_none() {
-                println!(
-                    "Codegen node is missing for element/if/for node. ` +
-                      `Apply appropriate transforms first."
-                );
-            }
+            check_codegen_node(node.codegen_node.is_some(), &node.loc, context);
 
             let IfNode { codegen_node, .. } = node;
             if let Some(codegen_node) = codegen_node {
@@ -795,16 +884,11 @@ fn gen_node(node: CodegenNode, context: &mut CodegenContext) {
             }
         }
         CodegenNode::For(node) => {
-            if context.global_compile_time_constants.__dev__ && node.codegen_node.is_none() {
-                println!(
-                    "Codegen node is missing for element/if/for node. ` +
-                      `Apply appropriate transforms first."
-                );
-            }
+            check_codegen_node(node.codegen_node.is_some(), &node.loc, context);
 
             let ForNode { codegen_node, .. } = node;
             if let Some(codegen_node) = codegen_node {
-                gen_node(CodegenNode::ForCodegen(codegen_node), context);
+                gen_node(CodegenNode::from(codegen_node), context);
             }
         }
         CodegenNode::Text(text) => {
@@ -847,6 +931,9 @@ fn gen_node(node: CodegenNode, context: &mut CodegenContext) {
         CodegenNode::ForIterator(node) => {
             gen_for_iterator_expression(node, context);
         }
+        CodegenNode::Function(node) => {
+            gen_function_expression(node, context);
+        }
         // NodeTypes.JS_CONDITIONAL_EXPRESSION
         CodegenNode::IfConditional(node) => {
             gen_if_conditional_expression(node, context);
@@ -1006,6 +1093,13 @@ fn gen_vnode_call(node: VNodeCall, context: &mut CodegenContext) {
         .patch_flag
         .map(|patch_flag| gen_vnode_call_patch_flag_string(patch_flag, context));
 
+    if node.directives.is_some() {
+        context.push(
+            &format!("{}(", context.helper(WithDirectives.to_string())),
+            None,
+            None,
+        );
+    }
     if node.is_block {
         context.push(
             &format!(
@@ -1021,9 +1115,15 @@ fn gen_vnode_call(node: VNodeCall, context: &mut CodegenContext) {
         context.push(PURE_ANNOTATION, None, None);
     }
     let call_helper = if node.is_block {
-        get_vnode_block_helper(context.in_ssr, node.is_component)
+        get_vnode_block_helper(
+            context.in_ssr,
+            node.is_component || context.use_create_vnode_only,
+        )
     } else {
-        get_vnode_helper(context.in_ssr, node.is_component)
+        get_vnode_helper(
+            context.in_ssr,
+            node.is_component || context.use_create_vnode_only,
+        )
     };
     context.push(
         &format!("{}(", context.helper(call_helper)),
@@ -1054,6 +1154,11 @@ fn gen_vnode_call(node: VNodeCall, context: &mut CodegenContext) {
     if node.is_block {
         context.push(")", None, None);
     }
+    if let Some(directives) = node.directives {
+        context.push(", ", None, None);
+        gen_node(CodegenNode::Array(directives), context);
+        context.push(")", None, None);
+    }
 }
 
 fn gen_for_codegen_node(node: ForCodegenNode, context: &mut CodegenContext) {
@@ -1074,9 +1179,15 @@ fn gen_for_codegen_node(node: ForCodegenNode, context: &mut CodegenContext) {
         context.push(PURE_ANNOTATION, None, None);
     }
     let call_helper = if node.is_block() {
-        get_vnode_block_helper(context.in_ssr, node.is_component)
+        get_vnode_block_helper(
+            context.in_ssr,
+            node.is_component || context.use_create_vnode_only,
+        )
     } else {
-        get_vnode_helper(context.in_ssr, node.is_component)
+        get_vnode_helper(
+            context.in_ssr,
+            node.is_component || context.use_create_vnode_only,
+        )
     };
     context.push(
         &format!("{}(", context.helper(call_helper)),
@@ -1200,6 +1311,27 @@ fn gen_array_expression(node: ArrayExpression, context: &mut CodegenContext) {
     );
 }
 
+fn gen_function_params(params: FunctionParams, context: &mut CodegenContext) {
+    match params {
+        FunctionParams::Expression(node) => gen_node(CodegenNode::from(node), context),
+        FunctionParams::String(node) => context.push(&node, None, None),
+        FunctionParams::ExpressionList(list) => gen_node_list(
+            list.into_iter()
+                .map(|node| GenNodeListNode::CodegenNode(CodegenNode::from(node)))
+                .collect(),
+            context,
+            None,
+            None,
+        ),
+        FunctionParams::StringList(list) => gen_node_list(
+            list.into_iter().map(GenNodeListNode::String).collect(),
+            context,
+            None,
+            None,
+        ),
+    }
+}
+
 fn gen_for_iterator_expression(node: ForIteratorExpression, context: &mut CodegenContext) {
     let ForIteratorExpression {
         params,
@@ -1213,19 +1345,7 @@ fn gen_for_iterator_expression(node: ForIteratorExpression, context: &mut Codege
     );
 
     if let Some(params) = params {
-        match params {
-            FunctionParams::ExpressionList(list) => {
-                gen_node_list(
-                    list.into_iter()
-                        .map(|node| GenNodeListNode::CodegenNode(CodegenNode::from(node)))
-                        .collect(),
-                    context,
-                    None,
-                    None,
-                );
-            }
-            _ => todo!(),
-        }
+        gen_function_params(params, context);
     }
     context.push(") => ", None, None);
     if newline {
@@ -1238,7 +1358,10 @@ fn gen_for_iterator_expression(node: ForIteratorExpression, context: &mut Codege
         }
         match returns {
             BlockCodegenNode::VNodeCall(node) => {
-                gen_node(CodegenNode::VNodeCall(node), context);
+                gen_node(CodegenNode::VNodeCall(*node), context);
+            }
+            BlockCodegenNode::RenderSlotCall(node) => {
+                gen_node(CodegenNode::Call(node), context);
             }
         }
     }
@@ -1248,6 +1371,44 @@ fn gen_for_iterator_expression(node: ForIteratorExpression, context: &mut Codege
     }
 }
 
+fn gen_function_expression(node: FunctionExpression, context: &mut CodegenContext) {
+    let FunctionExpression {
+        params,
+        returns,
+        newline,
+        ..
+    } = node.clone();
+    context.push(
+        "(",
+        Some(NewlineType::None),
+        Some(CodegenNode::Function(node)),
+    );
+    if let Some(params) = params {
+        gen_function_params(params, context);
+    }
+    context.push(") => ", None, None);
+    if newline {
+        context.push("{", None, None);
+        context.indent();
+    }
+    if let Some(returns) = returns {
+        if newline {
+            context.push("return ", None, None);
+        }
+        gen_node_list_as_array(
+            returns
+                .into_iter()
+                .map(|node| GenNodeListNode::CodegenNode(CodegenNode::from(node)))
+                .collect(),
+            context,
+        );
+    }
+    if newline {
+        context.deindent(None);
+        context.push("}", None, None);
+    }
+}
+
 fn gen_if_conditional_expression(node: IfConditionalExpression, context: &mut CodegenContext) {
     let IfConditionalExpression {
         test,
@@ -1345,6 +1506,10 @@ fn gen_cache_expression(node: CacheExpression, context: &mut CodegenContext) {
     }
 }
 
+// TODO: SSR comments. There is no SSR transform yet, nothing builds the
+// template literals rendered by `ssrRender`. Once there is, a comment is pushed
+// into the current literal as `<!--${escape(content)}-->` when the `comments`
+// option keeps it, and only anchor comments are kept in production.
 fn gen_template_literal(node: TemplateLiteral, context: &mut CodegenContext) {
     context.push("`", None, None);
     let l = node.elements.len();
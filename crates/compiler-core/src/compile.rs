@@ -1,16 +1,21 @@
 use crate::{
     ast::RootNode,
     codegen::{CodegenResult, generate},
-    options::CompilerOptions,
+    errors::{CompilerError, ErrorCodes},
+    options::{CodegenMode, CompilerOptions},
     parser::base_parse,
     transform::{DirectiveTransform, NodeTransform, transform},
     transforms::{
+        noop_directive::TransformNoopDirective,
         transform_element::transform_element,
+        transform_expression::transform_expression,
+        transform_slot_outlet::transform_slot_outlet,
         transform_text::transform_text,
         // transform_v_bind_shorthand::TransformVBindShorthand,
         v_bind::TransformBind,
         v_for::transform_for,
         v_if::transform_if,
+        v_once::transform_once,
     },
 };
 use std::collections::HashMap;
@@ -20,25 +25,36 @@ pub type TransformPreset = (
     HashMap<String, Box<dyn DirectiveTransform>>,
 );
 
-pub fn get_base_transform_preset() -> TransformPreset {
+pub fn get_base_transform_preset(prefix_identifiers: bool) -> TransformPreset {
+    let mut node_transforms: Vec<NodeTransform> = vec![
+        // Box::new(TransformVBindShorthand),
+        transform_once,
+        transform_if,
+        transform_for,
+    ];
+    if prefix_identifiers {
+        node_transforms.push(transform_expression);
+    }
+    node_transforms.extend([transform_slot_outlet, transform_element, transform_text]);
+
     (
-        vec![
-            // Box::new(TransformVBindShorthand),
-            transform_if,
-            transform_for,
-            transform_element,
-            transform_text,
-        ],
-        HashMap::from([(
-            "bind".to_string(),
-            Box::new(TransformBind) as Box<dyn DirectiveTransform>,
-        )]),
+        node_transforms,
+        HashMap::from([
+            (
+                "bind".to_string(),
+                Box::new(TransformBind) as Box<dyn DirectiveTransform>,
+            ),
+            (
+                "cloak".to_string(),
+                Box::new(TransformNoopDirective) as Box<dyn DirectiveTransform>,
+            ),
+        ]),
     )
 }
 
 pub enum BaseCompileSource {
     String(String),
-    RootNode(RootNode),
+    RootNode(Box<RootNode>),
 }
 
 // we name it `baseCompile` so that higher order compilers like
@@ -46,13 +62,45 @@ pub enum BaseCompileSource {
 pub fn base_compile(source: BaseCompileSource, options: CompilerOptions) -> CodegenResult {
     let (parser_options, mut transform_options, codegen_options) = options.into();
 
+    // the browser build has no expression parser to prefix identifiers or to
+    // generate imports with
+    if transform_options.global_compile_time_constants.__browser__ {
+        if transform_options.prefix_identifiers == Some(true) {
+            transform_options
+                .error_handling_options
+                .on_error(CompilerError::new(ErrorCodes::XPrefixIdNotSupported, None));
+        } else if codegen_options.mode == Some(CodegenMode::Module) {
+            transform_options
+                .error_handling_options
+                .on_error(CompilerError::new(
+                    ErrorCodes::XModuleModeNotSupported,
+                    None,
+                ));
+        }
+    }
+
     let mut ast = match source {
         BaseCompileSource::String(source) => base_parse(&source, Some(parser_options)),
-        BaseCompileSource::RootNode(node) => node,
+        BaseCompileSource::RootNode(node) => *node,
     };
 
-    let (node_transforms, directive_transforms) = get_base_transform_preset();
+    let prefix_identifiers = !transform_options.global_compile_time_constants.__browser__
+        && transform_options
+            .prefix_identifiers
+            .unwrap_or(codegen_options.mode == Some(CodegenMode::Module));
+    transform_options.prefix_identifiers = Some(prefix_identifiers);
+    let (mut node_transforms, mut directive_transforms) =
+        get_base_transform_preset(prefix_identifiers);
 
+    // user node transforms run after the built-in ones, user directive
+    // transforms override them
+    node_transforms.extend(transform_options.node_transforms.take().unwrap_or_default());
+    directive_transforms.extend(
+        transform_options
+            .directive_transforms
+            .take()
+            .unwrap_or_default(),
+    );
     transform_options.node_transforms = Some(node_transforms);
     transform_options.directive_transforms = Some(directive_transforms);
 
@@ -0,0 +1,166 @@
+//! HTML character reference decoding for non-browser builds.
+//!
+//! Covers numeric references and all named references of the HTML spec.
+
+mod named;
+
+use named::{LEGACY_ENTITIES, NAMED_ENTITIES};
+use std::borrow::Cow;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DecodingMode {
+    /// Entities in text nodes that can omit the semicolon.
+    Legacy,
+    /// Entities in attributes. Legacy entities without a semicolon are left
+    /// as is when followed by `=` or an alphanumeric character.
+    Attribute,
+}
+
+/// Replacements for numeric references in the C1 control range, as defined
+/// by the HTML spec (windows-1252).
+const C1_REPLACEMENTS: [(u32, u32); 27] = [
+    (0x80, 0x20ac),
+    (0x82, 0x201a),
+    (0x83, 0x0192),
+    (0x84, 0x201e),
+    (0x85, 0x2026),
+    (0x86, 0x2020),
+    (0x87, 0x2021),
+    (0x88, 0x02c6),
+    (0x89, 0x2030),
+    (0x8a, 0x0160),
+    (0x8b, 0x2039),
+    (0x8c, 0x0152),
+    (0x8e, 0x017d),
+    (0x91, 0x2018),
+    (0x92, 0x2019),
+    (0x93, 0x201c),
+    (0x94, 0x201d),
+    (0x95, 0x2022),
+    (0x96, 0x2013),
+    (0x97, 0x2014),
+    (0x98, 0x02dc),
+    (0x99, 0x2122),
+    (0x9a, 0x0161),
+    (0x9b, 0x203a),
+    (0x9c, 0x0153),
+    (0x9e, 0x017e),
+    (0x9f, 0x0178),
+];
+
+/// Decode the character reference starting at `buffer[start]` (which must be
+/// `&`). Returns the decoded text, one or two characters, and the number of
+/// consumed bytes (including the leading `&`), or `None` if no reference
+/// should be decoded.
+pub fn decode_entity(
+    buffer: &[u8],
+    start: usize,
+    mode: DecodingMode,
+) -> Option<(Cow<'static, str>, usize)> {
+    match buffer.get(start + 1) {
+        Some(b'#') => decode_numeric_entity(buffer, start),
+        Some(_) => decode_named_entity(buffer, start, mode),
+        None => None,
+    }
+}
+
+fn decode_numeric_entity(buffer: &[u8], start: usize) -> Option<(Cow<'static, str>, usize)> {
+    let mut index = start + 2;
+    let radix = match buffer.get(index) {
+        Some(b'x') | Some(b'X') => {
+            index += 1;
+            16
+        }
+        _ => 10,
+    };
+    let digits_start = index;
+    let mut code_point: u32 = 0;
+    while let Some(digit) = buffer.get(index).and_then(|c| (*c as char).to_digit(radix)) {
+        // saturate, anything above 0x10FFFF is replaced anyway
+        code_point = code_point.saturating_mul(radix).saturating_add(digit);
+        index += 1;
+    }
+    if index == digits_start {
+        return None;
+    }
+    if buffer.get(index) == Some(&b';') {
+        index += 1;
+    }
+    Some((
+        Cow::Owned(replace_code_point(code_point).to_string()),
+        index - start,
+    ))
+}
+
+fn replace_code_point(code_point: u32) -> char {
+    if let Some((_, replacement)) = C1_REPLACEMENTS.iter().find(|(c, _)| *c == code_point) {
+        return char::from_u32(*replacement).unwrap_or(char::REPLACEMENT_CHARACTER);
+    }
+    if code_point == 0 {
+        return char::REPLACEMENT_CHARACTER;
+    }
+    // surrogates and out of range values are rejected by `from_u32`
+    char::from_u32(code_point).unwrap_or(char::REPLACEMENT_CHARACTER)
+}
+
+fn decode_named_entity(
+    buffer: &[u8],
+    start: usize,
+    mode: DecodingMode,
+) -> Option<(Cow<'static, str>, usize)> {
+    let name_start = start + 1;
+    let name_end = buffer[name_start..]
+        .iter()
+        .position(|c| !c.is_ascii_alphanumeric())
+        .map_or(buffer.len(), |len| name_start + len);
+    // only ASCII alphanumerics, so always valid UTF-8
+    let name = std::str::from_utf8(&buffer[name_start..name_end]).ok()?;
+
+    // a terminated reference always wins
+    if buffer.get(name_end) == Some(&b';')
+        && let Some(decoded) = lookup(name)
+    {
+        return Some((Cow::Borrowed(decoded), name_end + 1 - start));
+    }
+
+    // otherwise find the longest legacy reference that is a prefix of the name
+    let len = (1..=name.len())
+        .rev()
+        .find(|len| LEGACY_ENTITIES.binary_search(&&name[..*len]).is_ok())?;
+    let next = buffer.get(name_start + len);
+    if mode == DecodingMode::Attribute
+        && next.is_some_and(|c| *c == b'=' || c.is_ascii_alphanumeric())
+    {
+        return None;
+    }
+    lookup(&name[..len]).map(|decoded| (Cow::Borrowed(decoded), len + 1))
+}
+
+fn lookup(name: &str) -> Option<&'static str> {
+    NAMED_ENTITIES
+        .binary_search_by(|(entity, _)| (*entity).cmp(name))
+        .ok()
+        .map(|i| NAMED_ENTITIES[i].1)
+}
+
+/// Decode all character references in `input`.
+pub fn decode_html(input: &str, mode: DecodingMode) -> String {
+    let buffer = input.as_bytes();
+    let mut decoded = String::with_capacity(input.len());
+    let mut copied = 0;
+    let mut index = 0;
+    while index < buffer.len() {
+        if buffer[index] == b'&'
+            && let Some((c, consumed)) = decode_entity(buffer, index, mode)
+        {
+            decoded.push_str(&input[copied..index]);
+            decoded.push_str(&c);
+            index += consumed;
+            copied = index;
+        } else {
+            index += 1;
+        }
+    }
+    decoded.push_str(&input[copied..]);
+    decoded
+}
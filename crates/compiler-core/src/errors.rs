@@ -1,4 +1,10 @@
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+};
+
 use crate::{ast::SourceLocation, options::ErrorHandlingOptions};
+use vue_compiler_shared::generate_code_frame;
 
 #[derive(Debug)]
 pub struct CompilerError {
@@ -7,12 +13,23 @@ pub struct CompilerError {
     pub loc: Option<SourceLocation>,
 }
 
+// errors are identified by their code and location, the message may carry
+// extra details
 impl PartialEq for CompilerError {
     fn eq(&self, other: &Self) -> bool {
         self.code == other.code && self.loc == other.loc
     }
 }
 
+impl Eq for CompilerError {}
+
+impl Hash for CompilerError {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.code.hash(state);
+        self.loc.hash(state);
+    }
+}
+
 impl CompilerError {
     pub fn new(code: ErrorCodes, loc: Option<SourceLocation>) -> Self {
         // const msg =
@@ -21,9 +38,32 @@ impl CompilerError {
         //     : `https://vuejs.org/error-reference/#compiler-${code}`
         // const error = new SyntaxError(String(msg)) as InferCompilerError<T>
         Self {
+            message: code.message().to_string(),
             code,
             loc,
-            message: String::new(),
+        }
+    }
+
+    /// Renders the lines of `source` the error points at, with the location
+    /// underlined. Empty for errors without a location.
+    pub fn code_frame(&self, source: &str) -> String {
+        match &self.loc {
+            Some(loc) => generate_code_frame(source, loc.start.offset, loc.end.offset),
+            None => String::new(),
+        }
+    }
+}
+
+impl fmt::Display for CompilerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = if self.message.is_empty() {
+            self.code.message()
+        } else {
+            &self.message
+        };
+        match &self.loc {
+            Some(loc) => write!(f, "{} ({}:{})", message, loc.start.line, loc.start.column),
+            None => write!(f, "{}", message),
         }
     }
 }
@@ -33,10 +73,10 @@ pub struct DefaultErrorHandlingOptions;
 
 impl ErrorHandlingOptions for DefaultErrorHandlingOptions {}
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Hash)]
 pub enum ErrorCodes {
     // parse errors
-    // ABRUPT_CLOSING_OF_EMPTY_COMMENT,
+    AbruptClosingOfEmptyComment,
     CdataInHtmlContent,
     DuplicateAttribute,
     // END_TAG_WITH_ATTRIBUTES,
@@ -44,15 +84,15 @@ pub enum ErrorCodes {
     EOFBeforeTagName,
     EOFInCdata,
     EOFInComment,
-    // EOF_IN_SCRIPT_HTML_COMMENT_LIKE_TEXT,
+    EOFInScriptHtmlCommentLikeText,
     EOFInTag,
-    // INCORRECTLY_CLOSED_COMMENT,
+    IncorrectlyClosedComment,
     // INCORRECTLY_OPENED_COMMENT,
     // INVALID_FIRST_CHARACTER_OF_TAG_NAME,
     MissingAttributeValue,
     MissingEndTagName,
     // MISSING_WHITESPACE_BETWEEN_ATTRIBUTES,
-    // NESTED_COMMENT,
+    NestedComment,
     UnexpectedCharacterInAttributeName,
     UnexpectedCharacterInUnquotedAttributeValue,
     UnexpectedEqualsSignBeforeAttributeName,
@@ -70,12 +110,16 @@ pub enum ErrorCodes {
     // X_V_IF_NO_EXPRESSION,
     // X_V_IF_SAME_KEY,
     // X_V_ELSE_NO_ADJACENT_IF,
-    // X_V_FOR_NO_EXPRESSION,
-    // X_V_FOR_MALFORMED_EXPRESSION,
+    XVForNoExpression,
+    XVForMalformedExpression,
+    XVForNoKey,
+    XVForComponentNoKey,
     // X_V_FOR_TEMPLATE_KEY_PLACEMENT,
-    // X_V_BIND_NO_EXPRESSION,
+    XVBindNoExpression,
+    XVBindSyncModifier,
+    XVIfVForPrecedence,
     // X_V_ON_NO_EXPRESSION,
-    // X_V_SLOT_UNEXPECTED_DIRECTIVE_ON_SLOT_OUTLET,
+    XVSlotUnexpectedDirectiveOnSlotOutlet,
     // X_V_SLOT_MIXED_SLOT_USAGE,
     // X_V_SLOT_DUPLICATE_SLOT_NAMES,
     // X_V_SLOT_EXTRANEOUS_DEFAULT_SLOT_CHILDREN,
@@ -85,22 +129,103 @@ pub enum ErrorCodes {
     // X_V_MODEL_ON_SCOPE_VARIABLE,
     // X_V_MODEL_ON_PROPS,
     // X_V_MODEL_ON_CONST,
-    // X_INVALID_EXPRESSION,
-    // X_KEEP_ALIVE_INVALID_CHILDREN,
+    XInvalidExpression,
+    XKeepAliveInvalidChildren,
+    /// reported by codegen for element, if and for nodes that were not transformed
+    XCodegenNodeMissing,
 
-    // // generic errors
-    // X_PREFIX_ID_NOT_SUPPORTED,
-    // X_MODULE_MODE_NOT_SUPPORTED,
+    // generic errors
+    XPrefixIdNotSupported,
+    XModuleModeNotSupported,
     // X_CACHE_HANDLER_NOT_SUPPORTED,
     // X_SCOPE_ID_NOT_SUPPORTED,
     // X_VNODE_HOOKS,
 
-    // // placed here to preserve order for the current minor
-    // // TODO adjust order in 3.5
-    // X_V_BIND_INVALID_SAME_NAME_ARGUMENT,
+    // placed here to preserve order for the current minor
+    XVBindInvalidSameNameArgument,
+    /// a nested `<template>` without a structural directive is rendered as a
+    /// native `<template>` element
+    XInvalidTemplatePlacement,
+
+    /// Special value for higher-order compilers to report their own codes,
+    /// e.g. the `DOMErrorCodes` of vue-compiler-dom, without colliding with
+    /// these. This should always be kept as the last item.
+    Extended { code: u32, message: &'static str },
+}
+
+impl ErrorCodes {
+    pub fn message(&self) -> &'static str {
+        match self {
+            // parse errors
+            Self::AbruptClosingOfEmptyComment => "Illegal comment.",
+            Self::CdataInHtmlContent => "CDATA section is allowed only in XML context.",
+            Self::DuplicateAttribute => "Duplicate attribute.",
+            Self::EOFBeforeTagName => "Unexpected EOF in tag.",
+            Self::EOFInCdata => "Unexpected EOF in CDATA section.",
+            Self::EOFInComment => "Unexpected EOF in comment.",
+            Self::EOFInScriptHtmlCommentLikeText => "Unexpected EOF in script.",
+            Self::EOFInTag => "Unexpected EOF in tag.",
+            Self::IncorrectlyClosedComment => "Incorrectly closed comment.",
+            Self::MissingAttributeValue => "Attribute value was expected.",
+            Self::MissingEndTagName => "End tag name was expected.",
+            Self::NestedComment => "Unexpected '<!--' in comment.",
+            Self::UnexpectedCharacterInAttributeName => {
+                "Attribute name cannot contain U+0022 (\"), U+0027 ('), and U+003C (<)."
+            }
+            Self::UnexpectedCharacterInUnquotedAttributeValue => {
+                "Unquoted attribute value cannot contain U+0022 (\"), U+0027 ('), U+003C (<), U+003D (=), and U+0060 (`)."
+            }
+            Self::UnexpectedEqualsSignBeforeAttributeName => {
+                "Attribute name cannot start with '='."
+            }
+            Self::UnexpectedQuestionMarkInsteadOfTagName => "'<?' is allowed only in XML context.",
+            Self::UnexpectedSolidusInTag => "Illegal '/' in tags.",
 
-    // // Special value for higher-order compilers to pick up the last code
-    // // to avoid collision of error codes. This should always be kept as the last
-    // // item.
-    // __EXTEND_POINT__,
+            // Vue-specific parse errors
+            Self::XInvalidEndTag => "Invalid end tag.",
+            Self::XMissingEndTag => "Element is missing end tag.",
+            Self::XMissingInterpolationEnd => "Interpolation end sign was not found.",
+            Self::XMissingDirectiveName => "Legal directive name was expected.",
+            Self::XMissingDynamicDirectiveArgumentEnd => {
+                "End bracket for dynamic directive argument was not found. Note that dynamic directive argument cannot contain spaces."
+            }
+
+            // transform errors
+            Self::XVForNoExpression => "v-for is missing expression.",
+            Self::XVForMalformedExpression => "v-for has invalid expression.",
+            Self::XVForNoKey => "Elements in iteration expect to have 'v-bind:key' directives.",
+            Self::XVForComponentNoKey => {
+                "Components in iteration expect to have 'v-bind:key' directives to keep their state."
+            }
+            Self::XVBindNoExpression => "v-bind is missing expression.",
+            Self::XVBindSyncModifier => {
+                "The .sync modifier for v-bind has been removed. Use v-model with argument instead. `v-bind:prop.sync` should be changed to `v-model:prop`."
+            }
+            Self::XVSlotUnexpectedDirectiveOnSlotOutlet => {
+                "Unexpected custom directive on <slot> outlet."
+            }
+            Self::XVIfVForPrecedence => {
+                "v-if / v-for precedence when used on the same element has changed in Vue 3: v-if now takes higher precedence and will no longer have access to v-for scope variables. It is best to avoid the ambiguity with <template> tags or use a computed property that filters v-for data source."
+            }
+            Self::XInvalidExpression => "Error parsing JavaScript expression: ",
+            Self::XKeepAliveInvalidChildren => "<KeepAlive> expects exactly one child component.",
+            Self::XVBindInvalidSameNameArgument => {
+                "v-bind with same-name shorthand only allows static argument."
+            }
+            Self::XInvalidTemplatePlacement => {
+                "<template> without v-if, v-for or v-slot is rendered as a native <template> element and its content is not displayed."
+            }
+            Self::XPrefixIdNotSupported => {
+                "\"prefixIdentifiers\" option is not supported in this build of compiler."
+            }
+            Self::XModuleModeNotSupported => {
+                "ES module mode is not supported in this build of compiler."
+            }
+            Self::XCodegenNodeMissing => {
+                "Codegen node is missing for element/if/for node. Apply appropriate transforms first."
+            }
+
+            Self::Extended { message, .. } => message,
+        }
+    }
 }
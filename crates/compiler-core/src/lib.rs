@@ -1,6 +1,7 @@
 mod ast;
 mod codegen;
 mod compile;
+mod entities;
 mod errors;
 mod options;
 mod parser;
@@ -10,25 +11,37 @@ mod transform;
 mod transforms;
 mod utils;
 
-pub use compile::base_compile;
+pub use compile::{TransformPreset, base_compile, get_base_transform_preset};
 
 pub use ast::*;
 
 // Also expose lower level APIs & types
-pub use crate::codegen::{CodegenResult, generate};
+pub use crate::codegen::{AssetType, CodegenNode, CodegenResult, generate};
 pub use crate::compile::BaseCompileSource;
 pub use crate::errors::{CompilerError, ErrorCodes};
 pub use crate::options::{
-    CodegenMode, CodegenOptions, CompilerOptions, ErrorHandlingOptions, ParserOptions,
-    TransformOptions,
+    CodegenMode, CodegenOptions, CompilerOptions, ErrorHandlingOptions, IsBuiltInComponent,
+    IsCustomElement, ParserOptions, ParserOptionsBuilder, SharedErrorHandlingOptions,
+    TransformOptions, Whitespace,
 };
 pub use crate::parser::base_parse;
 pub use crate::runtime_helpers::*;
 pub use crate::tokenizer::ParseMode;
-pub use crate::transform::transform;
+pub use crate::transform::{
+    DirectiveTransform, DirectiveTransformResult, NodeTransform, NodeTransformState,
+    TransformAncestor, TransformContext, TransformControl, TransformNode, TransformScopes,
+    transform,
+};
 pub use crate::transforms::{
-    transform_element::transform_element,
+    transform_element::{attr_to_prop, transform_element},
+    transform_expression::transform_expression,
+    transform_slot_outlet::transform_slot_outlet,
     // transform_v_bind_shorthand::TransformVBindShorthand,
+    v_for::transform_for,
     v_if::transform_if,
+    v_once::transform_once,
+};
+pub use crate::utils::{
+    GlobalCompileTimeConstants, find_dir, find_prop, is_simple_identifier,
+    resolve_built_in_component, to_valid_asset_id,
 };
-pub use crate::utils::GlobalCompileTimeConstants;
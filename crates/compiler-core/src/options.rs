@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
 use crate::{
     ast::{ElementNode, Namespace, Namespaces},
@@ -18,6 +18,12 @@ pub trait ErrorHandlingOptions: std::fmt::Debug {
     }
 }
 
+/// Platform-specific built-in components, returning their runtime symbol
+pub type IsBuiltInComponent = dyn Fn(&String) -> Option<String>;
+
+/// Separate option for end users to extend the native elements list
+pub type IsCustomElement = dyn Fn(&String) -> Option<bool>;
+
 #[derive(Debug, PartialEq)]
 pub enum Whitespace {
     Preserve,
@@ -46,9 +52,9 @@ pub struct ParserOptions {
     /// e.g. elements that should preserve whitespace inside, e.g. `<pre>`
     pub is_pre_tag: Box<dyn Fn(&String) -> bool>,
     /// Platform-specific built-in components e.g. `<Transition>`
-    pub is_built_in_component: Option<Box<dyn Fn(&String) -> Option<()>>>,
+    pub is_built_in_component: Option<Box<IsBuiltInComponent>>,
     /// Separate option for end users to extend the native elements list
-    pub is_custom_element: Option<Box<dyn Fn(&String) -> Option<bool>>>,
+    pub is_custom_element: Option<Box<IsCustomElement>>,
     /// Transform expressions like {{ foo }} to `_ctx.foo`.
     /// If this option is false, the generated code will be wrapped in a
     /// `with (this) { ... }` block.
@@ -65,6 +71,15 @@ pub struct ParserOptions {
     /// Whether to keep comments in the templates AST.
     /// This defaults to `true` in development and `false` in production builds.
     pub comments: Option<bool>,
+    /// Whether to keep a copy of the whole template on `RootNode.source`.
+    /// Node locations are still sliced from the input while parsing, so large
+    /// batch compiles can turn this off to avoid cloning every template.
+    /// @default true
+    pub store_source: bool,
+    /// Preserve Vue 2 template behaviors for migrating projects, e.g. rewrite
+    /// `v-bind:prop.sync` to a `v-model:prop` style update handler.
+    /// @default false
+    pub compat: Option<bool>,
 
     pub error_handling_options: Box<dyn ErrorHandlingOptions>,
 
@@ -85,9 +100,14 @@ impl ParserOptions {
             is_built_in_component: None,
             is_custom_element: None,
             prefix_identifiers: Some(false),
-            get_namespace: Box::new(|_, _, _| Namespaces::HTML as u32),
+            // children stay in the namespace of their parent, or of the root
+            get_namespace: Box::new(|_, parent, root_namespace| {
+                parent.map_or(root_namespace, |parent| *parent.ns())
+            }),
             whitespace: None,
             comments: Some(global_compile_time_constants.__dev__),
+            store_source: true,
+            compat: None,
 
             error_handling_options: Box::new(DefaultErrorHandlingOptions),
 
@@ -114,10 +134,16 @@ impl std::fmt::Debug for ParserOptions {
             )
             .field("is_void_tag", &"<Fn(&String) -> bool>")
             .field("is_pre_tag", &"<Fn(&String) -> bool>")
+            .field(
+                "is_built_in_component",
+                &"Option<Box<dyn Fn(&String) -> Option<String>>>",
+            )
             .field(
                 "is_custom_element",
                 &"Option<Box<dyn Fn(&String) -> Option<bool>>>",
             )
+            .field("store_source", &self.store_source)
+            .field("compat", &self.compat)
             .field("error_handling_options", &self.error_handling_options)
             .field(
                 "global_compile_time_constants",
@@ -128,7 +154,132 @@ impl std::fmt::Debug for ParserOptions {
     }
 }
 
-#[derive(Debug)]
+impl ParserOptions {
+    pub fn builder() -> ParserOptionsBuilder {
+        ParserOptionsBuilder::default()
+    }
+
+    /// Sets `__DEV__`, leaving the already resolved `comments` as is.
+    pub fn with_dev(mut self, dev: bool) -> Self {
+        self.global_compile_time_constants.__dev__ = dev;
+        self
+    }
+
+    /// Sets `__BROWSER__`.
+    pub fn with_browser(mut self, browser: bool) -> Self {
+        self.global_compile_time_constants.__browser__ = browser;
+        self
+    }
+
+    /// Sets `__TEST__`.
+    pub fn with_test(mut self, test: bool) -> Self {
+        self.global_compile_time_constants.__test__ = test;
+        self
+    }
+}
+
+/// Chainable construction of [`ParserOptions`], e.g.
+/// `ParserOptions::builder().void_tag(|tag| tag == "img").comments(true).build()`.
+#[derive(Debug, Default)]
+pub struct ParserOptionsBuilder {
+    options: ParserOptions,
+    comments: Option<bool>,
+}
+
+impl ParserOptionsBuilder {
+    pub fn parse_mode(mut self, parse_mode: ParseMode) -> Self {
+        self.options.parse_mode = parse_mode;
+        self
+    }
+
+    pub fn ns(mut self, ns: Namespaces) -> Self {
+        self.options.ns = ns;
+        self
+    }
+
+    pub fn native_tag(mut self, is_native_tag: impl Fn(&String) -> bool + 'static) -> Self {
+        self.options.is_native_tag = Some(Box::new(is_native_tag));
+        self
+    }
+
+    pub fn void_tag(mut self, is_void_tag: impl Fn(&String) -> bool + 'static) -> Self {
+        self.options.is_void_tag = Box::new(is_void_tag);
+        self
+    }
+
+    pub fn pre_tag(mut self, is_pre_tag: impl Fn(&String) -> bool + 'static) -> Self {
+        self.options.is_pre_tag = Box::new(is_pre_tag);
+        self
+    }
+
+    pub fn built_in_component(
+        mut self,
+        is_built_in_component: impl Fn(&String) -> Option<String> + 'static,
+    ) -> Self {
+        self.options.is_built_in_component = Some(Box::new(is_built_in_component));
+        self
+    }
+
+    pub fn custom_element(
+        mut self,
+        is_custom_element: impl Fn(&String) -> Option<bool> + 'static,
+    ) -> Self {
+        self.options.is_custom_element = Some(Box::new(is_custom_element));
+        self
+    }
+
+    pub fn prefix_identifiers(mut self, prefix_identifiers: bool) -> Self {
+        self.options.prefix_identifiers = Some(prefix_identifiers);
+        self
+    }
+
+    pub fn namespace(
+        mut self,
+        get_namespace: impl Fn(&String, Option<&ElementNode>, Namespace) -> Namespace + 'static,
+    ) -> Self {
+        self.options.get_namespace = Box::new(get_namespace);
+        self
+    }
+
+    pub fn whitespace(mut self, whitespace: Whitespace) -> Self {
+        self.options.whitespace = Some(whitespace);
+        self
+    }
+
+    pub fn comments(mut self, comments: bool) -> Self {
+        self.comments = Some(comments);
+        self
+    }
+
+    pub fn store_source(mut self, store_source: bool) -> Self {
+        self.options.store_source = store_source;
+        self
+    }
+
+    pub fn on_error(mut self, error_handling_options: impl ErrorHandlingOptions + 'static) -> Self {
+        self.options.error_handling_options = Box::new(error_handling_options);
+        self
+    }
+
+    pub fn global_compile_time_constants(
+        mut self,
+        global_compile_time_constants: GlobalCompileTimeConstants,
+    ) -> Self {
+        self.options.global_compile_time_constants = global_compile_time_constants;
+        self
+    }
+
+    pub fn build(self) -> ParserOptions {
+        let Self {
+            mut options,
+            comments,
+        } = self;
+        // keep the `__DEV__` based default in sync with the final constants
+        options.comments = Some(comments.unwrap_or(options.global_compile_time_constants.__dev__));
+        options
+    }
+}
+
 pub struct TransformOptions {
     // SharedTransformCodegenOptions
     /// Control whether generate SSR-optimized render functions instead.
@@ -147,17 +298,71 @@ pub struct TransformOptions {
     ///  - context.ssr = false
     ///  - context.inSSR = true
     pub in_ssr: Option<bool>,
+    /// Transform expressions like {{ foo }} to `_ctx.foo`.
+    /// If this option is false, the generated code will be wrapped in a
+    /// `with (this) { ... }` block.
+    /// - This is force-enabled in module mode, since modules are by default strict
+    ///   and cannot use `with`
+    ///
+    /// @default mode === 'module'
+    pub prefix_identifiers: Option<bool>,
+    /// Platform-specific built-in components e.g. `<Transition>`
+    pub is_built_in_component: Option<Box<IsBuiltInComponent>>,
+    /// Separate option for end users to extend the native elements list
+    pub is_custom_element: Option<Box<IsCustomElement>>,
 
     /// An array of node transforms to be applied to every AST node.
     pub node_transforms: Option<Vec<NodeTransform>>,
     /// An object of { name: transform } to be applied to every directive attribute
     /// node found on element nodes.
     pub directive_transforms: Option<HashMap<String, Box<dyn DirectiveTransform>>>,
+    /// Preserve Vue 2 template behaviors for migrating projects, e.g. rewrite
+    /// `v-bind:prop.sync` to a `v-model:prop` style update handler.
+    /// @default false
+    pub compat: Option<bool>,
+    /// Hoist static VNodes to constants outside the render function, so they
+    /// are not recreated on every render.
+    /// @default false
+    pub hoist_static: Option<bool>,
+    /// The mode the template was parsed in. In `ParseMode::SFC` the root level
+    /// `<template>`s are the blocks of a single-file component.
+    /// @default ParseMode::BASE
+    pub parse_mode: Option<ParseMode>,
+
+    pub error_handling_options: Box<dyn ErrorHandlingOptions>,
 
     /// Global compile-time constants
     pub global_compile_time_constants: GlobalCompileTimeConstants,
 }
 
+impl std::fmt::Debug for TransformOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TransformOptions")
+            .field("ssr", &self.ssr)
+            .field("in_ssr", &self.in_ssr)
+            .field("prefix_identifiers", &self.prefix_identifiers)
+            .field(
+                "is_built_in_component",
+                &"Option<Box<dyn Fn(&String) -> Option<String>>>",
+            )
+            .field(
+                "is_custom_element",
+                &"Option<Box<dyn Fn(&String) -> Option<bool>>>",
+            )
+            .field("node_transforms", &self.node_transforms)
+            .field("directive_transforms", &self.directive_transforms)
+            .field("compat", &self.compat)
+            .field("hoist_static", &self.hoist_static)
+            .field("parse_mode", &self.parse_mode)
+            .field("error_handling_options", &self.error_handling_options)
+            .field(
+                "global_compile_time_constants",
+                &self.global_compile_time_constants,
+            )
+            .finish()
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum CodegenMode {
     Module,
@@ -223,6 +428,14 @@ pub struct CodegenOptions {
     /// in function mode
     /// @default 'Vue'
     pub runtime_global_name: Option<String>,
+    /// Always create vnodes with `createVNode` / `createBlock`, for runtimes
+    /// that don't ship the `createElementVNode` fast path.
+    /// @default false
+    pub use_create_vnode_only: Option<bool>,
+    /// Write the preamble, i.e. the helper declarations and hoisted values, to
+    /// `CodegenResult.preamble` instead of the start of `code`.
+    /// @default false
+    pub separate_preamble: Option<bool>,
 
     /// Global compile-time constants
     pub global_compile_time_constants: GlobalCompileTimeConstants,
@@ -242,12 +455,13 @@ impl Default for CodegenOptions {
             optimize_imports: None,
             runtime_module_name: None,
             runtime_global_name: None,
+            use_create_vnode_only: None,
+            separate_preamble: None,
             global_compile_time_constants: GlobalCompileTimeConstants::default(),
         }
     }
 }
 
-#[derive(Debug)]
 pub struct CompilerOptions {
     // SharedTransformCodegenOptions
     /// Control whether generate SSR-optimized render functions instead.
@@ -270,9 +484,32 @@ pub struct CompilerOptions {
     /// Also used for self-recursive reference in templates
     /// @default 'template.vue.html'
     pub filename: Option<String>,
+    /// Transform expressions like {{ foo }} to `_ctx.foo`.
+    /// If this option is false, the generated code will be wrapped in a
+    /// `with (this) { ... }` block.
+    /// - This is force-enabled in module mode, since modules are by default strict
+    ///   and cannot use `with`
+    ///
+    /// @default mode === 'module'
+    pub prefix_identifiers: Option<bool>,
+    // ParserOptions & TransformOptions
+    /// Platform-specific built-in components e.g. `<Transition>`
+    pub is_built_in_component: Option<Box<IsBuiltInComponent>>,
+    /// Separate option for end users to extend the native elements list
+    pub is_custom_element: Option<Box<IsCustomElement>>,
+    // ParserOptions
+    /// Whether to keep comments in the templates AST, and so in the generated
+    /// render function. Setting it in production builds keeps comments e.g. as
+    /// hydration anchors.
+    /// @default __DEV__
+    pub comments: Option<bool>,
     // TransformOptions
     pub node_transforms: Option<Vec<NodeTransform>>,
     pub directive_transforms: Option<HashMap<String, Box<dyn DirectiveTransform>>>,
+    /// Hoist static VNodes to constants outside the render function, so they
+    /// are not recreated on every render.
+    /// @default false
+    pub hoist_static: Option<bool>,
     // CodegenOptions
     /// - `module` mode will generate ES module import statements for helpers
     /// and export the render function as the default export.
@@ -282,6 +519,25 @@ pub struct CompilerOptions {
     /// used with `new Function(code)()` to generate a render function at runtime.
     /// @default 'function'
     pub mode: Option<CodegenMode>,
+    /// Always create vnodes with `createVNode` / `createBlock`, for runtimes
+    /// that don't ship the `createElementVNode` fast path.
+    /// @default false
+    pub use_create_vnode_only: Option<bool>,
+    /// Write the preamble, i.e. the helper declarations and hoisted values, to
+    /// `CodegenResult.preamble` instead of the start of `code`.
+    /// @default false
+    pub separate_preamble: Option<bool>,
+    // ParserOptions & TransformOptions
+    /// Preserve Vue 2 template behaviors for migrating projects, e.g. rewrite
+    /// `v-bind:prop.sync` to a `v-model:prop` style update handler.
+    /// @default false
+    pub compat: Option<bool>,
+    /// The mode to parse the template in. In `ParseMode::SFC` the root level
+    /// `<template>`s are the blocks of a single-file component.
+    /// @default ParseMode::BASE
+    pub parse_mode: Option<ParseMode>,
+    pub error_handling_options: Box<dyn ErrorHandlingOptions>,
+
     /// Global compile-time constants
     pub global_compile_time_constants: GlobalCompileTimeConstants,
 }
@@ -292,34 +548,145 @@ impl Default for CompilerOptions {
             ssr: None,
             in_ssr: None,
             filename: None,
+            prefix_identifiers: None,
+            is_built_in_component: None,
+            is_custom_element: None,
+            comments: None,
             node_transforms: None,
             directive_transforms: None,
+            hoist_static: None,
             mode: None,
+            use_create_vnode_only: None,
+            separate_preamble: None,
+            compat: None,
+            parse_mode: None,
+
+            error_handling_options: Box::new(DefaultErrorHandlingOptions),
 
             global_compile_time_constants: Default::default(),
         }
     }
 }
 
-impl Into<(ParserOptions, TransformOptions, CodegenOptions)> for CompilerOptions {
-    fn into(self) -> (ParserOptions, TransformOptions, CodegenOptions) {
+impl std::fmt::Debug for CompilerOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompilerOptions")
+            .field("ssr", &self.ssr)
+            .field("in_ssr", &self.in_ssr)
+            .field("filename", &self.filename)
+            .field("prefix_identifiers", &self.prefix_identifiers)
+            .field(
+                "is_built_in_component",
+                &"Option<Box<dyn Fn(&String) -> Option<String>>>",
+            )
+            .field(
+                "is_custom_element",
+                &"Option<Box<dyn Fn(&String) -> Option<bool>>>",
+            )
+            .field("comments", &self.comments)
+            .field("node_transforms", &self.node_transforms)
+            .field("directive_transforms", &self.directive_transforms)
+            .field("mode", &self.mode)
+            .field("use_create_vnode_only", &self.use_create_vnode_only)
+            .field("separate_preamble", &self.separate_preamble)
+            .field("compat", &self.compat)
+            .field("hoist_static", &self.hoist_static)
+            .field("parse_mode", &self.parse_mode)
+            .field("error_handling_options", &self.error_handling_options)
+            .field(
+                "global_compile_time_constants",
+                &self.global_compile_time_constants,
+            )
+            .finish()
+    }
+}
+
+impl CompilerOptions {
+    /// Split into the options of each compile phase, so that `base_parse`,
+    /// `transform` and `generate` can be called separately with one config.
+    pub fn split(self) -> (ParserOptions, TransformOptions, CodegenOptions) {
+        // shared by the parser and the transforms
+        let is_custom_element: Option<Rc<IsCustomElement>> = self.is_custom_element.map(Rc::from);
+        let share_is_custom_element = || {
+            is_custom_element.clone().map(|is_custom_element| {
+                Box::new(move |tag: &String| is_custom_element(tag)) as Box<IsCustomElement>
+            })
+        };
+        let is_built_in_component: Option<Rc<IsBuiltInComponent>> =
+            self.is_built_in_component.map(Rc::from);
+        let share_is_built_in_component = || {
+            is_built_in_component.clone().map(|is_built_in_component| {
+                Box::new(move |tag: &String| is_built_in_component(tag)) as Box<IsBuiltInComponent>
+            })
+        };
+        let error_handling_options = SharedErrorHandlingOptions::new(self.error_handling_options);
+
         (
             ParserOptions {
-                global_compile_time_constants: self.global_compile_time_constants,
-                ..Default::default()
+                prefix_identifiers: self.prefix_identifiers,
+                is_built_in_component: share_is_built_in_component(),
+                is_custom_element: share_is_custom_element(),
+                comments: Some(
+                    self.comments
+                        .unwrap_or(self.global_compile_time_constants.__dev__),
+                ),
+                compat: self.compat,
+                parse_mode: self.parse_mode.clone().unwrap_or(ParseMode::BASE),
+                error_handling_options: Box::new(error_handling_options.clone()),
+                ..ParserOptions::default_with_global_compile_time_constants(
+                    self.global_compile_time_constants,
+                )
             },
             TransformOptions {
                 ssr: self.ssr,
                 in_ssr: self.in_ssr,
+                prefix_identifiers: self.prefix_identifiers,
+                is_built_in_component: share_is_built_in_component(),
+                is_custom_element: share_is_custom_element(),
                 node_transforms: self.node_transforms,
                 directive_transforms: self.directive_transforms,
+                compat: self.compat,
+                hoist_static: self.hoist_static,
+                parse_mode: self.parse_mode,
+                error_handling_options: Box::new(error_handling_options),
                 global_compile_time_constants: self.global_compile_time_constants,
             },
             CodegenOptions {
+                prefix_identifiers: self.prefix_identifiers,
+                ssr: self.ssr,
+                in_ssr: self.in_ssr,
                 mode: self.mode,
+                use_create_vnode_only: self.use_create_vnode_only,
+                separate_preamble: self.separate_preamble,
                 global_compile_time_constants: self.global_compile_time_constants,
                 ..Default::default()
             },
         )
     }
 }
+
+/// Forwards the errors of every compile phase to one handler, so that it can be
+/// shared between the parser and the transforms.
+#[derive(Debug, Clone)]
+pub struct SharedErrorHandlingOptions(Rc<RefCell<Box<dyn ErrorHandlingOptions>>>);
+
+impl SharedErrorHandlingOptions {
+    pub fn new(error_handling_options: Box<dyn ErrorHandlingOptions>) -> Self {
+        Self(Rc::new(RefCell::new(error_handling_options)))
+    }
+}
+
+impl ErrorHandlingOptions for SharedErrorHandlingOptions {
+    fn on_warn(&mut self, warning: CompilerError) {
+        self.0.borrow_mut().on_warn(warning);
+    }
+    fn on_error(&mut self, error: CompilerError) {
+        self.0.borrow_mut().on_error(error);
+    }
+}
+
+impl Into<(ParserOptions, TransformOptions, CodegenOptions)> for CompilerOptions {
+    fn into(self) -> (ParserOptions, TransformOptions, CodegenOptions) {
+        self.split()
+    }
+}
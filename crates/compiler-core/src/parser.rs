@@ -4,11 +4,13 @@ use crate::{
         ForParseResult, Namespaces, NodeTypes, PlainElementNode, RootNode, SimpleExpressionNode,
         SourceLocation, TemplateChildNode, TextNode,
     },
+    entities::{DecodingMode, decode_html},
     errors::{CompilerError, ErrorCodes},
     options::{ParserOptions, Whitespace},
     tokenizer::{CharCodes, QuoteType, State, Tokenizer, is_whitespace, to_char_codes},
     utils::{
-        GlobalCompileTimeConstants, is_all_whitespace, is_core_component, is_v_pre, match_for_alias,
+        GlobalCompileTimeConstants, check_expression_syntax, is_all_whitespace,
+        is_simple_identifier, is_v_pre, match_for_alias, resolve_built_in_component,
     },
 };
 
@@ -39,7 +41,7 @@ impl<'a> Tokenizer<'a> {
     fn look_ahead(&self, index: usize, c: u32) -> usize {
         let buffer_len = self.buffer.len();
         for (i, c2) in self.buffer.split_at(index).1.iter().enumerate() {
-            if i >= buffer_len - 1 {
+            if index + i >= buffer_len - 1 {
                 return index + i;
             }
             if *c2 as u32 == c {
@@ -67,17 +69,18 @@ impl<'a> Tokenizer<'a> {
 
         if el.tag() == "component"
             || is_upper_case(el.tag().chars().nth(0).unwrap_or_default() as u32)
-            || is_core_component(el.tag()).is_some()
+            || resolve_built_in_component(
+                el.tag(),
+                self.context
+                    .current_options
+                    .is_built_in_component
+                    .as_deref(),
+            )
+            .is_some()
         {
             return true;
         }
 
-        if let Some(is_built_in_component) = &self.context.current_options.is_built_in_component {
-            if is_built_in_component(el.tag()).is_some() {
-                return true;
-            }
-        }
-
         if let Some(is_native_tag) = &self.context.current_options.is_native_tag {
             if !is_native_tag(el.tag()) {
                 return true;
@@ -129,7 +132,7 @@ impl<'a> Tokenizer<'a> {
 
         if self.in_sfc_root() {
             // in SFC mode, generate locations for root-level tags' inner content.
-            // currentOpenTag!.innerLoc = getLoc(end + 1, end + 1)
+            *current_open_tag.inner_loc_mut() = Some(self.get_loc(end + 1, None));
         }
         if current_open_tag.ns() == &(Namespaces::HTML as u32)
             && (self.context.current_options.is_pre_tag)(current_open_tag.tag())
@@ -137,7 +140,7 @@ impl<'a> Tokenizer<'a> {
             self.context.in_pre += 1;
         }
         if (self.context.current_options.is_void_tag)(current_open_tag.tag()) {
-            self.on_close_tag(&mut current_open_tag, end, None);
+            let current_open_tag = self.on_close_tag(current_open_tag, end, None);
             self.add_node(TemplateChildNode::Element(current_open_tag));
         } else {
             if current_open_tag.ns() == &(Namespaces::SVG as u32)
@@ -211,7 +214,12 @@ impl<'a> Tokenizer<'a> {
         }
     }
 
-    fn on_close_tag(&mut self, el: &mut ElementNode, end: usize, is_implied: Option<bool>) {
+    fn on_close_tag(
+        &mut self,
+        mut el: ElementNode,
+        end: usize,
+        is_implied: Option<bool>,
+    ) -> ElementNode {
         let is_implied = is_implied.unwrap_or_default();
 
         // attach end position
@@ -222,13 +230,52 @@ impl<'a> Tokenizer<'a> {
             self.set_loc_end(el.loc_mut(), self.look_ahead(end, CharCodes::Gt as u32) + 1)
         }
 
+        if self.in_sfc_root() {
+            // SFC root tag, resolve inner end
+            let inner_end = el
+                .children()
+                .last()
+                .map(|child| child.loc().end.offset)
+                .or_else(|| el.inner_loc().map(|inner_loc| inner_loc.start.offset));
+            if let Some(inner_end) = inner_end
+                && let Some(inner_loc) = el.inner_loc_mut().as_mut()
+            {
+                self.set_loc_end(inner_loc, inner_end);
+            }
+        }
+
         if !self.context.in_v_pre {
             if el.tag() == "slot" {
-                *el = el.to_slot_outlet();
-            } else if is_fragment_template(el) {
-                *el = el.to_template();
-            } else if self.is_component(el) {
-                *el = el.to_component();
+                el = el.to_slot_outlet();
+            } else if is_fragment_template(&el) {
+                el = el.to_template();
+            } else if self.is_component(&el) {
+                el = el.into_component();
+            }
+        }
+
+        if self.context.current_options.compat == Some(true)
+            && self
+                .context
+                .current_options
+                .global_compile_time_constants
+                .__dev__
+        {
+            // 2.x v-for has higher precedence than v-if on the same element
+            let has_dir = |name: &str| {
+                el.props().iter().any(
+                    |prop| matches!(prop, BaseElementProps::Directive(dir) if dir.name == name),
+                )
+            };
+            if has_dir("if") && has_dir("for") {
+                let loc = el.loc().clone();
+                self.context
+                    .current_options
+                    .error_handling_options
+                    .on_warn(CompilerError::new(
+                        ErrorCodes::XVIfVForPrecedence,
+                        Some(loc),
+                    ));
             }
         }
 
@@ -246,10 +293,20 @@ impl<'a> Tokenizer<'a> {
             self.in_v_pre = false;
             self.context.in_v_pre = false;
         }
+        if self.in_xml {
+            let in_html = match self.context.stack.first() {
+                Some(parent) => parent.ns() == &(Namespaces::HTML as u32),
+                None => self.context.current_options.ns == Namespaces::HTML,
+            };
+            if in_html {
+                self.in_xml = false;
+            }
+        }
+        el
     }
 
     fn create_exp(
-        &self,
+        &mut self,
         content: String,
         is_static: Option<bool>,
         loc: SourceLocation,
@@ -257,7 +314,7 @@ impl<'a> Tokenizer<'a> {
         parse_mode: Option<ExpParseMode>,
     ) -> SimpleExpressionNode {
         let parse_mode = parse_mode.unwrap_or(ExpParseMode::Normal);
-        let exp = SimpleExpressionNode::new(
+        let mut exp = SimpleExpressionNode::new(
             content.clone(),
             Some(is_static.unwrap_or_default()),
             Some(loc),
@@ -274,34 +331,28 @@ impl<'a> Tokenizer<'a> {
             && parse_mode != ExpParseMode::Skip
             && !content.trim().is_empty()
         {
-            // if (isSimpleIdentifier(content)) {
-            //     exp.ast = null // fast path
-            //     return exp
-            // }
-            // try {
-            //     const plugins = currentOptions.expressionPlugins
-            //     const options: BabelOptions = {
-            //     plugins: plugins ? [...plugins, 'typescript'] : ['typescript'],
-            //     }
-            //     if (parseMode === ExpParseMode.Statements) {
-            //     // v-on with multi-inline-statements, pad 1 char
-            //     exp.ast = parse(` ${content} `, options).program
-            //     } else if (parseMode === ExpParseMode.Params) {
-            //     exp.ast = parseExpression(`(${content})=>{}`, options)
-            //     } else {
-            //     // normal exp, wrap with parens
-            //     exp.ast = parseExpression(`(${content})`, options)
-            //     }
-            // } catch (e: any) {
-            //     exp.ast = false // indicate an error
-            //     emitError(ErrorCodes.X_INVALID_EXPRESSION, loc.start.offset, e.message)
-            // }
+            // there is no JS parser here, so only obvious syntax errors are
+            // caught instead of building `exp.ast`
+            if is_simple_identifier(&content) {
+                return exp;
+            }
+            let checked = check_expression_syntax(&content);
+            exp.ast_valid = Some(checked.is_ok());
+            if let Err(message) = checked {
+                let mut error =
+                    CompilerError::new(ErrorCodes::XInvalidExpression, Some(exp.loc.clone()));
+                error.message.push_str(&message);
+                self.context
+                    .current_options
+                    .error_handling_options
+                    .on_error(error);
+            }
         }
         exp
     }
 
     fn create_alias_expression(
-        &self,
+        &mut self,
         loc: &SourceLocation,
         content: String,
         offset: usize,
@@ -325,7 +376,7 @@ impl<'a> Tokenizer<'a> {
         )
     }
 
-    fn parse_for_expression(&self, input: &SimpleExpressionNode) -> Option<ForParseResult> {
+    fn parse_for_expression(&mut self, input: &SimpleExpressionNode) -> Option<ForParseResult> {
         let in_match = match_for_alias(&input.content);
 
         let Some(in_match) = in_match else {
@@ -334,7 +385,11 @@ impl<'a> Tokenizer<'a> {
 
         let (lhs, rhs) = in_match;
 
-        let Some(offset) = input.content.find(&rhs) else {
+        // the source may also appear in the alias, e.g. `list in list`
+        let Some(offset) = input.content[lhs.len()..]
+            .find(&rhs)
+            .map(|offset| offset + lhs.len())
+        else {
             unreachable!();
         };
         let mut result = ForParseResult {
@@ -398,6 +453,10 @@ impl<'a> Tokenizer<'a> {
         self.on_text(self.get_slice(start, end), start, end);
     }
 
+    pub fn ontextentity(&mut self, decoded: &str, start: usize, end: usize) {
+        self.on_text(decoded.to_string(), start, end);
+    }
+
     pub fn oninterpolation(&mut self, start: usize, end: usize) {
         if self.context.in_v_pre {
             return self.on_text(self.get_slice(start, end), start, end);
@@ -416,13 +475,13 @@ impl<'a> Tokenizer<'a> {
             }
             inner_end
         };
-        let exp = self.get_slice(inner_start, inner_end);
+        let mut exp = self.get_slice(inner_start, inner_end);
         // decode entities for backwards compat
         if exp.contains('&') {
             if self.context.global_compile_time_constants.__browser__ {
                 // exp = currentOptions.decodeEntities!(exp, false)
             } else {
-                // exp = decodeHTML(exp)
+                exp = decode_html(&exp, DecodingMode::Legacy);
             }
         }
 
@@ -452,6 +511,7 @@ impl<'a> Tokenizer<'a> {
             codegen_node: None,
             ssr_codegen_node: None,
             loc,
+            inner_loc: None,
         }))
     }
 
@@ -479,8 +539,8 @@ impl<'a> Tokenizer<'a> {
             }
             if found {
                 for j in 0..=index {
-                    let mut el = self.context.stack.remove(0);
-                    self.on_close_tag(&mut el, end, Some(j < index));
+                    let el = self.context.stack.remove(0);
+                    let el = self.on_close_tag(el, end, Some(j < index));
                     self.add_node(TemplateChildNode::Element(el));
                 }
             } else {
@@ -507,8 +567,8 @@ impl<'a> Tokenizer<'a> {
             .first()
             .is_some_and(|el| el.tag() == &name)
         {
-            let mut el = self.context.stack.remove(0);
-            self.on_close_tag(&mut el, end, None);
+            let el = self.context.stack.remove(0);
+            let el = self.on_close_tag(el, end, None);
             self.add_node(TemplateChildNode::Element(el));
         }
     }
@@ -532,7 +592,7 @@ impl<'a> Tokenizer<'a> {
         } else if raw == "#" {
             "slot".to_string()
         } else {
-            raw.split_at(2).1.to_string()
+            raw.strip_prefix("v-").unwrap_or(&raw).to_string()
         };
 
         if !self.context.in_v_pre && name.is_empty() {
@@ -581,8 +641,18 @@ impl<'a> Tokenizer<'a> {
         }
     }
 
+    /// A directive without a name, e.g. `v-:arg`, has been reported and kept as
+    /// a plain attribute, so its argument and modifiers are dropped.
+    fn is_nameless_dir(&self) -> bool {
+        !self.context.in_v_pre
+            && matches!(
+                self.context.current_prop,
+                Some(BaseElementProps::Attribute(_))
+            )
+    }
+
     pub fn ondirarg(&mut self, start: usize, end: usize) {
-        if start == end {
+        if start == end || self.is_nameless_dir() {
             return;
         }
         let arg = self.get_slice(start, end);
@@ -618,6 +688,9 @@ impl<'a> Tokenizer<'a> {
     }
 
     pub fn ondirmodifier(&mut self, start: usize, end: usize) {
+        if self.is_nameless_dir() {
+            return;
+        }
         let dir_mod = self.get_slice(start, end);
         if self.context.in_v_pre
             && let Some(prop) = &self.context.current_prop
@@ -680,6 +753,14 @@ impl<'a> Tokenizer<'a> {
         self.context.current_attr_end_index = Some(end);
     }
 
+    pub fn onattribentity(&mut self, decoded: &str, start: usize, end: usize) {
+        self.context.current_attr_value.push_str(decoded);
+        if self.context.current_attr_start_index.is_none() {
+            self.context.current_attr_start_index = Some(start);
+        }
+        self.context.current_attr_end_index = Some(end);
+    }
+
     pub fn onattribnameend(&mut self, end: usize) {
         let Some(current_prop) = &self.context.current_prop else {
             unreachable!();
@@ -729,7 +810,8 @@ impl<'a> Tokenizer<'a> {
                 ) {
                     // assign value
 
-                    // condense whitespaces in class
+                    // condense whitespaces in class, style is kept verbatim in the
+                    // AST and only normalized when it is turned into a prop
                     if let Some(prop) = &self.context.current_prop
                         && prop.name() == "class"
                     {
@@ -739,7 +821,7 @@ impl<'a> Tokenizer<'a> {
                                 .to_string();
                     }
 
-                    if quote == QuoteType::Unquoted && !self.context.current_attr_value.is_empty() {
+                    if quote == QuoteType::Unquoted && self.context.current_attr_value.is_empty() {
                         self.emit_error(ErrorCodes::MissingAttributeValue, end);
                     }
 
@@ -844,7 +926,8 @@ impl<'a> Tokenizer<'a> {
                 }
             }
 
-            let Some(current_prop) = &self.context.current_prop else {
+            // the prop is complete, move it onto the element
+            let Some(current_prop) = self.context.current_prop.take() else {
                 unreachable!();
             };
 
@@ -852,7 +935,7 @@ impl<'a> Tokenizer<'a> {
                 || current_prop.name() != "pre"
             {
                 if let Some(current_open_tag) = self.context.current_open_tag.as_mut() {
-                    current_open_tag.props_mut().push(current_prop.clone());
+                    current_open_tag.props_mut().push(current_prop);
                 }
             }
         }
@@ -862,9 +945,45 @@ impl<'a> Tokenizer<'a> {
     }
 
     pub fn oncomment(&mut self, start: usize, end: usize) {
+        // `<!-->` and `<!--->` end before their content starts
+        let abrupt = end < start;
+        let incorrectly_closed = self.context.current_input[end..].starts_with("--!>");
+        if self.context.global_compile_time_constants.__dev__
+            || !self.context.global_compile_time_constants.__browser__
+        {
+            if abrupt {
+                self.emit_error(ErrorCodes::AbruptClosingOfEmptyComment, start - 4);
+            } else {
+                let content = &self.context.current_input[start..end];
+                let nested: Vec<usize> = content
+                    .match_indices("<!--")
+                    // `<!--<!-->` closes right after the inner `<!--`
+                    .filter(|(index, _)| index + 4 < content.len())
+                    .map(|(index, _)| start + index)
+                    .collect();
+                for index in nested {
+                    self.emit_error(ErrorCodes::NestedComment, index);
+                }
+            }
+            if incorrectly_closed {
+                self.emit_error(ErrorCodes::IncorrectlyClosedComment, end);
+            }
+        }
         if self.context.current_options.comments.unwrap_or_default() {
-            let content = self.get_slice(start, end);
-            let loc = self.get_loc(start - 4, Some(end + 3));
+            let content = if abrupt {
+                String::new()
+            } else {
+                self.get_slice(start, end)
+            };
+            // a comment left open at EOF has no closing `-->`
+            let end_len = if end == self.context.current_input.len() {
+                0
+            } else if incorrectly_closed {
+                4
+            } else {
+                3
+            };
+            let loc = self.get_loc(start - 4, Some(end + end_len));
             self.add_node(TemplateChildNode::new_comment(content, loc));
         }
     }
@@ -893,6 +1012,9 @@ impl<'a> Tokenizer<'a> {
                         self.emit_error(ErrorCodes::EOFInComment, end);
                     }
                 }
+                State::InRCDATA if self.current_sequence == self.sequences.script_end => {
+                    self.emit_error(ErrorCodes::EOFInScriptHtmlCommentLikeText, end);
+                }
                 State::InTagName
                 | State::InSelfClosingTag
                 | State::InClosingTagName
@@ -915,9 +1037,10 @@ impl<'a> Tokenizer<'a> {
             }
         }
 
-        let stack = self.context.stack.drain(..).collect::<Vec<ElementNode>>();
-        for mut item in stack {
-            self.on_close_tag(&mut item, end - 1, None);
+        // close the unclosed elements innermost first, each into its parent
+        while !self.context.stack.is_empty() {
+            let item = self.context.stack.remove(0);
+            let item = self.on_close_tag(item, end - 1, None);
             let offset = item.loc().start.offset;
             self.add_node(TemplateChildNode::Element(item));
             self.emit_error(ErrorCodes::XMissingEndTag, offset);
@@ -1074,14 +1197,17 @@ enum ExpParseMode {
     Skip,
 }
 
+/// Parses a template into its AST. The input is tokenized in place, without a
+/// copy; there is no chunked or streaming API.
 pub fn base_parse(input: &str, options: Option<ParserOptions>) -> RootNode {
     let options = options.unwrap_or_default();
 
     let global_compile_time_constants = options.global_compile_time_constants.clone();
+    let source = options.store_source.then(|| input.to_string());
 
     let context = ParserContext {
         current_options: options,
-        current_root: RootNode::new(vec![], Some(input.to_string())),
+        current_root: RootNode::new(vec![], source),
 
         current_input: input,
         current_open_tag: None,
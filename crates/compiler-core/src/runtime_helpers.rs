@@ -1,3 +1,5 @@
+/// Declares a runtime helper whose `Display` is its name in the runtime.
+#[macro_export]
 macro_rules! symbol {
     (pub struct $StructName:ident : $lit:literal) => {
         pub struct $StructName;
@@ -13,6 +15,8 @@ macro_rules! symbol {
 symbol!(pub struct Fragment: "Fragment");
 symbol!(pub struct Teleport: "Teleport");
 symbol!(pub struct Suspense: "Suspense");
+symbol!(pub struct KeepAlive: "KeepAlive");
+symbol!(pub struct BaseTransition: "BaseTransition");
 
 symbol!(pub struct OpenBlock: "openBlock");
 symbol!(pub struct CreateBlock: "createBlock");
@@ -23,11 +27,18 @@ symbol!(pub struct CreateComment: "createCommentVNode");
 symbol!(pub struct CreateText: "createTextVNode");
 symbol!(pub struct CreateStatic: "createStaticVNode");
 symbol!(pub struct ResolveComponent: "resolveComponent");
+symbol!(pub struct ResolveDynamicComponent: "resolveDynamicComponent");
 symbol!(pub struct ResolveDirective: "resolveDirective");
+symbol!(pub struct WithDirectives: "withDirectives");
 
 symbol!(pub struct RenderList: "renderList");
+symbol!(pub struct RenderSlot: "renderSlot");
 
 symbol!(pub struct ToDisplayString: "toDisplayString");
+symbol!(pub struct MergeProps: "mergeProps");
 symbol!(pub struct NormalizeClass: "normalizeClass");
+symbol!(pub struct NormalizeStyle: "normalizeStyle");
+symbol!(pub struct NormalizeProps: "normalizeProps");
+symbol!(pub struct GuardReactiveProps: "guardReactiveProps");
 
 symbol!(pub struct SetBlockTracking: "setBlockTracking");
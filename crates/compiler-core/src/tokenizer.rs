@@ -1,4 +1,9 @@
-use crate::{ast::Position, errors::ErrorCodes, parser::ParserContext};
+use crate::{
+    ast::Position,
+    entities::{DecodingMode, decode_entity},
+    errors::ErrorCodes,
+    parser::ParserContext,
+};
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum ParseMode {
@@ -203,7 +208,7 @@ pub struct Sequences {
     /// `-->`
     comment_end: Vec<u32>,
     /// `</script`
-    script_end: Vec<u32>,
+    pub script_end: Vec<u32>,
     /// `</style`
     style_end: Vec<u32>,
     /// `</title`
@@ -229,8 +234,9 @@ impl Sequences {
 pub struct Tokenizer<'a> {
     /// The current state the tokenizer is in.
     pub state: State,
-    /// The read buffer.
-    pub buffer: Vec<char>,
+    /// The bytes of the template, borrowed rather than copied so that large
+    /// templates are not held in memory twice. Indices are byte offsets.
+    pub buffer: &'a [u8],
     /// The beginning of the section that is currently being read.
     /// js type: -1 or usize
     pub section_start: Option<usize>,
@@ -267,7 +273,7 @@ impl<'a> Tokenizer<'a> {
     pub fn new(context: ParserContext<'a>) -> Self {
         Self {
             state: State::Text,
-            buffer: Vec::new(),
+            buffer: &[],
             section_start: Some(0),
             index: 0,
             entity_start: 0,
@@ -296,15 +302,13 @@ impl<'a> Tokenizer<'a> {
     /// processed index, so all the newlines up to this index should have been
     /// recorded.
     pub fn get_pos(&self, index: usize) -> Position {
-        let mut line = 1;
-        let mut column = index + 1;
-        for (i, newline_index) in self.newlines.iter().rev().enumerate() {
-            if &index > newline_index {
-                line = i + 2;
-                column = index - newline_index;
-                break;
-            }
-        }
+        // newlines are recorded in order, count the ones before `index`
+        let lines_before = self.newlines.partition_point(|newline| *newline < index);
+        let line = lines_before + 1;
+        let column = match lines_before.checked_sub(1) {
+            Some(i) => index - self.newlines[i],
+            None => index + 1,
+        };
         Position {
             column,
             line,
@@ -312,8 +316,9 @@ impl<'a> Tokenizer<'a> {
         }
     }
 
+    /// Returns the byte after the current one, or `0` at the end of the buffer.
     fn peek(&self) -> u32 {
-        self.buffer[self.index + 1] as u32
+        self.buffer.get(self.index + 1).map_or(0, |c| *c as u32)
     }
 
     fn state_text(&mut self, c: u32) {
@@ -541,6 +546,24 @@ impl<'a> Tokenizer<'a> {
                 self.section_start = Some(self.index + 1);
                 self.state = State::Text;
             }
+        } else if self.sequence_index == 2
+            && c == CharCodes::ExclamationMark
+            && self.current_sequence == self.sequences.comment_end
+            && self
+                .section_start
+                .is_some_and(|start| self.index >= start + 2)
+            && self.buffer.get(self.index + 1).copied() == Some(CharCodes::Gt as u8)
+        {
+            // Incorrectly closed comments (eg. <!-- a --!>) end here as well
+            let Some(section_start) = self.section_start else {
+                unreachable!()
+            };
+            self.oncomment(section_start, self.index - 2);
+
+            self.index += 1;
+            self.sequence_index = 0;
+            self.section_start = Some(self.index + 1);
+            self.state = State::Text;
         } else if self.sequence_index == 0 {
             // Fast-forward to the first character of the sequence
             if self.fast_forward_to(self.current_sequence[0]) {
@@ -618,6 +641,8 @@ impl<'a> Tokenizer<'a> {
                 unreachable!();
             };
             let tag = self.get_slice(section_start, self.index);
+            // <template> is only RAWTEXT with a preprocessor lang, which the parser
+            // decides in `onattribend`, i.e. before the open tag ends
             if tag != "template" {
                 self.enter_rc_data(to_char_codes(format!("</{tag}")), 0);
             }
@@ -694,7 +719,7 @@ impl<'a> Tokenizer<'a> {
             {
                 self.onerr(ErrorCodes::UnexpectedSolidusInTag, self.index);
             }
-        } else if c == CharCodes::Lt && self.peek() == CharCodes::Slash {
+        } else if self.is_close_tag_start(c) {
             // special handling for </ appearing in open tag state
             // this is different from standard HTML parsing but makes practical sense
             // especially for parsing intermediate input state in IDEs.
@@ -747,7 +772,7 @@ impl<'a> Tokenizer<'a> {
     }
 
     fn state_in_attr_name(&mut self, c: u32) {
-        if c == CharCodes::Eq || is_end_of_tag_section(c) {
+        if self.is_end_of_attr_name(c) {
             let Some(section_start) = self.section_start else {
                 unreachable!();
             };
@@ -765,7 +790,7 @@ impl<'a> Tokenizer<'a> {
         let Some(section_start) = self.section_start else {
             unreachable!();
         };
-        if c == CharCodes::Eq || is_end_of_tag_section(c) {
+        if self.is_end_of_attr_name(c) {
             self.ondirname(section_start, self.index);
             self.handle_attr_name_end(c);
         } else if c == CharCodes::Colon {
@@ -780,7 +805,7 @@ impl<'a> Tokenizer<'a> {
     }
 
     fn state_in_dir_arg(&mut self, c: u32) {
-        if c == CharCodes::Eq || is_end_of_tag_section(c) {
+        if self.is_end_of_attr_name(c) {
             let Some(section_start) = self.section_start else {
                 unreachable!();
             };
@@ -816,7 +841,7 @@ impl<'a> Tokenizer<'a> {
     }
 
     fn state_in_dir_modifier(&mut self, c: u32) {
-        if c == CharCodes::Eq || is_end_of_tag_section(c) {
+        if self.is_end_of_attr_name(c) {
             let Some(section_start) = self.section_start else {
                 unreachable!()
             };
@@ -831,6 +856,16 @@ impl<'a> Tokenizer<'a> {
         }
     }
 
+    /// Unlike `=` and the end of the tag section, `<` doesn't end an
+    /// attribute name: `<a x</b>` has the attributes `x<` and `b`.
+    fn is_end_of_attr_name(&self, c: u32) -> bool {
+        c == CharCodes::Eq || is_end_of_tag_section(c)
+    }
+
+    fn is_close_tag_start(&self, c: u32) -> bool {
+        c == CharCodes::Lt && self.peek() == CharCodes::Slash
+    }
+
     fn handle_attr_name_end(&mut self, c: u32) {
         self.section_start = Some(self.index);
         self.state = State::AfterAttrName;
@@ -1016,39 +1051,54 @@ impl<'a> Tokenizer<'a> {
             self.base_state = self.state.clone();
             self.state = State::InEntity;
             self.entity_start = self.index;
-            // this.entityDecoder!.startEntity(
-            //   this.baseState === State.Text || this.baseState === State.InRCDATA
-            //     ? DecodingMode.Legacy
-            //     : DecodingMode.Attribute,
-            // )
-            todo!()
         }
     }
 
     fn state_in_entity(&mut self) {
         if !self.context.global_compile_time_constants.__browser__ {
-            // let length = this.entityDecoder!.write(this.buffer, this.index)
-
-            // If `length` is positive, we are done with the entity.
-            // if length >= 0 {
-            //     self.state = self.base_state;
-
-            //     if length == 0 {
-            //         self.index = self.entity_start;
-            //     }
-            // } else {
-            //     // Mark buffer as consumed.
-            //     self.index = self.buffer.len() - 1;
-            // }
-            todo!()
+            let mode = if self.base_state == State::Text || self.base_state == State::InRCDATA {
+                DecodingMode::Legacy
+            } else {
+                DecodingMode::Attribute
+            };
+            // the whole input is buffered, so the decoder never needs more data
+            self.state = self.base_state.clone();
+            match decode_entity(self.buffer, self.entity_start, mode) {
+                Some((decoded, consumed)) => self.emit_code_point(&decoded, consumed),
+                // not an entity, re-consume the `&` as regular data
+                None => self.index = self.entity_start,
+            }
+        }
+    }
+
+    fn emit_code_point(&mut self, decoded: &str, consumed: usize) {
+        let Some(section_start) = self.section_start else {
+            unreachable!();
+        };
+        let entity_start = self.entity_start;
+        let entity_end = entity_start + consumed;
+        if self.base_state != State::Text && self.base_state != State::InRCDATA {
+            if section_start < entity_start {
+                self.onattribdata(section_start, entity_start);
+            }
+            self.section_start = Some(entity_end);
+            self.index = entity_end - 1;
+            self.onattribentity(decoded, entity_start, entity_end);
+        } else {
+            if section_start < entity_start {
+                self.ontext(section_start, entity_start);
+            }
+            self.section_start = Some(entity_end);
+            self.index = entity_end - 1;
+            self.ontextentity(decoded, entity_start, entity_end);
         }
     }
 
     /// Iterates through the buffer, calling the function corresponding to the current state.
     ///
     /// States that are more likely to be hit are higher up, as a performance improvement.
-    pub fn parse(&mut self, input: &str) {
-        self.buffer = input.chars().collect();
+    pub fn parse(&mut self, input: &'a str) {
+        self.buffer = input.as_bytes();
 
         while self.index < self.buffer.len() {
             let c = self.buffer[self.index] as u32;
@@ -1193,9 +1243,8 @@ impl<'a> Tokenizer<'a> {
     fn finish(&mut self) {
         if !self.context.global_compile_time_constants.__browser__ && self.state == State::InEntity
         {
-            //   this.entityDecoder!.end()
+            // a trailing `&` without anything to decode
             self.state = self.base_state.clone();
-            todo!();
         }
 
         self.handle_trailing_data();
@@ -1234,6 +1283,7 @@ impl<'a> Tokenizer<'a> {
             | State::InAttrValueSq
             | State::InAttrValueDq
             | State::InAttrValueNq
+            | State::InSelfClosingTag
             | State::InClosingTagName => {
                 /*
                  * If we are currently in an opening or closing tag, us not calling the
@@ -1,13 +1,20 @@
 use crate::{
     ast::{
-        BaseElementProps, DirectiveNode, ElementNode, ElementTypes, JSChildNode, NodeTypes,
-        Property, RootCodegenNode, RootNode, TemplateChildNode, VNodeCall, VNodeCallChildren,
-        VNodeCallTag, convert_to_block,
+        BaseElementProps, CacheExpression, ComponentNodeCodegenNode, ConstantTypes, DirectiveNode,
+        ElementNode, ElementTypes, ExpressionNode, JSChildNode, NodeTypes,
+        PlainElementNodeCodegenNode, Property, RootCodegenNode, RootNode, SimpleExpressionNode,
+        TemplateChildNode, TemplateNodeCodegenNode, VNodeCall, VNodeCallChildren, VNodeCallTag,
+        convert_to_block,
     },
-    options::TransformOptions,
+    errors::CompilerError,
+    options::{ErrorHandlingOptions, IsBuiltInComponent, IsCustomElement, TransformOptions},
     runtime_helpers::{CreateComment, Fragment, ToDisplayString},
-    transforms::cache_static::get_single_element_root,
-    utils::GlobalCompileTimeConstants,
+    tokenizer::ParseMode,
+    transforms::{
+        cache_static::{get_single_element_root, hoist_static},
+        transform_expression::param_identifiers,
+    },
+    utils::{GlobalCompileTimeConstants, resolve_built_in_component},
 };
 use std::{collections::HashMap, fmt::Debug};
 use vue_compiler_shared::PatchFlags;
@@ -29,10 +36,10 @@ impl<'a> TransformNode<'a> {
     pub fn children(&self) -> Option<&Vec<TemplateChildNode>> {
         match self {
             Self::Root(node) => Some(&node.children),
-            Self::TemplateChild(node) => match node {
-                TemplateChildNode::Element(node) => Some(node.children()),
-                _ => None,
-            },
+            Self::TemplateChild(TemplateChildNode::Element(node)) => Some(node.children()),
+            Self::TemplateChild(TemplateChildNode::IfBranch(node)) => Some(&node.children),
+            Self::TemplateChild(TemplateChildNode::For(node)) => Some(&node.children),
+            _ => None,
         }
     }
 
@@ -58,9 +65,14 @@ pub trait NodeTransformState: Debug {
         let _ = context;
     }
 
-    fn transform(&mut self, node: &mut TransformNode, context: &mut TransformContext) {
+    fn transform(
+        &mut self,
+        node: &mut TransformNode,
+        context: &mut TransformContext,
+    ) -> TransformControl {
         let _ = node;
         let _ = context;
+        TransformControl::Continue
     }
 
     fn pre_exit(&mut self, node: &mut TransformNode, context: &mut TransformContext) {
@@ -68,12 +80,31 @@ pub trait NodeTransformState: Debug {
         let _ = context;
     }
 
+    /// Called when the child at `index` of the node this state was created
+    /// for has been removed with [`TransformContext::remove_node`].
+    fn on_node_removed(&mut self, index: usize, context: &mut TransformContext) {
+        let _ = index;
+        let _ = context;
+    }
+
     fn exit(&mut self, node: &mut TransformNode, context: &mut TransformContext) {
         let _ = node;
         let _ = context;
     }
 }
 
+/// Returned by [`NodeTransformState::transform`] to tell the traversal how to
+/// proceed with the current node.
+#[derive(Debug)]
+pub enum TransformControl {
+    /// Run the remaining transforms and traverse the children as usual.
+    Continue,
+    /// Replace the current node with the given one and stop there: the
+    /// remaining transforms, the children and the exit callbacks are skipped.
+    /// The root node cannot be replaced.
+    Replaced(Box<TemplateChildNode>),
+}
+
 /// There are two types of transforms:
 ///
 /// - NodeTransform:
@@ -86,8 +117,8 @@ pub trait DirectiveTransform: Debug {
     fn transform(
         &mut self,
         dir: &DirectiveNode,
-        node: &ElementNode,
-        context: &TransformContext,
+        node: &mut ElementNode,
+        context: &mut TransformContext,
     ) -> DirectiveTransformResult;
 
     fn clone_box(&self) -> Box<dyn DirectiveTransform>;
@@ -104,13 +135,64 @@ pub struct DirectiveTransformResult {
     pub props: Vec<Property>,
 }
 
+/// A node whose children are being traversed, as seen from its descendants.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TransformAncestor {
+    pub type_: NodeTypes,
+    /// The tag of element ancestors.
+    pub tag: Option<String>,
+    pub tag_type: Option<ElementTypes>,
+}
+
+impl TransformAncestor {
+    fn new(node: &TransformNode) -> Self {
+        match node {
+            TransformNode::TemplateChild(TemplateChildNode::Element(node)) => Self {
+                type_: node.type_(),
+                tag: Some(node.tag().clone()),
+                tag_type: Some(node.tag_type()),
+            },
+            _ => Self {
+                type_: node.type_(),
+                tag: None,
+                tag_type: None,
+            },
+        }
+    }
+}
+
+/// How deeply the node being transformed is nested in each kind of scope.
+#[derive(Debug, Default, Clone)]
+pub struct TransformScopes {
+    pub v_for: usize,
+}
+
 pub struct TransformContext {
     pub ssr: bool,
     pub in_ssr: bool,
+    pub prefix_identifiers: bool,
+    pub is_built_in_component: Option<Box<IsBuiltInComponent>>,
+    pub is_custom_element: Option<Box<IsCustomElement>>,
     pub node_transforms: Vec<NodeTransform>,
     pub directive_transforms: HashMap<String, Box<dyn DirectiveTransform>>,
-
+    pub compat: bool,
+    pub parse_mode: ParseMode,
+
+    pub scopes: TransformScopes,
+    /// The identifiers declared by the `v-for` aliases in scope, which are not
+    /// prefixed. Counted, nested scopes can declare the same name.
+    pub identifiers: HashMap<String, usize>,
+    pub in_v_once: bool,
+    ancestors: Vec<TransformAncestor>,
+    node_removed: bool,
+    node_replacement: Option<TemplateChildNode>,
     helpers: ::indexmap::IndexMap<String, usize>,
+    hoists: Vec<Option<JSChildNode>>,
+    cached: Vec<Option<CacheExpression>>,
+    pub components: ::indexmap::IndexSet<String>,
+    pub directives: ::indexmap::IndexSet<String>,
+
+    error_handling_options: Box<dyn ErrorHandlingOptions>,
 
     pub global_compile_time_constants: GlobalCompileTimeConstants,
 }
@@ -120,15 +202,74 @@ impl TransformContext {
         Self {
             ssr: options.ssr.unwrap_or_default(),
             in_ssr: options.in_ssr.unwrap_or_default(),
+            prefix_identifiers: options.prefix_identifiers.unwrap_or_default(),
+            is_built_in_component: options.is_built_in_component,
+            is_custom_element: options.is_custom_element,
             node_transforms: options.node_transforms.unwrap_or_default(),
             directive_transforms: options.directive_transforms.unwrap_or_default(),
-
+            compat: options.compat.unwrap_or_default(),
+            parse_mode: options.parse_mode.unwrap_or(ParseMode::BASE),
+
+            scopes: Default::default(),
+            identifiers: HashMap::new(),
+            in_v_once: false,
+            ancestors: Vec::new(),
+            node_removed: false,
+            node_replacement: None,
             helpers: Default::default(),
+            hoists: Vec::new(),
+            cached: Vec::new(),
+            components: Default::default(),
+            directives: Default::default(),
+
+            error_handling_options: options.error_handling_options,
 
             global_compile_time_constants: options.global_compile_time_constants,
         }
     }
 
+    /// The parent of the node being transformed, `None` for the root.
+    pub fn parent(&self) -> Option<&TransformAncestor> {
+        self.ancestors.last()
+    }
+
+    /// The ancestors of the node being transformed, starting from the root.
+    pub fn ancestors(&self) -> &[TransformAncestor] {
+        &self.ancestors
+    }
+
+    /// Removes the node being transformed from its parent. The remaining
+    /// transforms, its children and the exit callbacks are skipped.
+    pub fn remove_node(&mut self) {
+        self.node_removed = true;
+    }
+
+    /// Replaces the node being transformed, the remaining transforms and the
+    /// traversal continue with `node`.
+    pub fn replace_node(&mut self, node: TemplateChildNode) {
+        self.node_replacement = Some(node);
+    }
+
+    /// Applies a pending [`replace_node`](Self::replace_node), returning
+    /// whether the node has been removed.
+    fn take_node_change(&mut self, node: &mut TransformNode) -> bool {
+        if let Some(new_node) = self.node_replacement.take() {
+            let TransformNode::TemplateChild(node) = node else {
+                unreachable!("the root node cannot be replaced");
+            };
+            **node = new_node;
+        }
+        if self.node_removed {
+            assert!(
+                !matches!(node, TransformNode::Root(_)),
+                "the root node cannot be removed"
+            );
+            self.node_removed = false;
+            return true;
+        }
+        false
+    }
+
     pub fn helper(&mut self, name: String) -> String {
         if let Some(count) = self.helpers.get_mut(&name) {
             *count += 1;
@@ -138,6 +279,79 @@ impl TransformContext {
         name
     }
 
+    /// Registers a runtime helper for custom transforms, returning the name it
+    /// is imported as in the generated code, e.g. `_myHelper`.
+    pub fn register_helper(&mut self, name: &str) -> String {
+        format!("_{}", self.helper(name.to_string()))
+    }
+
+    /// Lifts `exp` out of the render function, returning the `_hoisted_N`
+    /// identifier to reference it with. An already hoisted reference is
+    /// returned as is.
+    pub fn hoist(&mut self, exp: JSChildNode) -> SimpleExpressionNode {
+        let exp = match exp {
+            JSChildNode::Simple(exp) if exp.hoisted.is_some() => return exp,
+            exp => exp,
+        };
+        let mut identifier = SimpleExpressionNode::new(
+            format!("_hoisted_{}", self.hoists.len() + 1),
+            Some(false),
+            None,
+            Some(ConstantTypes::CanCache),
+        );
+        identifier.hoisted = Some(Box::new(exp.clone()));
+        self.hoists.push(Some(exp));
+        identifier
+    }
+
+    /// Allocates the next `_cache` slot for `value`. Values cached by `v-once`
+    /// are vnodes which pause tracking while they are created.
+    pub fn cache(&mut self, value: JSChildNode, is_v_once: bool) -> CacheExpression {
+        let cache_exp =
+            CacheExpression::new(self.cached.len(), value, Some(is_v_once), Some(is_v_once));
+        self.cached.push(Some(cache_exp.clone()));
+        cache_exp
+    }
+
+    /// Brings the identifiers declared by `exp`, e.g. `item` or `{ id, name }`,
+    /// into scope.
+    pub fn add_identifiers(&mut self, exp: &ExpressionNode) {
+        for id in param_identifiers(exp) {
+            *self.identifiers.entry(id).or_default() += 1;
+        }
+    }
+
+    pub fn remove_identifiers(&mut self, exp: &ExpressionNode) {
+        for id in param_identifiers(exp) {
+            if let Some(count) = self.identifiers.get_mut(&id) {
+                *count -= 1;
+                if *count == 0 {
+                    self.identifiers.remove(&id);
+                }
+            }
+        }
+    }
+
+    pub fn is_built_in_component(&self, tag: &String) -> Option<String> {
+        self.is_built_in_component
+            .as_ref()
+            .and_then(|is_built_in_component| is_built_in_component(tag))
+    }
+
+    /// Resolves `tag` to the runtime symbol of a core or platform built-in
+    /// component, the same way the parser types it as a component.
+    pub fn resolve_built_in_component(&self, tag: &String) -> Option<String> {
+        resolve_built_in_component(tag, self.is_built_in_component.as_deref())
+    }
+
+    pub fn on_error(&mut self, error: CompilerError) {
+        self.error_handling_options.on_error(error);
+    }
+
+    pub fn on_warn(&mut self, warning: CompilerError) {
+        self.error_handling_options.on_warn(warning);
+    }
+
     pub fn remove_helper(&mut self, name: &str) {
         let count = self.helpers.get_mut(name);
         if let Some(count) = count {
@@ -150,11 +364,18 @@ impl TransformContext {
         }
     }
 
-    pub fn traverse_node(&mut self, mut node: TransformNode) {
+    /// Runs the node transforms over `node` and its children. Returns whether
+    /// the node has been removed, in which case the caller drops it from its
+    /// parent.
+    pub fn traverse_node(&mut self, mut node: TransformNode) -> bool {
         // apply transform plugins
         let mut node_transforms = vec![];
         for node_transform in self.node_transforms.clone() {
-            if let Some(node_transform) = node_transform(&node, self) {
+            let node_transform = node_transform(&node, self);
+            if self.take_node_change(&mut node) {
+                return true;
+            }
+            if let Some(node_transform) = node_transform {
                 node_transforms.push(node_transform);
             }
         }
@@ -163,9 +384,32 @@ impl TransformContext {
         }
 
         for node_transform in &mut node_transforms {
-            node_transform.transform(&mut node, self);
+            let control = node_transform.transform(&mut node, self);
+            if self.take_node_change(&mut node) {
+                return true;
+            }
+            if let TransformControl::Replaced(new_node) = control {
+                let TransformNode::TemplateChild(node) = node else {
+                    unreachable!("the root node cannot be replaced");
+                };
+                *node = *new_node;
+                return false;
+            }
         }
 
+        let has_children = matches!(
+            node,
+            TransformNode::Root(_)
+                | TransformNode::TemplateChild(
+                    TemplateChildNode::If(_)
+                        | TemplateChildNode::IfBranch(_)
+                        | TemplateChildNode::For(_)
+                        | TemplateChildNode::Element(_)
+                )
+        );
+        if has_children {
+            self.ancestors.push(TransformAncestor::new(&node));
+        }
         match &mut node {
             TransformNode::TemplateChild(TemplateChildNode::Comment(_)) => {
                 if !self.ssr {
@@ -184,7 +428,13 @@ impl TransformContext {
                 let branchs = node.branches.drain(..).collect::<Vec<_>>();
                 for child in branchs {
                     let mut child = TemplateChildNode::IfBranch(child);
-                    self.traverse_node(TransformNode::TemplateChild(&mut child));
+                    if self.traverse_node(TransformNode::TemplateChild(&mut child)) {
+                        let index = node.branches.len();
+                        for node_transform in &mut node_transforms {
+                            node_transform.on_node_removed(index, self);
+                        }
+                        continue;
+                    }
                     let TemplateChildNode::IfBranch(child) = child else {
                         unreachable!();
                     };
@@ -192,27 +442,38 @@ impl TransformContext {
                 }
             }
             TransformNode::TemplateChild(TemplateChildNode::IfBranch(node)) => {
-                for child in &mut node.children {
-                    self.traverse_node(TransformNode::TemplateChild(child));
-                }
+                self.traverse_children(&mut node.children, &mut node_transforms);
             }
             TransformNode::TemplateChild(TemplateChildNode::For(node)) => {
-                for child in &mut node.children {
-                    self.traverse_node(TransformNode::TemplateChild(child));
+                let aliases: Vec<_> =
+                    [&node.value_alias, &node.key_alias, &node.object_index_alias]
+                        .into_iter()
+                        .flatten()
+                        .cloned()
+                        .collect();
+                if self.prefix_identifiers {
+                    aliases.iter().for_each(|alias| self.add_identifiers(alias));
+                }
+                self.scopes.v_for += 1;
+                self.traverse_children(&mut node.children, &mut node_transforms);
+                self.scopes.v_for -= 1;
+                if self.prefix_identifiers {
+                    aliases
+                        .iter()
+                        .for_each(|alias| self.remove_identifiers(alias));
                 }
             }
             TransformNode::TemplateChild(TemplateChildNode::Element(node)) => {
-                for child in node.children_mut() {
-                    self.traverse_node(TransformNode::TemplateChild(child));
-                }
+                self.traverse_children(node.children_mut(), &mut node_transforms);
             }
             TransformNode::Root(node) => {
-                for child in &mut node.children {
-                    self.traverse_node(TransformNode::TemplateChild(child));
-                }
+                self.traverse_children(&mut node.children, &mut node_transforms);
             }
             _ => {}
         }
+        if has_children {
+            self.ancestors.pop();
+        }
 
         for node_transform in &mut node_transforms.iter_mut().rev() {
             node_transform.pre_exit(&mut node, self);
@@ -221,52 +482,111 @@ impl TransformContext {
         for node_transform in node_transforms.iter_mut().rev() {
             node_transform.exit(&mut node, self);
         }
+        false
+    }
+
+    fn traverse_children(
+        &mut self,
+        children: &mut Vec<TemplateChildNode>,
+        node_transforms: &mut [Box<dyn NodeTransformState>],
+    ) {
+        let mut i = 0;
+        while i < children.len() {
+            if self.traverse_node(TransformNode::TemplateChild(&mut children[i])) {
+                children.remove(i);
+                for node_transform in node_transforms.iter_mut() {
+                    node_transform.on_node_removed(i, self);
+                }
+            } else {
+                i += 1;
+            }
+        }
     }
 }
 
+/// Runs the node and directive transforms from `options` over an AST produced
+/// by [`base_parse`](crate::base_parse), then sets `root.codegen_node`, the
+/// collected helpers and directives, and marks the root as transformed.
+///
+/// Transforming a root again appends to its hoists and cached values.
+///
+/// No transforms are applied by default; use
+/// [`get_base_transform_preset`](crate::get_base_transform_preset) to get the
+/// ones [`base_compile`](crate::base_compile) uses.
 pub fn transform(root: &mut RootNode, options: TransformOptions) {
     let ssr = options.ssr;
+    let should_hoist_static = options.hoist_static.unwrap_or_default();
     let mut context = TransformContext::new(options);
+    // a root transformed before keeps its hoists, cache slots and the helpers
+    // they use, new ones are appended
+    context.hoists = std::mem::take(&mut root.hoists);
+    context.cached = std::mem::take(&mut root.cached);
+    context.helpers = root.helpers.iter().map(|name| (name.clone(), 1)).collect();
     context.traverse_node(TransformNode::Root(root));
 
+    if should_hoist_static {
+        hoist_static(root, &mut context);
+    }
     if !ssr.unwrap_or_default() {
         create_root_codegen(root, &mut context)
     }
-    let TransformContext { helpers, .. } = context;
+    let TransformContext {
+        helpers,
+        hoists,
+        cached,
+        components,
+        directives,
+        ..
+    } = context;
     root.helpers = helpers.keys().cloned().collect();
+    root.hoists = hoists;
+    root.cached = cached;
+    root.components = components.into_iter().collect();
+    root.directives = directives.into_iter().collect();
     root.transformed = Some(true);
 }
 
+/// Turns the codegen node of a single element root into a block.
+fn create_single_element_root_codegen(
+    node: ElementNode,
+    context: &mut TransformContext,
+) -> Option<RootCodegenNode> {
+    let mut codegen_node = match node {
+        ElementNode::PlainElement(node) => match node.codegen_node? {
+            PlainElementNodeCodegenNode::VNodeCall(node) => node,
+            PlainElementNodeCodegenNode::Cache(node) => {
+                return Some(RootCodegenNode::JSChild(JSChildNode::Cache(Box::new(node))));
+            }
+            // single element root is never hoisted so codegenNode will never be
+            // SimpleExpressionNode
+            PlainElementNodeCodegenNode::Simple(_) => return None,
+        },
+        ElementNode::Component(node) => match node.codegen_node? {
+            ComponentNodeCodegenNode::VNodeCall(node) => node,
+            ComponentNodeCodegenNode::Cache(node) => {
+                return Some(RootCodegenNode::JSChild(JSChildNode::Cache(Box::new(node))));
+            }
+        },
+        ElementNode::Template(node) => match node.codegen_node? {
+            TemplateNodeCodegenNode::VNodeCall(node) => node,
+            TemplateNodeCodegenNode::Cache(node) => {
+                return Some(RootCodegenNode::JSChild(JSChildNode::Cache(Box::new(node))));
+            }
+        },
+        ElementNode::SlotOutlet(_) => return None,
+    };
+    convert_to_block(&mut codegen_node, context);
+    Some(RootCodegenNode::JSChild(JSChildNode::VNodeCall(
+        codegen_node,
+    )))
+}
+
 fn create_root_codegen<'a>(root: &'a mut RootNode, context: &'a mut TransformContext) {
     if root.children.len() == 1 {
         // if the single child is an element, turn it into a block.
-        if let Some(single_element_root_child) = get_single_element_root(root)
-            && single_element_root_child.codegen_node_is_some()
+        if let Some(codegen_node) = get_single_element_root(root)
+            .and_then(|child| create_single_element_root_codegen(child, context))
         {
-            // single element root is never hoisted so codegenNode will never be
-            // SimpleExpressionNode
-            let codegen_node = match single_element_root_child {
-                ElementNode::PlainElement(node) => {
-                    let Some(codegen_node) = node.codegen_node else {
-                        unreachable!();
-                    };
-                    match codegen_node {
-                        crate::PlainElementNodeCodegenNode::VNodeCall(mut node) => {
-                            convert_to_block(&mut node, context);
-                            RootCodegenNode::JSChild(JSChildNode::VNodeCall(node))
-                        }
-                    }
-                }
-                ElementNode::Component(_) => {
-                    todo!()
-                }
-                ElementNode::Template(_) => {
-                    todo!()
-                }
-                ElementNode::SlotOutlet(_) => {
-                    unreachable!()
-                }
-            };
             root.codegen_node = Some(codegen_node);
         } else {
             // - single <slot/>, IfNode, ForNode: already blocks.
@@ -291,20 +611,13 @@ fn create_root_codegen<'a>(root: &'a mut RootNode, context: &'a mut TransformCon
         // }
         let tag = context.helper(Fragment.to_string());
         root.codegen_node = Some(RootCodegenNode::JSChild(JSChildNode::VNodeCall(
-            VNodeCall::new(
-                Some(context),
-                VNodeCallTag::Symbol(tag),
-                None,
-                Some(VNodeCallChildren::TemplateChildNodeList(
+            VNodeCall::builder(VNodeCallTag::Symbol(tag))
+                .children(Some(VNodeCallChildren::TemplateChildNodeList(
                     root.children.clone(),
-                )),
-                Some(patch_flag),
-                Some(true),
-                None,
-                /* isComponent */
-                Some(false),
-                None,
-            ),
+                )))
+                .patch_flag(Some(patch_flag))
+                .block(true)
+                .build(context),
         )));
     } else {
         // no children = noop. codegen will return null.
@@ -1,10 +1,147 @@
 use crate::{
     ast::{
-        CompoundExpressionNode, CompoundExpressionNodeChild, ConstantTypes, ElementNode,
-        ElementTypes, ExpressionNode, RootNode, TemplateChildNode,
+        BlockCodegenNode, CallArgument, CallExpression, CompoundExpressionNode,
+        CompoundExpressionNodeChild, ConstantTypes, ElementNode, ElementTypes, ExpressionNode,
+        ForNodeCodegenNode, ForRenderListArgument, IfCodegenNode, JSChildNode, PlainElementNode,
+        PlainElementNodeCodegenNode, RootNode, TemplateChildNode, TemplateNodeCodegenNode,
+        TextCallCodegenNode, TextCallContent, VNodeCall, VNodeCallChildren, get_vnode_block_helper,
+        get_vnode_helper,
     },
+    runtime_helpers::OpenBlock,
     transform::TransformContext,
+    transforms::transform_element::PropsExpression,
 };
+use vue_compiler_shared::PatchFlags;
+
+/// Hoists the fully static parts of the template out of the render function:
+/// - element vnodes whose props and children are all static, together with
+///   their whole subtree;
+/// - `createTextVNode` calls of static text, but only when the text sits among
+///   dynamic siblings, a lone text leaf is not worth a hoist of its own.
+///
+/// Runs on the transformed tree, before the root codegen node is created.
+pub fn hoist_static(root: &mut RootNode, context: &mut TransformContext) {
+    // the single element root is turned into a block, it can't be hoisted
+    let do_not_hoist_node = get_single_element_root(root).is_some();
+    walk(&mut root.children, context, do_not_hoist_node);
+}
+
+fn walk(
+    children: &mut [TemplateChildNode],
+    context: &mut TransformContext,
+    do_not_hoist_node: bool,
+) {
+    let has_dynamic_sibling = children
+        .iter()
+        .any(|child| get_constant_type(child, context) == ConstantTypes::NotConstant);
+    for child in children {
+        match child {
+            TemplateChildNode::Element(ElementNode::PlainElement(node)) => {
+                if !do_not_hoist_node
+                    && matches!(
+                        node.codegen_node,
+                        Some(PlainElementNodeCodegenNode::VNodeCall(_))
+                    )
+                    && get_element_constant_type(node, context) >= ConstantTypes::CanCache
+                    && let Some(PlainElementNodeCodegenNode::VNodeCall(mut codegen_node)) =
+                        node.codegen_node.take()
+                {
+                    codegen_node.patch_flag = Some(PatchFlags::Hoisted);
+                    disable_block(&mut codegen_node, context);
+                    node.codegen_node = Some(PlainElementNodeCodegenNode::Simple(
+                        context.hoist(JSChildNode::VNodeCall(codegen_node)),
+                    ));
+                } else if let Some(PlainElementNodeCodegenNode::VNodeCall(codegen_node)) =
+                    &mut node.codegen_node
+                    && let Some(VNodeCallChildren::TemplateChildNodeList(children)) =
+                        &mut codegen_node.children
+                {
+                    // hoist within the element itself so that transforming the
+                    // root again finds the `_hoisted_N` references
+                    walk(&mut node.children, context, false);
+                    *children = node.children.clone();
+                }
+            }
+            TemplateChildNode::Element(ElementNode::Template(node)) => {
+                if let Some(TemplateNodeCodegenNode::VNodeCall(codegen_node)) =
+                    &mut node.codegen_node
+                {
+                    walk_vnode_call(codegen_node, context);
+                }
+            }
+            TemplateChildNode::TextCall(node) => {
+                if has_dynamic_sibling
+                    && get_text_call_constant_type(&node.content) >= ConstantTypes::CanCache
+                    && let TextCallCodegenNode::Call(call) = &node.codegen_node
+                {
+                    node.codegen_node =
+                        TextCallCodegenNode::Simple(context.hoist(JSChildNode::Call(call.clone())));
+                }
+            }
+            TemplateChildNode::If(node) => {
+                if let Some(IfCodegenNode::IfConditional(codegen_node)) = &mut node.codegen_node {
+                    walk_js_child(&mut codegen_node.consequent, context);
+                    walk_js_child(&mut codegen_node.alternate, context);
+                }
+            }
+            TemplateChildNode::For(node) => {
+                // a list cached by `v-once` is created only once anyway
+                let Some(ForNodeCodegenNode::ForCodegen(codegen_node)) = &mut node.codegen_node
+                else {
+                    continue;
+                };
+                for argument in &mut codegen_node.children.arguments {
+                    let ForRenderListArgument::ForIterator(iterator) = argument else {
+                        continue;
+                    };
+                    match &mut iterator.returns {
+                        Some(BlockCodegenNode::VNodeCall(block)) => {
+                            walk_vnode_call(block, context);
+                        }
+                        Some(BlockCodegenNode::RenderSlotCall(block)) => {
+                            walk_render_slot_call(block, context);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The blocks of `v-if` branches and `v-for` items stay, only their children
+/// can be hoisted.
+fn walk_js_child(node: &mut JSChildNode, context: &mut TransformContext) {
+    match node {
+        JSChildNode::VNodeCall(node) => walk_vnode_call(node, context),
+        JSChildNode::IfConditional(node) => {
+            walk_js_child(&mut node.consequent, context);
+            walk_js_child(&mut node.alternate, context);
+        }
+        _ => {}
+    }
+}
+
+/// Only the fallback content of a `renderSlot` call can be hoisted.
+fn walk_render_slot_call(node: &mut CallExpression, context: &mut TransformContext) {
+    for argument in &mut node.arguments {
+        if let CallArgument::JSChild(JSChildNode::Function(fallback)) = argument
+            && let Some(children) = &mut fallback.returns
+        {
+            walk(children, context, false);
+        }
+    }
+}
+
+fn walk_vnode_call(node: &mut VNodeCall, context: &mut TransformContext) {
+    // the children of components are slots
+    if !node.is_component
+        && let Some(VNodeCallChildren::TemplateChildNodeList(children)) = &mut node.children
+    {
+        walk(children, context, false);
+    }
+}
 
 pub fn get_single_element_root(root: &RootNode) -> Option<ElementNode> {
     let children = root
@@ -28,7 +165,7 @@ pub fn get_single_element_root(root: &RootNode) -> Option<ElementNode> {
 
 pub fn get_constant_type(
     node: &TemplateChildNode,
-    _context: &mut TransformContext,
+    context: &mut TransformContext,
 ) -> ConstantTypes {
     match node {
         TemplateChildNode::Text(_) | TemplateChildNode::Comment(_) => ConstantTypes::CanStringify,
@@ -37,10 +174,105 @@ pub fn get_constant_type(
             ExpressionNode::Compound(node) => get_constant_type_with_compound(node),
         },
         TemplateChildNode::Compound(node) => get_constant_type_with_compound(node),
-        _ => {
-            todo!()
+        TemplateChildNode::TextCall(node) => get_text_call_constant_type(&node.content),
+        TemplateChildNode::Element(ElementNode::PlainElement(node)) => {
+            get_element_constant_type(node, context)
+        }
+        _ => ConstantTypes::NotConstant,
+    }
+}
+
+fn get_text_call_constant_type(content: &TextCallContent) -> ConstantTypes {
+    match content {
+        TextCallContent::Text(_) => ConstantTypes::CanStringify,
+        TextCallContent::Interpolation(node) => match &node.content {
+            ExpressionNode::Simple(node) => node.const_type,
+            ExpressionNode::Compound(node) => get_constant_type_with_compound(node),
+        },
+        TextCallContent::Compound(node) => get_constant_type_with_compound(node),
+    }
+}
+
+fn get_element_constant_type(
+    node: &PlainElementNode,
+    context: &mut TransformContext,
+) -> ConstantTypes {
+    let codegen_node = match &node.codegen_node {
+        Some(PlainElementNodeCodegenNode::VNodeCall(codegen_node)) => codegen_node,
+        // already analyzed and hoisted
+        Some(PlainElementNodeCodegenNode::Simple(exp)) if exp.hoisted.is_some() => {
+            return exp.const_type;
+        }
+        _ => return ConstantTypes::NotConstant,
+    };
+    if codegen_node.is_block && !matches!(node.tag.as_str(), "svg" | "foreignObject" | "math") {
+        return ConstantTypes::NotConstant;
+    }
+    // any patch flag means something has to be patched at runtime
+    if codegen_node.patch_flag.is_some() || codegen_node.directives.is_some() {
+        return ConstantTypes::NotConstant;
+    }
+
+    let mut return_type = get_props_constant_type(codegen_node.props.as_ref());
+    for child in &node.children {
+        if return_type == ConstantTypes::NotConstant {
+            break;
+        }
+        return_type = return_type.min(get_constant_type(child, context));
+    }
+    return_type
+}
+
+/// A hoisted vnode is created once, outside of any block, so a constant
+/// `svg`, `foreignObject` or `math` block and the blocks within it turn back
+/// into plain vnodes.
+fn disable_block(codegen_node: &mut VNodeCall, context: &mut TransformContext) {
+    if codegen_node.is_block {
+        codegen_node.is_block = false;
+        context.remove_helper(&OpenBlock.to_string());
+        context.remove_helper(&get_vnode_block_helper(
+            context.in_ssr,
+            codegen_node.is_component,
+        ));
+        context.helper(get_vnode_helper(context.in_ssr, codegen_node.is_component));
+    }
+    if let Some(VNodeCallChildren::TemplateChildNodeList(children)) = &mut codegen_node.children {
+        for child in children {
+            if let TemplateChildNode::Element(ElementNode::PlainElement(PlainElementNode {
+                codegen_node: Some(PlainElementNodeCodegenNode::VNodeCall(codegen_node)),
+                ..
+            })) = child
+            {
+                disable_block(codegen_node, context);
+            }
+        }
+    }
+}
+
+fn get_props_constant_type(props: Option<&PropsExpression>) -> ConstantTypes {
+    let properties = match props {
+        None => return ConstantTypes::CanStringify,
+        Some(PropsExpression::Object(props)) => &props.properties,
+        // v-bind="object"
+        Some(_) => return ConstantTypes::NotConstant,
+    };
+    let mut return_type = ConstantTypes::CanStringify;
+    for property in properties {
+        let key_type = match &property.key {
+            ExpressionNode::Simple(key) => key.const_type,
+            ExpressionNode::Compound(key) => get_constant_type_with_compound(key),
+        };
+        let value_type = match &property.value {
+            JSChildNode::Simple(value) => value.const_type,
+            JSChildNode::Compound(value) => get_constant_type_with_compound(value),
+            _ => ConstantTypes::NotConstant,
+        };
+        return_type = return_type.min(key_type).min(value_type);
+        if return_type == ConstantTypes::NotConstant {
+            break;
         }
     }
+    return_type
 }
 
 pub fn get_constant_type_with_compound(node: &CompoundExpressionNode) -> ConstantTypes {
@@ -1,7 +1,11 @@
 pub mod transform_element;
+pub mod transform_expression;
+pub mod transform_slot_outlet;
 // pub mod transform_v_bind_shorthand;
 pub mod cache_static;
+pub mod noop_directive;
 pub mod transform_text;
 pub mod v_bind;
 pub mod v_for;
 pub mod v_if;
+pub mod v_once;
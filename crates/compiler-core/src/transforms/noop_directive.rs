@@ -0,0 +1,23 @@
+use crate::{
+    ast::{DirectiveNode, ElementNode},
+    transform::{DirectiveTransform, DirectiveTransformResult, TransformContext},
+};
+
+/// Directives that only matter at runtime (e.g. `v-cloak`) produce no props.
+#[derive(Debug, Clone)]
+pub struct TransformNoopDirective;
+
+impl DirectiveTransform for TransformNoopDirective {
+    fn transform(
+        &mut self,
+        _dir: &DirectiveNode,
+        _node: &mut ElementNode,
+        _context: &mut TransformContext,
+    ) -> DirectiveTransformResult {
+        DirectiveTransformResult { props: vec![] }
+    }
+
+    fn clone_box(&self) -> Box<dyn DirectiveTransform> {
+        Box::new(self.clone())
+    }
+}
@@ -1,17 +1,27 @@
 use crate::{
     ComponentNodeCodegenNode,
     ast::{
-        BaseElementProps, CallArgument, CallCallee, CallExpression, ConstantTypes, DirectiveNode,
-        ElementNode, ElementTypes, ExpressionNode, JSChildNode, NodeTypes, ObjectExpression,
-        PlainElementNodeCodegenNode, Property, SimpleExpressionNode, TemplateChildNode,
-        TemplateTextChildNode, VNodeCall, VNodeCallChildren,
+        ArrayExpression, AttributeNode, BaseElementProps, CallArgument, CallCallee, CallExpression,
+        ConstantTypes, DirectiveArguments, DirectiveNode, ElementNode, ElementTypes,
+        ExpressionNode, JSChildNode, NodeTypes, ObjectExpression, PlainElementNodeCodegenNode,
+        Property, SimpleExpressionNode, SourceLocation, TemplateChildNode, TemplateNode,
+        TemplateNodeCodegenNode, TemplateTextChildNode, VNodeCall, VNodeCallChildren, VNodeCallTag,
+        convert_to_block,
     },
-    runtime_helpers::NormalizeClass,
+    codegen::{AssetType, CodegenNode},
+    errors::{CompilerError, ErrorCodes},
+    runtime_helpers::{
+        Fragment, GuardReactiveProps, KeepAlive, MergeProps, NormalizeClass, NormalizeProps,
+        NormalizeStyle, ResolveComponent, ResolveDirective, ResolveDynamicComponent, Suspense,
+        Teleport,
+    },
+    tokenizer::ParseMode,
     transform::{DirectiveTransformResult, NodeTransformState, TransformContext, TransformNode},
-    transforms::cache_static::get_constant_type,
-    utils::is_static_arg_of,
+    transforms::{cache_static::get_constant_type, transform_expression::process_expression},
+    utils::{find_prop, is_static_arg_of, to_valid_asset_id},
 };
-use vue_compiler_shared::PatchFlags;
+use std::collections::HashMap;
+use vue_compiler_shared::{PatchFlags, is_built_in_directive, is_on};
 
 /// generate a JavaScript AST for this element's codegen
 pub fn transform_element(
@@ -37,6 +47,18 @@ fn post_transform_element(node: &mut TransformNode, context: &mut TransformConte
         return;
     };
 
+    if let ElementNode::Template(node) = node {
+        node.codegen_node = Some(create_template_codegen_node(node, context));
+        return;
+    }
+    // hoisted by an earlier pass, the `_hoisted_N` reference stays
+    if let ElementNode::PlainElement(node) = node
+        && let Some(PlainElementNodeCodegenNode::Simple(exp)) = &node.codegen_node
+        && exp.hoisted.is_some()
+    {
+        return;
+    }
+
     if !matches!(
         node.tag_type(),
         ElementTypes::Element | ElementTypes::Component
@@ -44,26 +66,77 @@ fn post_transform_element(node: &mut TransformNode, context: &mut TransformConte
         return;
     }
 
+    if context.global_compile_time_constants.__dev__
+        && node.tag() == "template"
+        && node.tag_type() == ElementTypes::Element
+        // the root level `<template>`s of an SFC are its blocks
+        && !(context.parse_mode == ParseMode::SFC
+            && context
+                .parent()
+                .is_some_and(|parent| parent.type_ == NodeTypes::Root))
+    {
+        context.on_warn(CompilerError::new(
+            ErrorCodes::XInvalidTemplatePlacement,
+            Some(node.loc().clone()),
+        ));
+    }
+
     let is_component = matches!(node.tag_type(), ElementTypes::Component);
 
+    // The goal of the transform is to create a codegenNode implementing the
+    // VNodeCall interface.
+    let vnode_tag = if is_component {
+        resolve_component_type(node, context)
+    } else {
+        VNodeCallTag::String(format!("\"{}\"", node.tag()))
+    };
+    let is_dynamic_component = matches!(
+        &vnode_tag,
+        VNodeCallTag::Call(call) if call.callee == CallCallee::Symbol(ResolveDynamicComponent.to_string())
+    );
+
     let mut vnode_props = None::<PropsExpression>;
     let mut vnode_children = None::<VNodeCallChildren>;
     let mut patch_flag = None::<PatchFlags>;
+    let mut vnode_directives = None::<DirectiveArguments>;
 
-    let mut should_use_block = !is_component &&
+    let mut should_use_block =
+        // dynamic component may resolve to plain elements
+        is_dynamic_component ||
+        vnode_tag == VNodeCallTag::Symbol(Teleport.to_string()) ||
+        vnode_tag == VNodeCallTag::Symbol(Suspense.to_string()) ||
+        (!is_component &&
         // <svg> and <foreignObject> must be forced into blocks so that block
         // updates inside get proper isSVG flag at runtime. (#639, #643)
         // This is technically web-specific, but splitting the logic out of core
         // leads to too much unnecessary complexity.
-        (node.tag() == "svg" || node.tag() == "foreignObject" || node.tag() == "math");
+        (node.tag() == "svg" || node.tag() == "foreignObject" || node.tag() == "math"));
 
     // props
     if node.props().len() > 0 {
-        let props_build_result =
-            build_props(node, context, node.props(), is_component, false, false);
+        // directive transforms may update the element, e.g. v-text drops its children
+        let props = node.props().clone();
+        let props_build_result = build_props(
+            node,
+            context,
+            &props,
+            is_component,
+            is_dynamic_component,
+            false,
+        );
 
         vnode_props = props_build_result.props;
         patch_flag = props_build_result.patch_flag;
+        let directives = props_build_result.directives;
+        if !directives.is_empty() {
+            vnode_directives = Some(ArrayExpression::new(
+                directives
+                    .iter()
+                    .map(|dir| CodegenNode::Array(build_directive_args(dir, context)))
+                    .collect(),
+                None,
+            ));
+        }
 
         if props_build_result.should_use_block {
             should_use_block = true;
@@ -72,7 +145,42 @@ fn post_transform_element(node: &mut TransformNode, context: &mut TransformConte
 
     // children
     if node.children().len() > 0 {
-        if node.children().len() == 1 {
+        if vnode_tag == VNodeCallTag::Symbol(KeepAlive.to_string()) {
+            // Although a built-in component, we compile KeepAlive with raw children
+            // instead of slot functions so that it can be used inside Transition
+            // or other Transition-wrapping HOCs.
+            // To ensure correct updates with block optimizations, we need to:
+            // 1. Force keep-alive into a block. This avoids its children being
+            //    collected by a parent block.
+            should_use_block = true;
+            // 2. Force keep-alive to always be updated, since it uses raw children.
+            patch_flag = Some(patch_flag.map_or(PatchFlags::DynamicSlots, |flag| {
+                flag | PatchFlags::DynamicSlots
+            }));
+            // 3. Turn the cached child into a block of its own.
+            for child in node.children_mut() {
+                convert_element_to_block(child, context);
+            }
+            if context.global_compile_time_constants.__dev__
+                && (node.children().len() > 1
+                    || node
+                        .children()
+                        .iter()
+                        .any(|child| child.type_() != NodeTypes::Element))
+            {
+                let first = &node.children()[0];
+                let last = &node.children()[node.children().len() - 1];
+                let mut loc = first.loc().clone();
+                loc.end = last.loc().end.clone();
+                loc.source = String::new();
+                context.on_warn(CompilerError::new(
+                    ErrorCodes::XKeepAliveInvalidChildren,
+                    Some(loc),
+                ));
+            }
+        }
+
+        if node.children().len() == 1 && vnode_tag != VNodeCallTag::Symbol(Teleport.to_string()) {
             let Some(child) = node.children().first() else {
                 unreachable!();
             };
@@ -105,18 +213,15 @@ fn post_transform_element(node: &mut TransformNode, context: &mut TransformConte
         }
     }
 
-    let vnode_call = VNodeCall::new(
-        Some(context),
-        format!("\"{}\"", node.tag()),
-        vnode_props,
-        vnode_children,
-        patch_flag,
-        Some(should_use_block),
-        Some(false),
-        /* disableTracking */
-        Some(false),
-        Some(node.loc().clone()),
-    );
+    let vnode_call = VNodeCall::builder(vnode_tag)
+        .props(vnode_props)
+        .children(vnode_children)
+        .patch_flag(patch_flag)
+        .directives(vnode_directives)
+        .block(should_use_block)
+        .component(is_component)
+        .loc(node.loc().clone())
+        .build(context);
 
     match node {
         ElementNode::PlainElement(node) => {
@@ -131,65 +236,285 @@ fn post_transform_element(node: &mut TransformNode, context: &mut TransformConte
     }
 }
 
+fn convert_element_to_block(node: &mut TemplateChildNode, context: &mut TransformContext) {
+    match node {
+        TemplateChildNode::Element(ElementNode::PlainElement(node)) => {
+            if let Some(PlainElementNodeCodegenNode::VNodeCall(codegen_node)) =
+                &mut node.codegen_node
+            {
+                convert_to_block(codegen_node, context);
+            }
+        }
+        TemplateChildNode::Element(ElementNode::Component(node)) => {
+            if let Some(ComponentNodeCodegenNode::VNodeCall(codegen_node)) = &mut node.codegen_node
+            {
+                convert_to_block(codegen_node, context);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn is_component_tag(tag: &str) -> bool {
+    tag == "component" || tag == "Component"
+}
+
+fn resolve_component_type(node: &ElementNode, context: &mut TransformContext) -> VNodeCallTag {
+    let mut tag = node.tag().clone();
+
+    // 1. dynamic component
+    let is_explicit_dynamic = is_component_tag(&tag);
+    if let Some(is_prop) = find_prop(node, "is", None, Some(true)) {
+        if is_explicit_dynamic {
+            let exp = match is_prop {
+                BaseElementProps::Attribute(prop) => prop.value.map(|value| {
+                    ExpressionNode::new_simple(value.content, Some(true), Some(value.loc), None)
+                }),
+                BaseElementProps::Directive(prop) => prop.exp.or_else(|| {
+                    // #10469 handle :is shorthand
+                    let exp = SimpleExpressionNode::new(
+                        "is",
+                        Some(false),
+                        prop.arg.map(|arg| arg.loc().clone()),
+                        None,
+                    );
+                    Some(process_expression(exp, context, None))
+                }),
+            };
+            if let Some(exp) = exp {
+                return VNodeCallTag::Call(CallExpression::new(
+                    CallCallee::Symbol(context.helper(ResolveDynamicComponent.to_string())),
+                    Some(vec![CallArgument::JSChild(JSChildNode::from(exp))]),
+                    None,
+                ));
+            }
+        } else if let BaseElementProps::Attribute(prop) = is_prop
+            && let Some(value) = &prop.value
+            && let Some(name) = value.content.strip_prefix("vue:")
+        {
+            // <button is="vue:xxx">
+            // if not <component>, only is value that starts with "vue:" will be
+            // treated as component by the parse phase and reach here
+            tag = name.to_string();
+        }
+    }
+
+    // 2. built-in components (Teleport, Transition, KeepAlive, Suspense...)
+    if let Some(built_in) = context.resolve_built_in_component(&tag) {
+        // built-ins are simply fallthroughs / have special handling during ssr
+        // so we don't need to import their runtime equivalents
+        if !context.ssr {
+            context.helper(built_in.clone());
+        }
+        return VNodeCallTag::Symbol(built_in);
+    }
+
+    // 5. user component (resolve)
+    context.helper(ResolveComponent.to_string());
+    context.components.insert(tag.clone());
+    VNodeCallTag::String(to_valid_asset_id(&tag, &AssetType::Component))
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum PropsExpression {
     Object(ObjectExpression),
-    // ObjectExpression | CallExpression | ExpressionNode
+    Call(CallExpression),
+    Expression(ExpressionNode),
 }
 
-struct PropsBuildResult {
-    props: Option<PropsExpression>,
-    directives: Vec<DirectiveNode>,
-    patch_flag: Option<PatchFlags>,
-    should_use_block: bool,
+impl From<PropsExpression> for JSChildNode {
+    fn from(value: PropsExpression) -> Self {
+        match value {
+            PropsExpression::Object(node) => Self::Object(node),
+            PropsExpression::Call(node) => Self::Call(node),
+            PropsExpression::Expression(node) => Self::from(node),
+        }
+    }
 }
 
-fn build_props<'a>(
-    node: &'a ElementNode,
+/// A `<template>` left after the structural transforms renders its children in
+/// place: a single element child directly, otherwise wrapped in a fragment.
+fn create_template_codegen_node(
+    node: &TemplateNode,
     context: &mut TransformContext,
-    props: &'a Vec<BaseElementProps>,
+) -> TemplateNodeCodegenNode {
+    if let [TemplateChildNode::Element(child)] = node.children.as_slice() {
+        let vnode_call = match child {
+            ElementNode::PlainElement(child) => match child.codegen_node.clone() {
+                Some(PlainElementNodeCodegenNode::VNodeCall(node)) => Some(node),
+                _ => None,
+            },
+            ElementNode::Component(child) => match child.codegen_node.clone() {
+                Some(ComponentNodeCodegenNode::VNodeCall(node)) => Some(node),
+                _ => None,
+            },
+            ElementNode::Template(child) => match child.codegen_node.clone() {
+                Some(TemplateNodeCodegenNode::VNodeCall(node)) => Some(node),
+                _ => None,
+            },
+            ElementNode::SlotOutlet(_) => None,
+        };
+        if let Some(vnode_call) = vnode_call {
+            return TemplateNodeCodegenNode::VNodeCall(vnode_call);
+        }
+    }
+
+    let tag = context.helper(Fragment.to_string());
+    TemplateNodeCodegenNode::VNodeCall(
+        VNodeCall::builder(VNodeCallTag::Symbol(tag))
+            .children(Some(VNodeCallChildren::TemplateChildNodeList(
+                node.children.clone(),
+            )))
+            .patch_flag(Some(PatchFlags::StableFragment))
+            .loc(node.loc.clone())
+            .build(context),
+    )
+}
+
+/// Convert a static attribute into a props object entry. A valueless attribute
+/// gets an empty string, and the static `class` / `style` values are trimmed
+/// with runs of whitespace collapsed into a single space.
+pub fn attr_to_prop(attr: &AttributeNode) -> Property {
+    let (mut value, loc) = if let Some(node) = &attr.value {
+        (node.content.clone(), node.loc.clone())
+    } else {
+        (String::new(), attr.loc.clone())
+    };
+    if attr.name == "class" || attr.name == "style" {
+        value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+    }
+    Property::new(
+        ExpressionNode::new_simple(&attr.name, Some(true), Some(attr.name_loc.clone()), None),
+        JSChildNode::Simple(SimpleExpressionNode::new(
+            value,
+            Some(true),
+            Some(loc),
+            None,
+        )),
+    )
+}
+
+/// Refs inside `v-for` are collected into an array by the runtime.
+fn push_ref_v_for_marker(properties: &mut Vec<Property>, context: &TransformContext) {
+    if context.scopes.v_for > 0 {
+        properties.push(Property::new(
+            ExpressionNode::new_simple("ref_for", Some(true), None, None),
+            JSChildNode::Simple(SimpleExpressionNode::new("true", None, None, None)),
+        ));
+    }
+}
+
+fn push_merge_arg(
+    merge_args: &mut Vec<JSChildNode>,
+    properties: &mut Vec<Property>,
+    loc: &SourceLocation,
+) {
+    if !properties.is_empty() {
+        merge_args.push(JSChildNode::Object(ObjectExpression::new(
+            dedupe_properties(std::mem::take(properties)),
+            Some(loc.clone()),
+        )));
+    }
+}
+
+/// Merge duplicate `class`, `style` and listener props into arrays, other
+/// duplicates should have been reported as errors while parsing.
+fn dedupe_properties(properties: Vec<Property>) -> Vec<Property> {
+    let mut known_props: HashMap<String, usize> = HashMap::new();
+    let mut deduped: Vec<Property> = Vec::new();
+    for prop in properties {
+        // dynamic keys are always allowed
+        let ExpressionNode::Simple(key) = &prop.key else {
+            deduped.push(prop);
+            continue;
+        };
+        if !key.is_static {
+            deduped.push(prop);
+            continue;
+        }
+        let name = key.content.clone();
+        if let Some(&i) = known_props.get(&name) {
+            if name == "style" || name == "class" || is_on(&name) {
+                merge_as_array(&mut deduped[i], prop);
+            }
+        } else {
+            known_props.insert(name, deduped.len());
+            deduped.push(prop);
+        }
+    }
+    deduped
+}
+
+fn merge_as_array(existing: &mut Property, incoming: Property) {
+    if let JSChildNode::Array(array) = &mut existing.value {
+        array.elements.push(CodegenNode::from(incoming.value));
+    } else {
+        existing.value = JSChildNode::Array(ArrayExpression::new(
+            vec![
+                CodegenNode::from(existing.value.clone()),
+                CodegenNode::from(incoming.value),
+            ],
+            Some(existing.loc.clone()),
+        ));
+    }
+}
+
+pub(crate) struct PropsBuildResult {
+    pub(crate) props: Option<PropsExpression>,
+    pub(crate) directives: Vec<DirectiveNode>,
+    pub(crate) patch_flag: Option<PatchFlags>,
+    pub(crate) should_use_block: bool,
+}
+
+pub(crate) fn build_props(
+    node: &mut ElementNode,
+    context: &mut TransformContext,
+    props: &[BaseElementProps],
     is_component: bool,
     is_dynamic_component: bool,
     ssr: bool,
 ) -> PropsBuildResult {
+    let element_loc = node.loc().clone();
     let mut properties: Vec<Property> = Vec::new();
+    let mut merge_args: Vec<JSChildNode> = Vec::new();
     let mut runtime_directives: Vec<DirectiveNode> = Vec::new();
     let has_children = node.children().len() > 0;
     let mut should_use_block = false;
 
     let mut patch_flag = None::<PatchFlags>;
+    let mut has_ref = false;
     let mut has_class_binding = false;
+    let mut has_style_binding = false;
     let mut has_dynamic_keys = false;
 
     for prop in props {
         match prop {
             BaseElementProps::Attribute(prop) => {
-                let is_static = Some(true);
-
-                let (value, loc) = if let Some(node) = &prop.value {
-                    (node.content.clone(), node.loc.clone())
-                } else {
-                    (String::new(), prop.loc.clone())
-                };
-                properties.push(Property::new(
-                    ExpressionNode::new_simple(
-                        &prop.name,
-                        Some(true),
-                        Some(prop.name_loc.clone()),
-                        None,
-                    ),
-                    JSChildNode::Simple(SimpleExpressionNode::new(
-                        value,
-                        is_static,
-                        Some(loc),
-                        None,
-                    )),
-                ));
+                // skip is on <component>, or is="vue:xxx"
+                if prop.name == "is"
+                    && (is_component_tag(node.tag())
+                        || prop
+                            .value
+                            .as_ref()
+                            .is_some_and(|value| value.content.starts_with("vue:")))
+                {
+                    continue;
+                }
+                if prop.name == "ref" {
+                    has_ref = true;
+                    push_ref_v_for_marker(&mut properties, context);
+                }
+                properties.push(attr_to_prop(prop));
             }
             BaseElementProps::Directive(prop) => {
                 let is_v_bind = prop.name == "bind";
                 let is_v_on = prop.name == "on";
 
+                // skip :is on <component>
+                if is_v_bind && is_static_arg_of(&prop.arg, "is") && is_component_tag(node.tag()) {
+                    continue;
+                }
+
                 if
                 // #938: elements with dynamic keys should be forced into blocks
                 (is_v_bind && is_static_arg_of(&prop.arg, "key")) ||
@@ -200,6 +525,27 @@ fn build_props<'a>(
                     should_use_block = true;
                 }
 
+                // special case for v-bind with no argument
+                if is_v_bind && prop.arg.is_none() {
+                    has_dynamic_keys = true;
+                    if let Some(exp) = &prop.exp {
+                        // in case the object contains a ref
+                        push_ref_v_for_marker(&mut properties, context);
+                        push_merge_arg(&mut merge_args, &mut properties, &element_loc);
+                        merge_args.push(JSChildNode::from(exp.clone()));
+                    } else {
+                        context.on_error(CompilerError::new(
+                            ErrorCodes::XVBindNoExpression,
+                            Some(prop.loc.clone()),
+                        ));
+                    }
+                    continue;
+                }
+
+                if is_v_bind && is_static_arg_of(&prop.arg, "ref") {
+                    push_ref_v_for_marker(&mut properties, context);
+                }
+
                 let directive_transform = context.directive_transforms.get(&prop.name).cloned();
                 if let Some(mut directive_transform) = directive_transform {
                     let DirectiveTransformResult { props } =
@@ -213,6 +559,8 @@ fn build_props<'a>(
                                 let name = &key.content;
                                 if name == "class" {
                                     has_class_binding = true;
+                                } else if name == "style" {
+                                    has_style_binding = true;
                                 }
                             } else {
                                 has_dynamic_keys = true;
@@ -224,25 +572,57 @@ fn build_props<'a>(
                     } else {
                         properties.extend(props);
                     }
+                } else if !is_built_in_directive(&prop.name) {
+                    // no built-in transform, this is a user custom directive.
+                    runtime_directives.push(prop.clone());
                 }
             }
         }
     }
 
     let mut props_expression = None::<PropsExpression>;
-    if properties.len() > 0 {
+    // has v-bind="object", wrap with mergeProps
+    if !merge_args.is_empty() {
+        // close up any not-yet-merged props
+        push_merge_arg(&mut merge_args, &mut properties, &element_loc);
+        if merge_args.len() > 1 {
+            props_expression = Some(PropsExpression::Call(CallExpression::new(
+                CallCallee::Symbol(context.helper(MergeProps.to_string())),
+                Some(merge_args.into_iter().map(CallArgument::JSChild).collect()),
+                Some(element_loc),
+            )));
+        } else {
+            // single v-bind with nothing else - no need for a mergeProps call
+            props_expression = match merge_args.pop() {
+                Some(JSChildNode::Simple(exp)) => {
+                    Some(PropsExpression::Expression(ExpressionNode::Simple(exp)))
+                }
+                Some(JSChildNode::Compound(exp)) => {
+                    Some(PropsExpression::Expression(ExpressionNode::Compound(exp)))
+                }
+                _ => unreachable!(),
+            };
+        }
+    } else if !properties.is_empty() {
         props_expression = Some(PropsExpression::Object(ObjectExpression::new(
-            properties,
-            Some(node.loc().clone()),
+            dedupe_properties(properties),
+            Some(element_loc),
         )));
     }
 
     // patchFlag analysis
     if has_dynamic_keys {
+        patch_flag = Some(patch_flag.map_or(PatchFlags::FullProps, |f| f | PatchFlags::FullProps));
     } else {
         if has_class_binding && !is_component {
             patch_flag = Some(patch_flag.map_or(PatchFlags::Class, |f| f | PatchFlags::Class));
         }
+        if has_style_binding && !is_component {
+            patch_flag = Some(patch_flag.map_or(PatchFlags::Style, |f| f | PatchFlags::Style));
+        }
+    }
+    if !should_use_block && patch_flag.is_none() && (has_ref || !runtime_directives.is_empty()) {
+        patch_flag = Some(PatchFlags::NeedPatch);
     }
 
     if !context.in_ssr
@@ -282,12 +662,36 @@ fn build_props<'a>(
                         None,
                     ))
                 }
+                // the style object parsed from a static `style` attribute is already
+                // normalized, so only bound or array styles are wrapped
                 if let Some(i) = style_key_index
                     && let Some(style_prop) = props_expression.properties.get_mut(i)
+                    && !style_prop.value.is_static_exp()
+                    && (has_style_binding
+                        || matches!(&style_prop.value, JSChildNode::Simple(exp) if exp.content.trim_start().starts_with('['))
+                        || matches!(&style_prop.value, JSChildNode::Array(_)))
                 {
+                    let callee = context.helper(NormalizeStyle.to_string());
+                    style_prop.value = JSChildNode::Call(CallExpression::new(
+                        CallCallee::Symbol(callee),
+                        Some(vec![CallArgument::JSChild(style_prop.value.clone())]),
+                        None,
+                    ))
                 }
             } else {
             }
+        } else if let PropsExpression::Expression(exp) = props_expression {
+            // single v-bind
+            let guard = CallExpression::new(
+                CallCallee::Symbol(context.helper(GuardReactiveProps.to_string())),
+                Some(vec![CallArgument::JSChild(JSChildNode::from(exp.clone()))]),
+                None,
+            );
+            *props_expression = PropsExpression::Call(CallExpression::new(
+                CallCallee::Symbol(context.helper(NormalizeProps.to_string())),
+                Some(vec![CallArgument::JSChild(JSChildNode::Call(guard))]),
+                None,
+            ));
         }
     }
 
@@ -298,3 +702,57 @@ fn build_props<'a>(
         should_use_block,
     }
 }
+
+fn build_directive_args(dir: &DirectiveNode, context: &mut TransformContext) -> ArrayExpression {
+    let simple = |content: String| {
+        CodegenNode::Simple(SimpleExpressionNode::new(
+            content,
+            Some(false),
+            Some(dir.loc.clone()),
+            None,
+        ))
+    };
+
+    // user directive.
+    let mut dir_args = Vec::new();
+    context.helper(ResolveDirective.to_string());
+    context.directives.insert(dir.name.clone());
+    dir_args.push(simple(to_valid_asset_id(&dir.name, &AssetType::Directive)));
+
+    if let Some(exp) = &dir.exp {
+        dir_args.push(CodegenNode::from(exp.clone()));
+    }
+    if let Some(arg) = &dir.arg {
+        if dir.exp.is_none() {
+            dir_args.push(simple("void 0".to_string()));
+        }
+        dir_args.push(CodegenNode::from(arg.clone()));
+    }
+    if !dir.modifiers.is_empty() {
+        if dir.arg.is_none() {
+            if dir.exp.is_none() {
+                dir_args.push(simple("void 0".to_string()));
+            }
+            dir_args.push(simple("void 0".to_string()));
+        }
+        let true_expression = JSChildNode::Simple(SimpleExpressionNode::new(
+            "true",
+            Some(false),
+            Some(dir.loc.clone()),
+            None,
+        ));
+        dir_args.push(CodegenNode::Object(ObjectExpression::new(
+            dir.modifiers
+                .iter()
+                .map(|modifier| {
+                    Property::new(
+                        ExpressionNode::Simple(modifier.clone()),
+                        true_expression.clone(),
+                    )
+                })
+                .collect(),
+            Some(dir.loc.clone()),
+        )));
+    }
+    ArrayExpression::new(dir_args, Some(dir.loc.clone()))
+}
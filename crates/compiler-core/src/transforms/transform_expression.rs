@@ -0,0 +1,612 @@
+// Prefix identifiers in template expressions with `_ctx.` when
+// `prefix_identifiers` is enabled.
+//
+// There is no JavaScript parser available here, so expressions are walked with
+// a light-weight scanner instead: string, template and regex literals,
+// comments, member accesses, static object keys, keywords, the params and
+// variables of the arrow functions in scope and the `v-for` aliases in scope
+// are left untouched, everything else that looks like an identifier is
+// rewritten.
+
+use std::collections::HashSet;
+
+use vue_compiler_shared::is_globally_allowed;
+
+use crate::{
+    ast::{
+        BaseElementProps, CompoundExpressionNode, CompoundExpressionNodeChild, ConstantTypes,
+        ExpressionNode, NodeTypes, SimpleExpressionNode, SourceLocation, TemplateChildNode,
+    },
+    transform::{NodeTransformState, TransformContext, TransformControl, TransformNode},
+    utils::{advance_position_with_clone, is_simple_identifier},
+};
+
+pub fn transform_expression(
+    node: &TransformNode,
+    _context: &mut TransformContext,
+) -> Option<Box<dyn NodeTransformState>> {
+    if matches!(node.type_(), NodeTypes::Interpolation | NodeTypes::Element) {
+        Some(Box::new(TransformExpression))
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TransformExpression;
+
+impl NodeTransformState for TransformExpression {
+    fn transform(
+        &mut self,
+        node: &mut TransformNode,
+        context: &mut TransformContext,
+    ) -> TransformControl {
+        match node {
+            TransformNode::TemplateChild(TemplateChildNode::Interpolation(node)) => {
+                if let ExpressionNode::Simple(content) = &node.content {
+                    node.content = process_expression(content.clone(), context, None);
+                }
+            }
+            TransformNode::TemplateChild(TemplateChildNode::Element(node)) => {
+                // handle directives on element
+                for prop in node.props_mut() {
+                    // do not process for v-on & v-for since they are special handled
+                    let BaseElementProps::Directive(dir) = prop else {
+                        continue;
+                    };
+                    if dir.name == "for" {
+                        continue;
+                    }
+                    // do not process exp if this is v-on:arg - we need special handling
+                    // for wrapping inline statements.
+                    if let Some(ExpressionNode::Simple(exp)) = &dir.exp
+                        && !(dir.name == "on" && dir.arg.is_some())
+                    {
+                        dir.exp = Some(process_expression(
+                            exp.clone(),
+                            context,
+                            Some(dir.name == "slot"),
+                        ));
+                    }
+                    if let Some(ExpressionNode::Simple(arg)) = &dir.arg
+                        && !arg.is_static
+                    {
+                        dir.arg = Some(process_expression(arg.clone(), context, None));
+                    }
+                }
+            }
+            _ => {}
+        }
+        TransformControl::Continue
+    }
+}
+
+fn is_literal_whitelisted(key: &str) -> bool {
+    matches!(key, "true" | "false" | "null" | "this")
+}
+
+fn is_keyword(key: &str) -> bool {
+    is_literal_whitelisted(key)
+        || matches!(
+            key,
+            "typeof"
+                | "instanceof"
+                | "in"
+                | "new"
+                | "void"
+                | "delete"
+                | "await"
+                | "async"
+                | "yield"
+                | "function"
+                | "return"
+                | "var"
+                | "let"
+                | "const"
+                | "if"
+                | "else"
+                | "class"
+                | "super"
+        )
+}
+
+fn can_prefix(name: &str) -> bool {
+    // skip whitelisted globals
+    if is_globally_allowed(name) {
+        return false;
+    }
+    // special case for webpack compilation
+    if name == "require" {
+        return false;
+    }
+    true
+}
+
+fn rewrite_identifier(raw: &str) -> String {
+    format!("_ctx.{}", raw)
+}
+
+/// Rewrite the identifiers of an expression coming from a template. Params
+/// (e.g. `v-for` aliases) declare identifiers and are returned as is.
+pub fn process_expression(
+    mut node: SimpleExpressionNode,
+    context: &TransformContext,
+    as_params: Option<bool>,
+) -> ExpressionNode {
+    if context.global_compile_time_constants.__browser__ {
+        return ExpressionNode::Simple(node);
+    }
+
+    if !context.prefix_identifiers || node.content.trim().is_empty() {
+        return ExpressionNode::Simple(node);
+    }
+
+    let as_params = as_params.unwrap_or_default();
+    let raw_exp = node.content.clone();
+
+    // fast path if expression is a simple identifier.
+    if is_simple_identifier(&raw_exp) {
+        let is_scope_var_reference = context.identifiers.contains_key(&raw_exp);
+        let is_allowed_global = is_globally_allowed(&raw_exp);
+        let is_literal = is_literal_whitelisted(&raw_exp);
+        if !as_params && !is_scope_var_reference && !is_literal && !is_allowed_global {
+            node.content = rewrite_identifier(&raw_exp);
+        } else if !is_scope_var_reference {
+            if is_literal {
+                node.const_type = ConstantTypes::CanStringify;
+            } else {
+                node.const_type = ConstantTypes::CanCache;
+            }
+        }
+        return ExpressionNode::Simple(node);
+    }
+
+    // identifiers declared by function params are never prefixed, their
+    // default values are
+    let ids: Vec<_> = walk_identifiers(&raw_exp, as_params)
+        .into_iter()
+        .filter(|id| !id.declared)
+        .collect();
+    if ids.is_empty() {
+        node.const_type = ConstantTypes::CanStringify;
+        return ExpressionNode::Simple(node);
+    }
+
+    let mut children = Vec::new();
+    let mut last = 0;
+    for id in ids {
+        let mut leading_text = raw_exp[last..id.start].to_string();
+        if id.shorthand {
+            // `{ foo }` -> `{ foo: _ctx.foo }`
+            leading_text.push_str(&format!("{}: ", id.name));
+        }
+        if !leading_text.is_empty() {
+            children.push(CompoundExpressionNodeChild::String(leading_text));
+        }
+
+        let loc = SourceLocation {
+            start: advance_position_with_clone(&node.loc.start, &raw_exp[..id.start]),
+            end: advance_position_with_clone(&node.loc.start, &raw_exp[..id.end]),
+            source: id.name.clone(),
+        };
+        let is_local = id.is_local || context.identifiers.contains_key(&id.name);
+        let need_prefix = can_prefix(&id.name);
+        let exp = if need_prefix && !is_local {
+            SimpleExpressionNode::new(
+                rewrite_identifier(&id.name),
+                Some(false),
+                Some(loc),
+                Some(ConstantTypes::NotConstant),
+            )
+        } else {
+            // a local scope variable is not constant
+            let const_type = if need_prefix {
+                ConstantTypes::NotConstant
+            } else {
+                ConstantTypes::CanStringify
+            };
+            SimpleExpressionNode::new(id.name, Some(false), Some(loc), Some(const_type))
+        };
+        children.push(CompoundExpressionNodeChild::Simple(exp));
+        last = id.end;
+    }
+    if last < raw_exp.len() {
+        children.push(CompoundExpressionNodeChild::String(
+            raw_exp[last..].to_string(),
+        ));
+    }
+
+    ExpressionNode::Compound(CompoundExpressionNode::new(children, Some(node.loc)))
+}
+
+/// The identifiers declared by a param, e.g. a `v-for` alias like `item` or
+/// `{ id, name: label }`. Default values of destructured params are skipped.
+pub fn param_identifiers(exp: &ExpressionNode) -> Vec<String> {
+    let content: String = match exp {
+        ExpressionNode::Simple(exp) => exp.content.clone(),
+        // params whose default values have been prefixed
+        ExpressionNode::Compound(exp) => exp
+            .children
+            .iter()
+            .map(|child| match child {
+                CompoundExpressionNodeChild::Simple(exp) => exp.content.as_str(),
+                CompoundExpressionNodeChild::String(text) => text.as_str(),
+                _ => "",
+            })
+            .collect(),
+    };
+    walk_identifiers(&content, true)
+        .into_iter()
+        .filter(|id| id.declared)
+        .map(|id| id.name)
+        .collect()
+}
+
+#[derive(Debug)]
+struct Identifier {
+    name: String,
+    /// byte offsets into the expression
+    start: usize,
+    end: usize,
+    /// object property shorthand, e.g. `{ foo }`
+    shorthand: bool,
+    /// declared by an enclosing function param or variable declaration
+    is_local: bool,
+    /// a function param or variable declaration itself
+    declared: bool,
+}
+
+#[derive(Debug, PartialEq)]
+enum Scope {
+    Paren,
+    Bracket,
+    Object,
+    /// the param list of an arrow function and the identifiers it declares
+    Params(HashSet<String>),
+    /// a function body in braces and the identifiers declared in it
+    Block(HashSet<String>),
+    /// an arrow function body without braces, ending with the enclosing
+    /// parens, brackets or braces or with a `,`
+    Expression(HashSet<String>),
+    TemplateLiteral,
+    TemplateExpression,
+}
+
+impl Scope {
+    fn declared(&self) -> Option<&HashSet<String>> {
+        match self {
+            Scope::Params(ids) | Scope::Block(ids) | Scope::Expression(ids) => Some(ids),
+            _ => None,
+        }
+    }
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c == '$' || c == '_' || c.is_ascii_alphabetic() || c as u32 >= 0xA0
+}
+
+fn is_identifier_char(c: char) -> bool {
+    is_identifier_start(c) || c.is_ascii_digit()
+}
+
+/// The word ending right before `index`, e.g. the `let` of `let x`.
+fn prev_word(chars: &[(usize, char)], index: usize) -> String {
+    let Some(end) = prev_non_whitespace(chars, index) else {
+        return String::new();
+    };
+    let start = (0..=end)
+        .rev()
+        .take_while(|i| is_identifier_char(chars[*i].1))
+        .last()
+        .unwrap_or(end + 1);
+    chars[start..=end].iter().map(|(_, c)| c).collect()
+}
+
+fn prev_non_whitespace(chars: &[(usize, char)], index: usize) -> Option<usize> {
+    (0..index).rev().find(|i| !chars[*i].1.is_whitespace())
+}
+
+fn next_non_whitespace(chars: &[(usize, char)], index: usize) -> Option<usize> {
+    (index..chars.len()).find(|i| !chars[*i].1.is_whitespace())
+}
+
+fn is_arrow_at(chars: &[(usize, char)], index: Option<usize>) -> bool {
+    index.is_some_and(|i| chars[i].1 == '=' && chars.get(i + 1).is_some_and(|(_, c)| *c == '>'))
+}
+
+/// Returns the index after the closing quote.
+fn skip_string(chars: &[(usize, char)], start: usize, quote: char) -> usize {
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i].1 {
+            '\\' => i += 2,
+            c if c == quote => return i + 1,
+            _ => i += 1,
+        }
+    }
+    i
+}
+
+/// Whether the `/` at `index` starts a regex literal rather than a division.
+fn starts_regex(chars: &[(usize, char)], index: usize) -> bool {
+    let Some(prev) = prev_non_whitespace(chars, index) else {
+        return true;
+    };
+    let ends_operand = |i: usize| {
+        let c = chars[i].1;
+        is_identifier_char(c) || matches!(c, ')' | ']' | '\'' | '"' | '`')
+    };
+    match chars[prev].1 {
+        // postfix `++` / `--` and the TS non-null `!` keep ending an operand
+        c @ ('+' | '-') if prev > 0 && chars[prev - 1].1 == c => {
+            !(prev > 1 && prev_non_whitespace(chars, prev - 1).is_some_and(ends_operand))
+        }
+        '!' => !prev_non_whitespace(chars, prev).is_some_and(ends_operand),
+        c => "(,=:[&|?{};+-*%<>~^".contains(c),
+    }
+}
+
+/// Returns the index after the flags of the regex literal at `start`.
+fn skip_regex(chars: &[(usize, char)], start: usize) -> usize {
+    let mut in_class = false;
+    let mut i = start + 1;
+    while i < chars.len() && (in_class || chars[i].1 != '/') {
+        match chars[i].1 {
+            '\\' => i += 1,
+            '[' => in_class = true,
+            ']' => in_class = false,
+            _ => {}
+        }
+        i += 1;
+    }
+    i += 1;
+    while i < chars.len() && is_identifier_char(chars[i].1) {
+        i += 1;
+    }
+    i
+}
+
+/// If the paren at `open` starts the param list of an arrow function, returns
+/// the index of the matching closing paren.
+fn arrow_params_end(chars: &[(usize, char)], open: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut i = open;
+    while i < chars.len() {
+        match chars[i].1 {
+            '\'' | '"' | '`' => {
+                i = skip_string(chars, i, chars[i].1);
+                continue;
+            }
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return is_arrow_at(chars, next_non_whitespace(chars, i + 1)).then_some(i);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Walks the identifiers of `exp`. With `as_params` the expression is the
+/// param list of a function, e.g. a `v-for` alias.
+fn walk_identifiers(exp: &str, as_params: bool) -> Vec<Identifier> {
+    let chars: Vec<(usize, char)> = exp.char_indices().collect();
+    let byte_offset = |i: usize| chars.get(i).map_or(exp.len(), |(offset, _)| *offset);
+
+    let mut ids = Vec::new();
+    let mut stack: Vec<Scope> = Vec::new();
+    if as_params {
+        stack.push(Scope::Params(HashSet::new()));
+    }
+    // the params of the arrow function whose `=>` comes next
+    let mut arrow_params = HashSet::new();
+    // the stack depths of the param default values being walked
+    let mut defaults: Vec<usize> = Vec::new();
+    // whether an identifier declares a param, i.e. it is within a param list
+    // and its destructuring patterns but not within a default value
+    let in_pattern = |stack: &[Scope], defaults: &[usize]| {
+        let Some(index) = stack
+            .iter()
+            .rposition(|scope| !matches!(scope, Scope::Object | Scope::Bracket))
+        else {
+            return false;
+        };
+        matches!(stack[index], Scope::Params(_)) && defaults.last().is_none_or(|d| *d <= index)
+    };
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i].1;
+
+        if stack.last() == Some(&Scope::TemplateLiteral) {
+            match c {
+                '\\' => i += 1,
+                '`' => {
+                    stack.pop();
+                }
+                '$' if chars.get(i + 1).is_some_and(|(_, c)| *c == '{') => {
+                    stack.push(Scope::TemplateExpression);
+                    i += 1;
+                }
+                _ => {}
+            }
+            i += 1;
+            continue;
+        }
+
+        // a body without braces ends with the expression it is part of
+        if matches!(c, ',' | ')' | ']' | '}') {
+            while matches!(stack.last(), Some(Scope::Expression(_))) {
+                stack.pop();
+            }
+        }
+
+        match c {
+            '\'' | '"' => {
+                i = skip_string(&chars, i, c);
+                continue;
+            }
+            '/' if chars.get(i + 1).is_some_and(|(_, c)| *c == '/') => {
+                while i < chars.len() && chars[i].1 != '\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            '/' if chars.get(i + 1).is_some_and(|(_, c)| *c == '*') => {
+                i += 2;
+                while i < chars.len()
+                    && !(chars[i].1 == '*' && chars.get(i + 1).is_some_and(|(_, c)| *c == '/'))
+                {
+                    i += 1;
+                }
+                i += 2;
+                continue;
+            }
+            '/' if starts_regex(&chars, i) => {
+                i = skip_regex(&chars, i);
+                continue;
+            }
+            '`' => stack.push(Scope::TemplateLiteral),
+            '=' if chars.get(i + 1).is_some_and(|(_, c)| *c == '>') => {
+                // the params are local to the function body
+                let params = std::mem::take(&mut arrow_params);
+                match next_non_whitespace(&chars, i + 2) {
+                    Some(next) if chars[next].1 == '{' => {
+                        stack.push(Scope::Block(params));
+                        i = next + 1;
+                    }
+                    _ => {
+                        stack.push(Scope::Expression(params));
+                        i += 2;
+                    }
+                }
+                continue;
+            }
+            '=' => {
+                let is_assignment = chars.get(i + 1).is_none_or(|(_, c)| *c != '=')
+                    && (i == 0 || !"=!<>".contains(chars[i - 1].1));
+                if is_assignment && in_pattern(&stack, &defaults) {
+                    defaults.push(stack.len());
+                }
+                // skip the rest of `==` and `===`
+                while chars.get(i + 1).is_some_and(|(_, c)| *c == '=') {
+                    i += 1;
+                }
+            }
+            ',' if defaults.last() == Some(&stack.len()) => {
+                defaults.pop();
+            }
+            '(' => {
+                if arrow_params_end(&chars, i).is_some() {
+                    stack.push(Scope::Params(HashSet::new()));
+                } else {
+                    stack.push(Scope::Paren);
+                }
+            }
+            '[' => stack.push(Scope::Bracket),
+            '{' => {
+                // a statement block within a function body
+                let is_block = matches!(stack.last(), Some(Scope::Block(_)))
+                    && prev_non_whitespace(&chars, i)
+                        .is_none_or(|prev| "){};".contains(chars[prev].1))
+                    || prev_word(&chars, i) == "else";
+                if is_block {
+                    stack.push(Scope::Block(HashSet::new()));
+                } else {
+                    stack.push(Scope::Object);
+                }
+            }
+            ')' | ']' | '}' => {
+                if let Some(Scope::Params(params)) = stack.pop() {
+                    arrow_params = params;
+                }
+                while defaults.last().is_some_and(|d| *d > stack.len()) {
+                    defaults.pop();
+                }
+            }
+            c if c.is_ascii_digit() => {
+                // numeric literals, including `1e5`, `0x1f` and `1_000`
+                while i < chars.len() && (is_identifier_char(chars[i].1) || chars[i].1 == '.') {
+                    i += 1;
+                }
+                continue;
+            }
+            c if is_identifier_start(c) => {
+                let start = i;
+                while i < chars.len() && is_identifier_char(chars[i].1) {
+                    i += 1;
+                }
+                let name: String = chars[start..i].iter().map(|(_, c)| c).collect();
+                let prev = prev_non_whitespace(&chars, start);
+                let next = next_non_whitespace(&chars, i);
+                let prev_char = prev.map(|prev| chars[prev].1);
+                let next_char = next.map(|next| chars[next].1);
+
+                // member access, but not a spread
+                if let Some(prev) = prev
+                    && prev_char == Some('.')
+                    && !(prev >= 2 && chars[prev - 1].1 == '.' && chars[prev - 2].1 == '.')
+                {
+                    continue;
+                }
+                if is_keyword(&name) {
+                    continue;
+                }
+                let in_object_key_position =
+                    stack.last() == Some(&Scope::Object) && matches!(prev_char, Some('{' | ','));
+                // static property key
+                if in_object_key_position && next_char == Some(':') {
+                    continue;
+                }
+
+                let declared = if is_arrow_at(&chars, next) {
+                    // single param arrow function
+                    arrow_params = HashSet::from([name.clone()]);
+                    true
+                } else if in_pattern(&stack, &defaults) {
+                    if let Some(Scope::Params(params)) = stack
+                        .iter_mut()
+                        .rev()
+                        .find(|scope| matches!(scope, Scope::Params(_)))
+                    {
+                        params.insert(name.clone());
+                    }
+                    true
+                } else if matches!(prev_word(&chars, start).as_str(), "let" | "const" | "var") {
+                    if let Some(Scope::Block(ids) | Scope::Expression(ids)) = stack
+                        .iter_mut()
+                        .rev()
+                        .find(|scope| matches!(scope, Scope::Block(_) | Scope::Expression(_)))
+                    {
+                        ids.insert(name.clone());
+                    }
+                    true
+                } else {
+                    false
+                };
+
+                ids.push(Identifier {
+                    is_local: declared
+                        || stack
+                            .iter()
+                            .filter_map(Scope::declared)
+                            .any(|ids| ids.contains(&name)),
+                    shorthand: !declared
+                        && in_object_key_position
+                        && matches!(next_char, Some(',' | '}')),
+                    name,
+                    start: byte_offset(start),
+                    end: byte_offset(i),
+                    declared,
+                });
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    ids
+}
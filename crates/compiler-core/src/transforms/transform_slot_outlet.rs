@@ -0,0 +1,154 @@
+use crate::{
+    ast::{
+        BaseElementProps, CallArgument, CallCallee, CallExpression, ElementNode, ExpressionNode,
+        FunctionExpression, JSChildNode, PropsExpression, SimpleExpressionNode,
+        SlotOutletNodeCodegenNode, TemplateChildNode,
+    },
+    errors::{CompilerError, ErrorCodes},
+    runtime_helpers::RenderSlot,
+    transform::{NodeTransformState, TransformContext, TransformNode},
+    transforms::{
+        transform_element::{PropsBuildResult, build_props},
+        transform_expression::process_expression,
+    },
+    utils::is_static_arg_of,
+};
+use vue_compiler_shared::camelize;
+
+pub fn transform_slot_outlet(
+    node: &TransformNode,
+    _context: &mut TransformContext,
+) -> Option<Box<dyn NodeTransformState>> {
+    if matches!(
+        node,
+        TransformNode::TemplateChild(TemplateChildNode::Element(ElementNode::SlotOutlet(_)))
+    ) {
+        Some(Box::new(TransformSlotOutlet))
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TransformSlotOutlet;
+
+impl NodeTransformState for TransformSlotOutlet {
+    // runs on exit so that the fallback content has been transformed
+    fn exit(&mut self, node: &mut TransformNode, context: &mut TransformContext) {
+        let TransformNode::TemplateChild(TemplateChildNode::Element(node)) = node else {
+            return;
+        };
+
+        let SlotOutletProcessResult {
+            slot_name,
+            slot_props,
+        } = process_slot_outlet(node, context);
+
+        let mut slot_args = vec![
+            CallArgument::String(if context.prefix_identifiers {
+                "_ctx.$slots".to_string()
+            } else {
+                "$slots".to_string()
+            }),
+            slot_name,
+        ];
+        let children = node.children().clone();
+        if let Some(slot_props) = slot_props {
+            slot_args.push(CallArgument::JSChild(JSChildNode::from(slot_props)));
+        } else if !children.is_empty() {
+            slot_args.push(CallArgument::String("{}".to_string()));
+        }
+        if !children.is_empty() {
+            slot_args.push(CallArgument::JSChild(JSChildNode::Function(
+                FunctionExpression::new(None, Some(children), None, Some(node.loc().clone())),
+            )));
+        }
+
+        let ElementNode::SlotOutlet(node) = node else {
+            unreachable!();
+        };
+        node.codegen_node = Some(SlotOutletNodeCodegenNode::RenderSlotCall(
+            CallExpression::new(
+                CallCallee::Symbol(context.helper(RenderSlot.to_string())),
+                Some(slot_args),
+                Some(node.loc.clone()),
+            ),
+        ));
+    }
+}
+
+struct SlotOutletProcessResult {
+    slot_name: CallArgument,
+    slot_props: Option<PropsExpression>,
+}
+
+fn process_slot_outlet(
+    node: &mut ElementNode,
+    context: &mut TransformContext,
+) -> SlotOutletProcessResult {
+    let mut slot_name = CallArgument::String("\"default\"".to_string());
+    let mut slot_props = None;
+
+    let mut non_name_props = vec![];
+    for prop in node.props() {
+        match prop {
+            BaseElementProps::Attribute(attr) => {
+                if let Some(value) = &attr.value {
+                    if attr.name == "name" {
+                        slot_name = CallArgument::String(
+                            serde_json::to_string(&value.content)
+                                .unwrap_or_else(|_| value.content.clone()),
+                        );
+                    } else {
+                        let mut attr = attr.clone();
+                        attr.name = camelize(&attr.name);
+                        non_name_props.push(BaseElementProps::Attribute(attr));
+                    }
+                }
+            }
+            BaseElementProps::Directive(dir) => {
+                if dir.name == "bind" && is_static_arg_of(&dir.arg, "name") {
+                    let exp = dir.exp.clone().unwrap_or_else(|| {
+                        // :name shorthand
+                        let exp = SimpleExpressionNode::new(
+                            "name",
+                            Some(false),
+                            dir.arg.as_ref().map(|arg| arg.loc().clone()),
+                            None,
+                        );
+                        process_expression(exp, context, None)
+                    });
+                    slot_name = CallArgument::JSChild(JSChildNode::from(exp));
+                } else {
+                    let mut dir = dir.clone();
+                    if dir.name == "bind"
+                        && let Some(ExpressionNode::Simple(arg)) = &mut dir.arg
+                        && arg.is_static
+                    {
+                        arg.content = camelize(&arg.content);
+                    }
+                    non_name_props.push(BaseElementProps::Directive(dir));
+                }
+            }
+        }
+    }
+
+    if !non_name_props.is_empty() {
+        let PropsBuildResult {
+            props, directives, ..
+        } = build_props(node, context, &non_name_props, false, false, false);
+        slot_props = props;
+
+        if !directives.is_empty() {
+            context.on_error(CompilerError::new(
+                ErrorCodes::XVSlotUnexpectedDirectiveOnSlotOutlet,
+                Some(directives[0].loc.clone()),
+            ));
+        }
+    }
+
+    SlotOutletProcessResult {
+        slot_name,
+        slot_props,
+    }
+}
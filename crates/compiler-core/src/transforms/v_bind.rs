@@ -1,7 +1,13 @@
 use crate::{
-    ast::{DirectiveNode, ElementNode, JSChildNode, Property},
+    ast::{
+        CompoundExpressionNode, CompoundExpressionNodeChild, DirectiveNode, ElementNode,
+        ExpressionNode, JSChildNode, Property, SimpleExpressionNode,
+    },
+    errors::{CompilerError, ErrorCodes},
     transform::{DirectiveTransform, DirectiveTransformResult, TransformContext},
+    transforms::transform_expression::process_expression,
 };
+use vue_compiler_shared::camelize;
 
 #[derive(Debug, Clone)]
 pub struct TransformBind;
@@ -10,22 +16,105 @@ impl DirectiveTransform for TransformBind {
     fn transform(
         &mut self,
         dir: &DirectiveNode,
-        _node: &ElementNode,
-        _context: &TransformContext,
+        _node: &mut ElementNode,
+        context: &mut TransformContext,
     ) -> DirectiveTransformResult {
+        let is_sync = dir.modifiers.iter().any(|m| m.content == "sync");
+        // `.sync` was removed in Vue 3 in favor of `v-model:arg`
+        if is_sync && !context.compat && context.global_compile_time_constants.__dev__ {
+            context.on_warn(CompilerError::new(
+                ErrorCodes::XVBindSyncModifier,
+                Some(dir.loc.clone()),
+            ));
+        }
+
         let Some(arg) = dir.arg.clone() else {
             unreachable!();
         };
-        let Some(exp) = dir.exp.clone() else {
-            unreachable!();
+        let exp = match dir.exp.clone() {
+            Some(exp) => exp,
+            // same-name shorthand, `:id` is `:id="id"`
+            None => {
+                // a prefixed dynamic argument is a compound expression
+                let ExpressionNode::Simple(arg) = &arg else {
+                    return invalid_same_name_argument(arg, context);
+                };
+                if !arg.is_static {
+                    return invalid_same_name_argument(
+                        ExpressionNode::Simple(arg.clone()),
+                        context,
+                    );
+                }
+                let exp = SimpleExpressionNode::new(
+                    camelize(&arg.content),
+                    Some(false),
+                    Some(arg.loc.clone()),
+                    None,
+                );
+                process_expression(exp, context, None)
+            }
         };
 
-        DirectiveTransformResult {
-            props: vec![Property::new(arg, JSChildNode::from(exp))],
+        let mut props = vec![Property::new(arg.clone(), JSChildNode::from(exp.clone()))];
+        if is_sync && context.compat {
+            // 2.x `:prop.sync="foo"` is `v-model:prop="foo"`
+            props.push(Property::new(
+                create_update_event_name(arg),
+                JSChildNode::Compound(CompoundExpressionNode::new(
+                    vec![
+                        CompoundExpressionNodeChild::String("$event => ((".to_string()),
+                        to_compound_child(exp),
+                        CompoundExpressionNodeChild::String(") = $event)".to_string()),
+                    ],
+                    None,
+                )),
+            ));
         }
+
+        DirectiveTransformResult { props }
     }
 
     fn clone_box(&self) -> Box<dyn DirectiveTransform> {
         Box::new(self.clone())
     }
 }
+
+fn create_update_event_name(arg: ExpressionNode) -> ExpressionNode {
+    match arg {
+        ExpressionNode::Simple(arg) if arg.is_static => ExpressionNode::Simple(
+            SimpleExpressionNode::constant(format!("onUpdate:{}", arg.content)),
+        ),
+        arg => ExpressionNode::Compound(CompoundExpressionNode::new(
+            vec![
+                CompoundExpressionNodeChild::String("\"onUpdate:\" + ".to_string()),
+                to_compound_child(arg),
+            ],
+            None,
+        )),
+    }
+}
+
+fn to_compound_child(exp: ExpressionNode) -> CompoundExpressionNodeChild {
+    match exp {
+        ExpressionNode::Simple(exp) => CompoundExpressionNodeChild::Simple(exp),
+        ExpressionNode::Compound(exp) => CompoundExpressionNodeChild::Compound(exp),
+    }
+}
+
+/// Dynamic arguments have no name to bind to, they are bound to an empty
+/// string instead.
+fn invalid_same_name_argument(
+    arg: ExpressionNode,
+    context: &mut TransformContext,
+) -> DirectiveTransformResult {
+    context.on_error(CompilerError::new(
+        ErrorCodes::XVBindInvalidSameNameArgument,
+        Some(arg.loc().clone()),
+    ));
+    DirectiveTransformResult {
+        props: vec![Property::new(
+            arg,
+            JSChildNode::Simple(SimpleExpressionNode::constant(String::new())),
+        )],
+    }
+}
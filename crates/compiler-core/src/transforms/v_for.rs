@@ -1,15 +1,20 @@
 use crate::{
     ast::{
-        BlockCodegenNode, CallCallee, ConstantTypes, ElementNode, ExpressionNode, ForCodegenNode,
-        ForIteratorExpression, ForNode, ForParseResult, ForRenderListArgument,
-        ForRenderListExpression, FunctionParams, PlainElementNodeCodegenNode, TemplateChildNode,
-        VNodeCall, VNodeCallTag,
+        BaseElementProps, BlockCodegenNode, CallCallee, ComponentNode, ComponentNodeCodegenNode,
+        ConstantTypes, ElementNode, ElementTypes, ExpressionNode, ForCodegenNode,
+        ForIteratorExpression, ForNode, ForNodeCodegenNode, ForParseResult, ForRenderListArgument,
+        ForRenderListExpression, FunctionParams, JSChildNode, ObjectExpression, PlainElementNode,
+        PlainElementNodeCodegenNode, Property, PropsExpression, SlotOutletNodeCodegenNode,
+        TemplateChildNode, TemplateNode, TemplateNodeCodegenNode, VNodeCall, VNodeCallChildren,
+        VNodeCallTag, get_vnode_block_helper, get_vnode_helper,
     },
-    runtime_helpers::{Fragment, RenderList},
+    errors::{CompilerError, ErrorCodes},
+    runtime_helpers::{Fragment, OpenBlock, RenderList},
     transform::{
         NodeTransformState, StructuralDirectiveTransform, TransformContext, TransformNode,
     },
-    utils::find_prop,
+    transforms::transform_expression::process_expression,
+    utils::{find_dir, find_prop, inject_prop, inject_render_slot_prop},
 };
 use vue_compiler_shared::PatchFlags;
 
@@ -18,14 +23,14 @@ pub fn transform_for(
     _context: &mut TransformContext,
 ) -> Option<Box<dyn NodeTransformState>> {
     if node.children().is_some() {
-        Some(Box::new(TransformFor::default()))
+        Some(Box::new(TransformFor))
     } else {
         None
     }
 }
 
 #[derive(Debug, Clone, Default)]
-pub struct TransformFor(Vec<usize>);
+pub struct TransformFor;
 
 impl StructuralDirectiveTransform for TransformFor {
     fn matches(&self, name: &String) -> bool {
@@ -54,9 +59,19 @@ impl NodeTransformState for TransformFor {
             };
 
             for dir in dirs {
-                let Some(parse_result) = dir.for_parse_result else {
-                    unreachable!();
+                let Some(mut parse_result) = dir.for_parse_result else {
+                    // leave the element in place, rendered without the loop
+                    let error = match dir.exp {
+                        Some(exp) => CompilerError::new(
+                            ErrorCodes::XVForMalformedExpression,
+                            Some(exp.loc().clone()),
+                        ),
+                        None => CompilerError::new(ErrorCodes::XVForNoExpression, Some(dir.loc)),
+                    };
+                    context.on_error(error);
+                    continue;
                 };
+                finalize_for_parse_result(&mut parse_result, context);
 
                 let ForParseResult {
                     source,
@@ -66,6 +81,14 @@ impl NodeTransformState for TransformFor {
                     ..
                 } = parse_result.clone();
 
+                let TemplateChildNode::Element(node) = &children[i] else {
+                    unreachable!();
+                };
+                let user_key = if node.tag_type() == ElementTypes::Template {
+                    find_prop(node, "key", Some(false), Some(true))
+                } else {
+                    None
+                };
                 let for_node = ForNode {
                     source,
                     value_alias: value,
@@ -73,13 +96,14 @@ impl NodeTransformState for TransformFor {
                     object_index_alias: index,
                     parse_result: parse_result,
                     children: vec![],
+                    user_key,
+                    is_once: find_dir(node, "once", Some(true)).is_some(),
                     codegen_node: None,
                     loc: dir.loc.clone(),
                 };
 
                 children.push(TemplateChildNode::For(for_node));
                 let node = children.swap_remove(i);
-                self.0.push(i);
                 if let TemplateChildNode::For(for_node) = &mut children[i] {
                     let TemplateChildNode::Element(node) = node else {
                         unreachable!();
@@ -97,54 +121,167 @@ impl NodeTransformState for TransformFor {
         }
     }
 
-    fn pre_exit(&mut self, node: &mut TransformNode, context: &mut TransformContext) {
-        let Some(children) = node.children_mut() else {
-            unreachable!();
+    // runs on the for node itself once its children have been transformed,
+    // before `v-once` caches the list
+    fn exit(&mut self, node: &mut TransformNode, context: &mut TransformContext) {
+        let TransformNode::TemplateChild(TemplateChildNode::For(for_node)) = node else {
+            return;
         };
-        for (key, index) in self.0.drain(..).enumerate() {
-            let TemplateChildNode::For(for_node) = &mut children[index] else {
-                unreachable!();
-            };
-
-            let is_stable_fragment = matches!(&for_node.source, ExpressionNode::Simple(node) if node.const_type > ConstantTypes::NotConstant);
+        let key_property = for_node
+            .user_key
+            .clone()
+            .and_then(create_key_property)
+            .map(|key_property| process_key_property(key_property, for_node, context));
 
-            let child_block = {
-                // Normal element v-for. Directly use the child's codegenNode
-                // but mark it as a block.
-                let TemplateChildNode::Element(ElementNode::PlainElement(node)) =
-                    &for_node.children[0]
-                else {
-                    unreachable!();
-                };
+        let is_stable_fragment = matches!(&for_node.source, ExpressionNode::Simple(node) if node.const_type > ConstantTypes::NotConstant);
 
-                let Some(PlainElementNodeCodegenNode::VNodeCall(mut child_block)) =
-                    node.codegen_node.clone()
-                else {
-                    unreachable!();
-                };
+        // a child cached by its own `v-once` is not a block
+        let need_fragment_wrapper = !matches!(
+            for_node.children.as_slice(),
+            [TemplateChildNode::Element(node)] if !is_cached(node)
+        );
+        let child_block = if need_fragment_wrapper {
+            // <template v-for="..."> with text or multi-elements
+            // should generate a fragment block for each loop
+            let tag = context.helper(Fragment.to_string());
+            BlockCodegenNode::VNodeCall(Box::new(
+                VNodeCall::builder(VNodeCallTag::Symbol(tag))
+                    .props(key_property.map(|key_property| {
+                        PropsExpression::Object(ObjectExpression::new(vec![key_property], None))
+                    }))
+                    .children(Some(VNodeCallChildren::TemplateChildNodeList(
+                        for_node.children.clone(),
+                    )))
+                    .patch_flag(Some(PatchFlags::StableFragment))
+                    .block(true)
+                    .build(context),
+            ))
+        } else if let [TemplateChildNode::Element(ElementNode::SlotOutlet(node))] =
+            for_node.children.as_slice()
+        {
+            // <slot v-for="..."> or <template v-for="..."><slot/></template>
+            let Some(SlotOutletNodeCodegenNode::RenderSlotCall(call)) = &node.codegen_node else {
+                unreachable!();
+            };
+            let mut child_block = call.clone();
+            if let Some(key_property) = key_property {
+                inject_render_slot_prop(&mut child_block, key_property, context);
+            }
+            BlockCodegenNode::RenderSlotCall(child_block)
+        } else {
+            // Normal element v-for. Directly use the child's codegenNode
+            // but mark it as a block.
+            let mut child_block = match &for_node.children[0] {
+                TemplateChildNode::Element(ElementNode::PlainElement(PlainElementNode {
+                    codegen_node: Some(PlainElementNodeCodegenNode::VNodeCall(child_block)),
+                    ..
+                }))
+                | TemplateChildNode::Element(ElementNode::Component(ComponentNode {
+                    codegen_node: Some(ComponentNodeCodegenNode::VNodeCall(child_block)),
+                    ..
+                }))
+                | TemplateChildNode::Element(ElementNode::Template(TemplateNode {
+                    codegen_node: Some(TemplateNodeCodegenNode::VNodeCall(child_block)),
+                    ..
+                })) => child_block.clone(),
+                // slot outlets are handled above, text and cached children
+                // need a fragment
+                _ => unreachable!(),
+            };
 
-                // TODO
-                child_block.is_block = !is_stable_fragment;
+            if let Some(key_property) = key_property {
+                inject_prop(&mut child_block, key_property, context);
+            }
 
-                BlockCodegenNode::VNodeCall(child_block)
-            };
+            let in_ssr = context.in_ssr;
+            let is_component = child_block.is_component;
+            if child_block.is_block == is_stable_fragment {
+                if child_block.is_block {
+                    // switch from block to vnode
+                    context.remove_helper(&OpenBlock.to_string());
+                    context.remove_helper(&get_vnode_block_helper(in_ssr, is_component));
+                } else {
+                    // switch from vnode to block
+                    context.remove_helper(&get_vnode_helper(in_ssr, is_component));
+                }
+            }
+            child_block.is_block = !is_stable_fragment;
+            if child_block.is_block {
+                context.helper(OpenBlock.to_string());
+                context.helper(get_vnode_block_helper(in_ssr, is_component));
+            } else {
+                context.helper(get_vnode_helper(in_ssr, is_component));
+            }
 
-            let Some(codegen_node) = &mut for_node.codegen_node else {
-                unreachable!();
-            };
-            let arguments = &mut codegen_node.children.arguments;
+            BlockCodegenNode::VNodeCall(Box::new(child_block))
+        };
 
-            arguments.push(ForRenderListArgument::ForIterator(ForIteratorExpression {
-                params: Some(FunctionParams::ExpressionList(create_for_loop_params(
-                    &for_node.parse_result,
-                    Default::default(),
-                ))),
+        let params = create_for_loop_params(&for_node.parse_result, Default::default());
+        let Some(ForNodeCodegenNode::ForCodegen(codegen_node)) = &mut for_node.codegen_node else {
+            unreachable!();
+        };
+        codegen_node
+            .children
+            .arguments
+            .push(ForRenderListArgument::ForIterator(ForIteratorExpression {
+                params: Some(FunctionParams::ExpressionList(params)),
                 returns: Some(child_block),
                 /* force newline */
                 newline: true,
             }));
+    }
+}
+
+fn is_cached(node: &ElementNode) -> bool {
+    match node {
+        ElementNode::PlainElement(node) => {
+            matches!(
+                node.codegen_node,
+                Some(PlainElementNodeCodegenNode::Cache(_))
+            )
+        }
+        ElementNode::Component(node) => {
+            matches!(node.codegen_node, Some(ComponentNodeCodegenNode::Cache(_)))
+        }
+        ElementNode::SlotOutlet(node) => {
+            matches!(node.codegen_node, Some(SlotOutletNodeCodegenNode::Cache(_)))
+        }
+        ElementNode::Template(node) => {
+            matches!(node.codegen_node, Some(TemplateNodeCodegenNode::Cache(_)))
+        }
+    }
+}
+
+pub fn finalize_for_parse_result(result: &mut ForParseResult, context: &TransformContext) {
+    if result.finalized {
+        return;
+    }
+
+    if !context.global_compile_time_constants.__browser__ && context.prefix_identifiers {
+        fn process(
+            exp: ExpressionNode,
+            context: &TransformContext,
+            as_params: bool,
+        ) -> ExpressionNode {
+            match exp {
+                ExpressionNode::Simple(exp) => process_expression(exp, context, Some(as_params)),
+                exp => exp,
+            }
         }
+
+        result.source = process(result.source.clone(), context, false);
+        result.key = result.key.take().map(|key| process(key, context, true));
+        result.index = result
+            .index
+            .take()
+            .map(|index| process(index, context, true));
+        result.value = result
+            .value
+            .take()
+            .map(|value| process(value, context, true));
     }
+
+    result.finalized = true;
 }
 
 fn process_codegen(for_node: &mut ForNode, node: &ElementNode, context: &mut TransformContext) {
@@ -166,24 +303,25 @@ fn process_codegen(for_node: &mut ForNode, node: &ElementNode, context: &mut Tra
     } else if key_prop.is_some() {
         PatchFlags::KeyedFragment
     } else {
+        if context.global_compile_time_constants.__dev__ {
+            // components are reused by position without a key, losing their state
+            let code = if node.tag_type() == ElementTypes::Component {
+                ErrorCodes::XVForComponentNoKey
+            } else {
+                ErrorCodes::XVForNoKey
+            };
+            context.on_warn(CompilerError::new(code, Some(node.loc().clone())));
+        }
         PatchFlags::UnkeyedFragment
     };
 
     let tag = context.helper(Fragment.to_string());
-    let codegen_node = VNodeCall::new(
-        Some(context),
-        VNodeCallTag::Symbol(tag),
-        None,
-        None,
-        Some(fragment_flag),
-        /* isBlock */
-        Some(true),
-        /* disableTracking */
-        Some(!is_stable_fragment),
-        /* isComponent */
-        Some(false),
-        Some(for_node.loc.clone()),
-    );
+    let codegen_node = VNodeCall::builder(VNodeCallTag::Symbol(tag))
+        .patch_flag(Some(fragment_flag))
+        .block(true)
+        .disable_tracking(!is_stable_fragment)
+        .loc(for_node.loc.clone())
+        .build(context);
     let Some(patch_flag) = codegen_node.patch_flag else {
         unreachable!();
     };
@@ -199,7 +337,50 @@ fn process_codegen(for_node: &mut ForNode, node: &ElementNode, context: &mut Tra
         loc: codegen_node.loc,
     };
 
-    for_node.codegen_node = Some(codegen_node);
+    for_node.codegen_node = Some(ForNodeCodegenNode::ForCodegen(Box::new(codegen_node)));
+}
+
+fn create_key_property(key: BaseElementProps) -> Option<Property> {
+    let key_exp = match key {
+        BaseElementProps::Attribute(prop) => prop.value.map(|value| {
+            ExpressionNode::new_simple(value.content, Some(true), Some(value.loc), None)
+        }),
+        BaseElementProps::Directive(prop) => prop.exp,
+    }?;
+    Some(Property::new(
+        ExpressionNode::new_simple("key", Some(true), None, None),
+        JSChildNode::from(key_exp),
+    ))
+}
+
+/// The `key` of a `<template v-for>` is only processed once the aliases are
+/// known, so that it can reference them.
+fn process_key_property(
+    mut key_property: Property,
+    for_node: &ForNode,
+    context: &mut TransformContext,
+) -> Property {
+    let JSChildNode::Simple(exp) = &key_property.value else {
+        return key_property;
+    };
+    if exp.is_static || !context.prefix_identifiers {
+        return key_property;
+    }
+    let aliases = [
+        &for_node.value_alias,
+        &for_node.key_alias,
+        &for_node.object_index_alias,
+    ];
+    aliases
+        .into_iter()
+        .flatten()
+        .for_each(|alias| context.add_identifiers(alias));
+    key_property.value = JSChildNode::from(process_expression(exp.clone(), context, None));
+    aliases
+        .into_iter()
+        .flatten()
+        .for_each(|alias| context.remove_identifiers(alias));
+    key_property
 }
 
 fn create_for_loop_params(
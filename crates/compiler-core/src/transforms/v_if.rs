@@ -1,16 +1,18 @@
 use crate::{
     ast::{
-        CallArgument, CallExpression, ComponentNodeCodegenNode, ElementNode, ExpressionNode,
-        IfBranchNode, IfCodegenNode, IfConditionalExpression, IfNode, JSChildNode, NodeTypes,
-        ObjectExpression, PlainElementNodeCodegenNode, Property, PropsExpression,
-        SimpleExpressionNode, SourceLocation, TemplateChildNode, VNodeCall, VNodeCallChildren,
-        VNodeCallTag, convert_to_block,
+        CallArgument, CallCallee, CallExpression, ComponentNodeCodegenNode, ElementNode,
+        ExpressionNode, IfBranchNode, IfCodegenNode, IfConditionalExpression, IfNode, JSChildNode,
+        NodeTypes, ObjectExpression, PlainElementNodeCodegenNode, Property, PropsExpression,
+        SimpleExpressionNode, SlotOutletNodeCodegenNode, SourceLocation, TemplateChildNode,
+        TemplateNodeCodegenNode, VNodeCall, VNodeCallChildren, VNodeCallTag, convert_to_block,
     },
+    errors::{CompilerError, ErrorCodes},
     runtime_helpers::{CreateComment, Fragment},
     transform::{
         NodeTransformState, StructuralDirectiveTransform, TransformContext, TransformNode,
     },
-    utils::inject_prop,
+    transforms::transform_expression::process_expression,
+    utils::{find_dir, inject_prop, inject_render_slot_prop},
 };
 use vue_compiler_shared::PatchFlags;
 
@@ -31,17 +33,39 @@ struct TransformIfState {
 }
 
 fn exit(context: &mut TransformContext, if_node: &mut IfNode, key: usize) {
-    let branch = if_node.branches[0].clone();
-
-    let Some(condition) = branch.condition.clone() else {
+    let mut branches = if_node.branches.clone().into_iter().enumerate();
+    let Some((_, branch)) = branches.next() else {
+        unreachable!();
+    };
+    let JSChildNode::IfConditional(codegen_node) =
+        create_codegen_node_for_branch(branch, key, context)
+    else {
         unreachable!();
     };
+    let mut codegen_node = IfCodegenNode::IfConditional(*codegen_node);
+    for (i, branch) in branches {
+        // attach this branch's codegen node to the v-if root.
+        let parent_condition = codegen_node.get_parent_condition();
+        parent_condition.alternate = create_codegen_node_for_branch(branch, key + i, context);
+    }
+    if_node.codegen_node = Some(codegen_node);
+}
 
-    let mut codegen_node = IfCodegenNode::IfConditional(IfConditionalExpression {
+/// `v-if` and `v-else-if` branches test their condition and fall back to a
+/// comment, which the following branch replaces; `v-else` renders directly.
+fn create_codegen_node_for_branch(
+    branch: IfBranchNode,
+    key_index: usize,
+    context: &mut TransformContext,
+) -> JSChildNode {
+    let Some(condition) = branch.condition.clone() else {
+        return create_children_codegen_node(branch, key_index, context);
+    };
+    JSChildNode::IfConditional(Box::new(IfConditionalExpression {
         test: JSChildNode::from(condition),
-        consequent: create_children_codegen_node(branch, key, context),
+        consequent: create_children_codegen_node(branch, key_index, context),
         alternate: JSChildNode::Call(CallExpression::new(
-            context.helper(CreateComment.to_string()),
+            CallCallee::Symbol(context.helper(CreateComment.to_string())),
             Some(vec![
                 if context.global_compile_time_constants.__dev__ {
                     CallArgument::String("\"v-if\"".to_string())
@@ -53,14 +77,7 @@ fn exit(context: &mut TransformContext, if_node: &mut IfNode, key: usize) {
             None,
         )),
         newline: true,
-    });
-    let branches = if_node.branches.clone().drain(1..).collect::<Vec<_>>();
-    for (i, branch) in branches.into_iter().enumerate() {
-        // attach this branch's codegen node to the v-if root.
-        let parent_condition = codegen_node.get_parent_condition();
-        parent_condition.alternate = create_children_codegen_node(branch, key + i + 1, context);
-    }
-    if_node.codegen_node = Some(codegen_node);
+    }))
 }
 
 #[derive(Debug, Clone, Default)]
@@ -93,8 +110,23 @@ impl NodeTransformState for TransformIf {
             };
 
             let mut node_removed = false;
-            for dir in dirs {
+            for mut dir in dirs {
+                if let Some(ExpressionNode::Simple(exp)) = &dir.exp {
+                    dir.exp = Some(process_expression(exp.clone(), context, None));
+                }
                 if dir.name == "if" {
+                    if let TemplateChildNode::Element(node) = &children[i]
+                        && find_dir(node, "for", None).is_some()
+                        && !context.compat
+                        && context.global_compile_time_constants.__dev__
+                    {
+                        // v-if is applied first, so its condition can't see the v-for alias.
+                        // in compat mode the parser has already reported the precedence change
+                        context.on_warn(CompilerError::new(
+                            ErrorCodes::XVIfVForPrecedence,
+                            Some(node.loc().clone()),
+                        ));
+                    }
                     let if_node = if let TemplateChildNode::Element(node) = &children[i] {
                         let branch = IfBranchNode::new(node, dir);
                         let if_node = IfNode {
@@ -127,22 +159,14 @@ impl NodeTransformState for TransformIf {
                             };
                             debug_assert!(!node_removed);
                             node_removed = true;
+                            // the branch is traversed along with the if node's
+                            // other branches
                             let branch = IfBranchNode::new(&node, dir.clone());
-
-                            let mut branch = TemplateChildNode::IfBranch(branch);
-                            let transform_node = TransformNode::TemplateChild(&mut branch);
-                            // since the branch was removed, it will not be traversed.
-                            // make sure to traverse here.
-                            context.traverse_node(transform_node);
-
-                            let TemplateChildNode::IfBranch(branch) = branch else {
-                                unreachable!();
-                            };
                             let TemplateChildNode::If(sibling) = &mut children[j] else {
                                 unreachable!();
                             };
                             sibling.branches.push(branch);
-                        } else {
+                            break;
                         }
                     }
                 }
@@ -154,6 +178,15 @@ impl NodeTransformState for TransformIf {
         }
     }
 
+    fn on_node_removed(&mut self, index: usize, _context: &mut TransformContext) {
+        self.0.retain(|state| state.index != index);
+        for state in &mut self.0 {
+            if state.index > index {
+                state.index -= 1;
+            }
+        }
+    }
+
     fn pre_exit(&mut self, node: &mut TransformNode, context: &mut TransformContext) {
         let Some(children) = node.children_mut() else {
             unreachable!();
@@ -188,52 +221,68 @@ fn create_children_codegen_node(
         let patch_flag = PatchFlags::StableFragment;
 
         let tag = context.helper(Fragment.to_string());
-        JSChildNode::VNodeCall(VNodeCall::new(
-            Some(context),
-            VNodeCallTag::Symbol(tag),
-            Some(PropsExpression::Object(ObjectExpression::new(
-                vec![key_property],
-                None,
-            ))),
-            Some(VNodeCallChildren::TemplateChildNodeList(children)),
-            Some(patch_flag),
-            Some(true),
-            Some(false),
-            /* isComponent */
-            Some(false),
-            Some(branch.loc),
-        ))
+        JSChildNode::VNodeCall(
+            VNodeCall::builder(VNodeCallTag::Symbol(tag))
+                .props(Some(PropsExpression::Object(ObjectExpression::new(
+                    vec![key_property],
+                    None,
+                ))))
+                .children(Some(VNodeCallChildren::TemplateChildNodeList(children)))
+                .patch_flag(Some(patch_flag))
+                .block(true)
+                .loc(branch.loc)
+                .build(context),
+        )
     } else {
-        let mut ret = if let TemplateChildNode::Element(node) = &children[0] {
-            match node {
-                ElementNode::PlainElement(node) => match &node.codegen_node {
-                    Some(PlainElementNodeCodegenNode::VNodeCall(node)) => {
-                        JSChildNode::VNodeCall(node.clone())
-                    }
-                    _ => {
-                        todo!()
-                    }
-                },
-                ElementNode::Component(node) => match &node.codegen_node {
-                    Some(ComponentNodeCodegenNode::VNodeCall(node)) => {
-                        JSChildNode::VNodeCall(node.clone())
-                    }
-                    _ => {
-                        todo!()
-                    }
-                },
-                _ => {
-                    todo!()
-                }
-            }
-        } else {
+        let TemplateChildNode::Element(node) = &children[0] else {
             unreachable!();
         };
+        // the codegen nodes are created before hoisting, a branch root is
+        // never a hoisted `SimpleExpressionNode`
+        let mut ret = match node {
+            ElementNode::PlainElement(node) => match &node.codegen_node {
+                Some(PlainElementNodeCodegenNode::VNodeCall(node)) => {
+                    JSChildNode::VNodeCall(node.clone())
+                }
+                Some(PlainElementNodeCodegenNode::Cache(node)) => {
+                    JSChildNode::Cache(Box::new(node.clone()))
+                }
+                _ => unreachable!(),
+            },
+            ElementNode::Component(node) => match &node.codegen_node {
+                Some(ComponentNodeCodegenNode::VNodeCall(node)) => {
+                    JSChildNode::VNodeCall(node.clone())
+                }
+                Some(ComponentNodeCodegenNode::Cache(node)) => {
+                    JSChildNode::Cache(Box::new(node.clone()))
+                }
+                None => unreachable!(),
+            },
+            ElementNode::Template(node) => match &node.codegen_node {
+                Some(TemplateNodeCodegenNode::VNodeCall(node)) => {
+                    JSChildNode::VNodeCall(node.clone())
+                }
+                Some(TemplateNodeCodegenNode::Cache(node)) => {
+                    JSChildNode::Cache(Box::new(node.clone()))
+                }
+                None => unreachable!(),
+            },
+            ElementNode::SlotOutlet(node) => match &node.codegen_node {
+                Some(SlotOutletNodeCodegenNode::RenderSlotCall(node)) => {
+                    JSChildNode::Call(node.clone())
+                }
+                Some(SlotOutletNodeCodegenNode::Cache(node)) => JSChildNode::Cache(node.clone()),
+                None => unreachable!(),
+            },
+        };
 
-        if let JSChildNode::VNodeCall(node) = &mut ret {
-            convert_to_block(node, context);
-
-            inject_prop(node, key_property, context);
+        match &mut ret {
+            JSChildNode::VNodeCall(node) => {
+                convert_to_block(node, context);
+                inject_prop(node, key_property, context);
+            }
+            JSChildNode::Call(node) => inject_render_slot_prop(node, key_property, context),
+            _ => {}
         }
         ret
     }
@@ -0,0 +1,105 @@
+use crate::{
+    ast::{
+        CacheExpression, ComponentNodeCodegenNode, ElementNode, ForNodeCodegenNode, JSChildNode,
+        PlainElementNodeCodegenNode, SlotOutletNodeCodegenNode, TemplateChildNode,
+        TemplateNodeCodegenNode, VNodeCall, VNodeCallTag,
+    },
+    runtime_helpers::{Fragment, SetBlockTracking},
+    transform::{NodeTransformState, TransformContext, TransformNode},
+    utils::find_dir,
+};
+
+pub fn transform_once(
+    node: &TransformNode,
+    context: &mut TransformContext,
+) -> Option<Box<dyn NodeTransformState>> {
+    let is_once = match node {
+        TransformNode::TemplateChild(TemplateChildNode::Element(node)) => {
+            find_dir(node, "once", Some(true)).is_some()
+        }
+        // `v-once` on a `v-for` element caches the list rather than each item
+        TransformNode::TemplateChild(TemplateChildNode::For(node)) => node.is_once,
+        _ => false,
+    };
+    if !is_once || context.in_v_once || context.in_ssr {
+        return None;
+    }
+    context.in_v_once = true;
+    context.helper(SetBlockTracking.to_string());
+    Some(Box::new(TransformOnce))
+}
+
+#[derive(Debug, Clone)]
+pub struct TransformOnce;
+
+impl NodeTransformState for TransformOnce {
+    // runs after the element and for transforms have created the codegen node
+    // to cache
+    fn exit(&mut self, node: &mut TransformNode, context: &mut TransformContext) {
+        context.in_v_once = false;
+        let node = match node {
+            TransformNode::TemplateChild(TemplateChildNode::Element(node)) => node,
+            TransformNode::TemplateChild(TemplateChildNode::For(node)) => {
+                if let Some(ForNodeCodegenNode::ForCodegen(codegen_node)) = node.codegen_node.take()
+                {
+                    let cache = cache(
+                        JSChildNode::VNodeCall(VNodeCall::from(*codegen_node)),
+                        context,
+                    );
+                    node.codegen_node = Some(ForNodeCodegenNode::Cache(Box::new(cache)));
+                }
+                return;
+            }
+            _ => return,
+        };
+        match node {
+            ElementNode::PlainElement(node) => {
+                if let Some(PlainElementNodeCodegenNode::VNodeCall(vnode_call)) =
+                    node.codegen_node.take()
+                {
+                    let cache = cache(JSChildNode::VNodeCall(vnode_call), context);
+                    node.codegen_node = Some(PlainElementNodeCodegenNode::Cache(cache));
+                }
+            }
+            ElementNode::Component(node) => {
+                if let Some(ComponentNodeCodegenNode::VNodeCall(vnode_call)) =
+                    node.codegen_node.take()
+                {
+                    let cache = cache(JSChildNode::VNodeCall(vnode_call), context);
+                    node.codegen_node = Some(ComponentNodeCodegenNode::Cache(cache));
+                }
+            }
+            ElementNode::SlotOutlet(node) => {
+                if let Some(SlotOutletNodeCodegenNode::RenderSlotCall(call)) =
+                    node.codegen_node.take()
+                {
+                    let cache = cache(JSChildNode::Call(call), context);
+                    node.codegen_node = Some(SlotOutletNodeCodegenNode::Cache(Box::new(cache)));
+                }
+            }
+            ElementNode::Template(node) => {
+                if let Some(TemplateNodeCodegenNode::VNodeCall(vnode_call)) =
+                    node.codegen_node.take()
+                {
+                    let cache = cache(JSChildNode::VNodeCall(vnode_call), context);
+                    node.codegen_node = Some(TemplateNodeCodegenNode::Cache(cache));
+                }
+            }
+        }
+    }
+}
+
+/// Caches `value`, spreading a cached fragment or array into a fresh array so
+/// the renderer does not mutate the cached children.
+fn cache(value: JSChildNode, context: &mut TransformContext) -> CacheExpression {
+    let need_array_spread = match &value {
+        JSChildNode::VNodeCall(vnode_call) => {
+            matches!(&vnode_call.tag, VNodeCallTag::Symbol(tag) if *tag == Fragment.to_string())
+        }
+        JSChildNode::Array(_) => true,
+        _ => false,
+    };
+    let mut cache = context.cache(value, true);
+    cache.need_array_spread = need_array_spread;
+    cache
+}
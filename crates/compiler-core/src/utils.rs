@@ -1,9 +1,15 @@
 use crate::{
     ast::{
-        BaseElementProps, DirectiveNode, ElementNode, ExpressionNode, NodeTypes, ObjectExpression,
-        Property, PropsExpression, VNodeCall,
+        BaseElementProps, CallArgument, CallCallee, CallExpression, DirectiveNode, ElementNode,
+        ExpressionNode, JSChildNode, NodeTypes, ObjectExpression, Position, Property,
+        PropsExpression, VNodeCall,
     },
     codegen::AssetType,
+    options::IsBuiltInComponent,
+    runtime_helpers::{
+        BaseTransition, GuardReactiveProps, KeepAlive, MergeProps, NormalizeProps, Suspense,
+        Teleport,
+    },
     tokenizer::is_whitespace,
     transform::TransformContext,
 };
@@ -18,14 +24,26 @@ pub fn is_v_pre(p: &BaseElementProps) -> bool {
 
 pub fn is_core_component(tag: &str) -> Option<String> {
     match tag {
-        "Teleport" | "teleport" => Some("TELEPORT".to_string()),
-        "Suspense" | "suspense" => Some("SUSPENSE".to_string()),
-        "KeepAlive" | "keep-alive" => Some("KEEP_ALIVE".to_string()),
-        "BaseTransition" | "base-transition" => Some("BASE_TRANSITION".to_string()),
+        "Teleport" | "teleport" => Some(Teleport.to_string()),
+        "Suspense" | "suspense" => Some(Suspense.to_string()),
+        "KeepAlive" | "keep-alive" => Some(KeepAlive.to_string()),
+        "BaseTransition" | "base-transition" => Some(BaseTransition.to_string()),
         _ => None,
     }
 }
 
+/// Resolves `tag` to the runtime symbol of a built-in component, checking the
+/// core components before the platform's `is_built_in_component` option, e.g.
+/// `Transition` from compiler-dom.
+pub fn resolve_built_in_component(
+    tag: &String,
+    is_built_in_component: Option<&IsBuiltInComponent>,
+) -> Option<String> {
+    is_core_component(tag).or_else(|| {
+        is_built_in_component.and_then(|is_built_in_component| is_built_in_component(tag))
+    })
+}
+
 pub fn is_simple_identifier(name: &str) -> bool {
     if name.is_empty() {
         return false;
@@ -53,6 +71,28 @@ pub fn is_simple_identifier(name: &str) -> bool {
     true
 }
 
+pub fn advance_position_with_clone(pos: &Position, source: &str) -> Position {
+    let mut lines_count = 0;
+    let mut last_new_line_pos = None;
+    // offsets are byte offsets, same as the positions recorded by the tokenizer
+    let number_of_characters = source.len();
+    for (i, c) in source.bytes().enumerate() {
+        if c == b'\n' {
+            lines_count += 1;
+            last_new_line_pos = Some(i);
+        }
+    }
+
+    Position {
+        offset: pos.offset + number_of_characters,
+        line: pos.line + lines_count,
+        column: match last_new_line_pos {
+            Some(last_new_line_pos) => number_of_characters - last_new_line_pos,
+            None => pos.column + number_of_characters,
+        },
+    }
+}
+
 pub fn find_dir(
     node: &ElementNode,
     name: &str,
@@ -120,94 +160,120 @@ pub fn is_text(type_: NodeTypes) -> bool {
     matches!(type_, NodeTypes::Text | NodeTypes::Interpolation)
 }
 
+/// Injects `prop` (the `key` of a v-if branch or a `<template v-for>`) into the
+/// props of `node`, leaving a user-provided prop with the same key in place.
 pub fn inject_prop(node: &mut VNodeCall, prop: Property, context: &mut TransformContext) {
-    // ObjectExpression | CallExpression | undefined;
-    let mut props_with_injection = None::<PropsExpression>;
-    // /**
-    //  * 1. mergeProps(...)
-    //  * 2. toHandlers(...)
-    //  * 3. normalizeProps(...)
-    //  * 4. normalizeProps(guardReactiveProps(...))
-    //  *
-    //  * we need to get the real props before normalization
-    //  */
-    // let props =
-    //   node.type === NodeTypes.VNODE_CALL ? node.props : node.arguments[2]
-    // let callPath: CallExpression[] = []
-    // let parentCall: CallExpression | undefined
-    // if (
-    //   props &&
-    //   !isString(props) &&
-    //   props.type === NodeTypes.JS_CALL_EXPRESSION
-    // ) {
-    //   const ret = getUnnormalizedProps(props)
-    //   props = ret[0]
-    //   callPath = ret[1]
-    //   parentCall = callPath[callPath.length - 1]
-    // }
+    node.props = Some(match node.props.take() {
+        None => PropsExpression::Object(ObjectExpression::new(vec![prop], None)),
+        Some(props) => match inject_prop_into(JSChildNode::from(props), prop, context) {
+            JSChildNode::Object(props) => PropsExpression::Object(props),
+            JSChildNode::Call(props) => PropsExpression::Call(props),
+            _ => unreachable!(),
+        },
+    });
+}
+
+/// Same as [`inject_prop`] for a `renderSlot(slots, name, props?, fallback?)`
+/// call, e.g. a `<slot>` rendered by `v-if` or `v-for`.
+pub fn inject_render_slot_prop(
+    node: &mut CallExpression,
+    prop: Property,
+    context: &mut TransformContext,
+) {
+    let props = if node.arguments.len() > 2 {
+        Some(node.arguments.remove(2))
+    } else {
+        None
+    };
+    let props = match props {
+        Some(CallArgument::JSChild(props)) => inject_prop_into(props, prop, context),
+        // no props, or the `{}` placeholder before the fallback
+        _ => JSChildNode::Object(ObjectExpression::new(vec![prop], None)),
+    };
+    node.arguments.insert(2, CallArgument::JSChild(props));
+}
 
-    if node.props.is_none() {
-        props_with_injection = Some(PropsExpression::Object(ObjectExpression::new(
-            vec![prop],
+fn inject_prop_into(
+    props: JSChildNode,
+    prop: Property,
+    context: &mut TransformContext,
+) -> JSChildNode {
+    match props {
+        JSChildNode::Object(mut props) => {
+            if !has_prop(&prop, &props) {
+                props.properties.insert(0, prop);
+            }
+            JSChildNode::Object(props)
+        }
+        // normalizeProps(...) / normalizeProps(guardReactiveProps(...)):
+        // inject into the real props before normalization
+        JSChildNode::Call(mut props)
+            if props.callee == CallCallee::Symbol(NormalizeProps.to_string())
+                && matches!(props.arguments.first(), Some(CallArgument::JSChild(_))) =>
+        {
+            let CallArgument::JSChild(first) = props.arguments.remove(0) else {
+                unreachable!();
+            };
+            let first = inject_prop_into(first, prop, context);
+            props.arguments.insert(0, CallArgument::JSChild(first));
+            JSChildNode::Call(props)
+        }
+        // the merged replacement no longer needs the guard, e.g.
+        // `normalizeProps(mergeProps({ key: 0 }, props))`
+        JSChildNode::Call(mut props)
+            if props.callee == CallCallee::Symbol(GuardReactiveProps.to_string())
+                && matches!(props.arguments.as_slice(), [CallArgument::JSChild(_)]) =>
+        {
+            let Some(CallArgument::JSChild(first)) = props.arguments.pop() else {
+                unreachable!();
+            };
+            context.remove_helper(&GuardReactiveProps.to_string());
+            inject_prop_into(first, prop, context)
+        }
+        // merged props, only inject key to object literal if it's the first
+        // argument so that it doesn't override user provided keys
+        JSChildNode::Call(mut props) => {
+            if let Some(CallArgument::JSChild(JSChildNode::Object(first))) =
+                props.arguments.first_mut()
+            {
+                if !has_prop(&prop, first) {
+                    first.properties.insert(0, prop);
+                }
+            } else {
+                props.arguments.insert(
+                    0,
+                    CallArgument::JSChild(JSChildNode::Object(ObjectExpression::new(
+                        vec![prop],
+                        None,
+                    ))),
+                );
+            }
+            JSChildNode::Call(props)
+        }
+        // single v-bind with expression, return a merged replacement
+        props => JSChildNode::Call(CallExpression::new(
+            CallCallee::Symbol(context.helper(MergeProps.to_string())),
+            Some(vec![
+                CallArgument::JSChild(JSChildNode::Object(ObjectExpression::new(vec![prop], None))),
+                CallArgument::JSChild(props),
+            ]),
             None,
-        )));
+        )),
     }
-    // } else if (props.type === NodeTypes.JS_CALL_EXPRESSION) {
-    //   // merged props... add ours
-    //   // only inject key to object literal if it's the first argument so that
-    //   // if doesn't override user provided keys
-    //   const first = props.arguments[0] as string | JSChildNode
-    //   if (!isString(first) && first.type === NodeTypes.JS_OBJECT_EXPRESSION) {
-    //     // #6631
-    //     if (!hasProp(prop, first)) {
-    //       first.properties.unshift(prop)
-    //     }
-    //   } else {
-    //     if (props.callee === TO_HANDLERS) {
-    //       // #2366
-    //       propsWithInjection = createCallExpression(context.helper(MERGE_PROPS), [
-    //         createObjectExpression([prop]),
-    //         props,
-    //       ])
-    //     } else {
-    //       props.arguments.unshift(createObjectExpression([prop]))
-    //     }
-    //   }
-    //   !propsWithInjection && (propsWithInjection = props)
-    // } else if (props.type === NodeTypes.JS_OBJECT_EXPRESSION) {
-    //   if (!hasProp(prop, props)) {
-    //     props.properties.unshift(prop)
-    //   }
-    //   propsWithInjection = props
-    // } else {
-    //   // single v-bind with expression, return a merged replacement
-    //   propsWithInjection = createCallExpression(context.helper(MERGE_PROPS), [
-    //     createObjectExpression([prop]),
-    //     props,
-    //   ])
-    //   // in the case of nested helper call, e.g. `normalizeProps(guardReactiveProps(props))`,
-    //   // it will be rewritten as `normalizeProps(mergeProps({ key: 0 }, props))`,
-    //   // the `guardReactiveProps` will no longer be needed
-    //   if (parentCall && parentCall.callee === GUARD_REACTIVE_PROPS) {
-    //     parentCall = callPath[callPath.length - 2]
-    //   }
-    // }
-    // if (node.type === NodeTypes.VNODE_CALL) {
-    //   if (parentCall) {
-    //     parentCall.arguments[0] = propsWithInjection
-    //   } else {
-    node.props = props_with_injection;
-    //   }
-    // } else {
-    //   if (parentCall) {
-    //     parentCall.arguments[0] = propsWithInjection
-    //   } else {
-    //     node.arguments[2] = propsWithInjection
-    //   }
-    // }
 }
 
-pub fn to_valid_asset_id(name: &String, type_: &AssetType) -> String {
+/// check existing key to avoid overriding user provided keys
+fn has_prop(prop: &Property, props: &ObjectExpression) -> bool {
+    let ExpressionNode::Simple(key) = &prop.key else {
+        return false;
+    };
+    key.is_static
+        && props.properties.iter().any(
+            |p| matches!(&p.key, ExpressionNode::Simple(p_key) if p_key.content == key.content),
+        )
+}
+
+pub fn to_valid_asset_id(name: &str, type_: &AssetType) -> String {
     // see issue#4422, we need adding identifier on validAssetId if variable `name` has specific character
     let name: String = name
         .chars()
@@ -223,59 +289,164 @@ pub fn to_valid_asset_id(name: &String, type_: &AssetType) -> String {
             }
         })
         .collect();
-    format!("_{}_{}", type_.to_string(), name)
+    format!("_{}_{}", type_, name)
 }
 
 /// forAliasRE: /([\s\S]*?)\s+(?:in|of)\s+(\S[\s\S]*)/
+/// Splits a v-for expression into its alias and source at the first `in` or
+/// `of` keyword surrounded by whitespace, skipping keywords nested in brackets
+/// or strings.
 pub fn match_for_alias(text: &str) -> Option<(String, String)> {
-    let mut in_text = text;
-    let mut in_left = String::new();
-
-    let mut of_text = text;
-    let mut of_left = String::new();
-
-    fn find(text: &mut &str, left_text: &mut String, pat: &str) -> Option<(String, String)> {
-        let Some((left, right)) = text.split_once(pat) else {
-            unreachable!();
-        };
-
-        if !left_text.is_empty() {
-            left_text.push_str(pat);
-        }
-        left_text.push_str(left);
-
-        if left.chars().last().is_some_and(|c| c.is_whitespace())
-            && right.chars().next().is_some_and(|c| c.is_whitespace())
-        {
-            return Some((
-                left_text.trim_end().to_string(),
-                right.trim_start().to_string(),
-            ));
+    let bytes = text.as_bytes();
+    let mut depth = 0usize;
+    let mut quote = None::<u8>;
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        if let Some(q) = quote {
+            if c == b'\\' {
+                i += 1;
+            } else if c == q {
+                quote = None;
+            }
+        } else {
+            match c {
+                b'\'' | b'"' | b'`' => quote = Some(c),
+                b'(' | b'[' | b'{' => depth += 1,
+                b')' | b']' | b'}' => depth = depth.saturating_sub(1),
+                c if depth == 0 && c.is_ascii_whitespace() => {
+                    let keyword = text[i..].trim_start();
+                    if (keyword.starts_with("in") || keyword.starts_with("of"))
+                        && keyword[2..].starts_with(char::is_whitespace)
+                    {
+                        let source = keyword[2..].trim_start();
+                        if !source.is_empty() {
+                            return Some((text[..i].trim_end().to_string(), source.to_string()));
+                        }
+                    }
+                }
+                _ => {}
+            }
         }
-        *text = right;
-        None
+        i += 1;
     }
-    loop {
-        let in_index = in_text.find("in");
-        let of_index = of_text.find("of");
-        let result = match (in_index, of_index) {
-            (Some(in_index), Some(of_index)) => {
-                if in_left.len() + in_index <= of_left.len() + of_index {
-                    find(&mut in_text, &mut in_left, "in")
-                } else {
-                    find(&mut of_text, &mut of_left, "of")
+    None
+}
+
+/// A lightweight stand-in for parsing an expression with a JS parser: only
+/// unbalanced brackets, unterminated literals and operators missing an operand
+/// are reported, as the message of the error.
+pub fn check_expression_syntax(exp: &str) -> Result<(), String> {
+    const OPERATORS: &str = "+-*/%&|^!~=<>?:.";
+
+    let chars: Vec<char> = exp.chars().collect();
+    let mut closers = Vec::new();
+    // the last significant char, string and regex literals count as `"`
+    let mut prev: Option<char> = None;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\'' | '"' | '`' => {
+                i += 1;
+                while i < chars.len() && chars[i] != c {
+                    if chars[i] == '\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                if i >= chars.len() {
+                    return Err("Unterminated string constant.".to_string());
                 }
+                prev = Some('"');
             }
-            (Some(_), None) => find(&mut in_text, &mut in_left, "in"),
-            (None, Some(_)) => find(&mut of_text, &mut of_left, "of"),
-            (None, None) => {
-                return None;
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
             }
-        };
-        if result.is_some() {
-            return result;
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                while i + 1 < chars.len() && !(chars[i] == '*' && chars[i + 1] == '/') {
+                    i += 1;
+                }
+                if i + 1 >= chars.len() {
+                    return Err("Unterminated comment.".to_string());
+                }
+                i += 1;
+            }
+            '/' if prev.is_none_or(|prev| "(,=:[!&|?{};+-*%<>~^".contains(prev)) => {
+                // regex literal
+                let mut in_class = false;
+                i += 1;
+                while i < chars.len() && (in_class || chars[i] != '/') {
+                    match chars[i] {
+                        '\\' => i += 1,
+                        '[' => in_class = true,
+                        ']' => in_class = false,
+                        _ => {}
+                    }
+                    i += 1;
+                }
+                if i >= chars.len() {
+                    return Err("Unterminated regular expression.".to_string());
+                }
+                prev = Some('"');
+            }
+            '(' | '[' | '{' => {
+                closers.push(match c {
+                    '(' => ')',
+                    '[' => ']',
+                    _ => '}',
+                });
+                prev = Some(c);
+            }
+            ')' | ']' | '}' => {
+                if closers.pop() != Some(c) {
+                    return Err(format!("Unexpected token `{c}`."));
+                }
+                prev = Some(c);
+            }
+            // postfix `++` / `--` and the TS non-null `!` keep ending an operand,
+            // so a following `/` is a division
+            '+' | '-' if chars.get(i + 1) == Some(&c) && prev.is_some_and(ends_operand) => {
+                i += 1;
+            }
+            '!' if chars.get(i + 1) != Some(&'=') && prev.is_some_and(ends_operand) => {}
+            c if !c.is_whitespace() => prev = Some(c),
+            _ => {}
         }
+        i += 1;
     }
+
+    if let Some(closer) = closers.last() {
+        return Err(format!("Unexpected token, expected `{closer}`."));
+    }
+    let trimmed = exp.trim();
+    if let Some(first) = trimmed.chars().next()
+        && "*%&|^=?:,>".contains(first)
+    {
+        return Err(format!("Unexpected token `{first}`."));
+    }
+    if let Some(last) = prev
+        && OPERATORS.contains(last)
+        && !trimmed.ends_with("++")
+        && !trimmed.ends_with("--")
+        // a number like `1.`
+        && !(last == '.'
+            && trimmed
+                .strip_suffix('.')
+                .is_some_and(|rest| rest.ends_with(|c: char| c.is_ascii_digit())))
+    {
+        return Err(format!("Unexpected token after `{last}`."));
+    }
+    Ok(())
+}
+
+/// Whether `c` can be the last char of an operand, `"` stands for string and
+/// regex literals.
+fn ends_operand(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '$' | ')' | ']' | '"')
 }
 
 pub fn is_all_whitespace(str: &str) -> bool {
@@ -303,4 +474,24 @@ fn test_match_for_alias() {
             Some(("a".to_string(), "in b".to_string()))
         );
     }
+    let pair = |lhs: &str, rhs: &str| Some((lhs.to_string(), rhs.to_string()));
+    assert_eq!(match_for_alias("x of list"), pair("x", "list"));
+    assert_eq!(match_for_alias("x in obj.in"), pair("x", "obj.in"));
+    assert_eq!(match_for_alias("(a, b) in pairs"), pair("(a, b)", "pairs"));
+    assert_eq!(match_for_alias("in1 in list"), pair("in1", "list"));
+    assert_eq!(match_for_alias("index\tof\nlist"), pair("index", "list"));
+    assert_eq!(
+        match_for_alias("({ a = x in y }, i) in list"),
+        pair("({ a = x in y }, i)", "list")
+    );
+    assert_eq!(
+        match_for_alias("({ a = ' in ' }) of list"),
+        pair("({ a = ' in ' })", "list")
+    );
+    assert_eq!(
+        match_for_alias("[a, b] in { ...obj }"),
+        pair("[a, b]", "{ ...obj }")
+    );
+    assert!(match_for_alias("a in ").is_none());
+    assert!(match_for_alias("(a in b)").is_none());
 }
@@ -0,0 +1,68 @@
+#[cfg(test)]
+mod ast_constructors {
+    use vue_compiler_core::{
+        BaseElementProps, ConstantTypes, ElementNode, ExpressionNode, NodeTypes,
+        SimpleExpressionNode, SourceLocation, TemplateChildNode, base_parse,
+    };
+
+    #[test]
+    fn constant_expression() {
+        let exp = SimpleExpressionNode::constant("class");
+        assert_eq!(exp.content, "class");
+        assert!(exp.is_static);
+        assert_eq!(exp.const_type, ConstantTypes::CanStringify);
+        assert_eq!(exp.loc, SourceLocation::loc_stub());
+    }
+
+    #[test]
+    fn empty_text() {
+        let node = TemplateChildNode::empty_text(SourceLocation::loc_stub());
+        assert_eq!(node.type_(), NodeTypes::Text);
+        assert!(matches!(node, TemplateChildNode::Text(text) if text.content.is_empty()));
+    }
+
+    #[test]
+    fn text_from_borrowed_content() {
+        let content = "hello";
+        let node = TemplateChildNode::new_text(content, SourceLocation::loc_stub());
+        assert!(matches!(node, TemplateChildNode::Text(text) if text.content == content));
+    }
+
+    #[test]
+    fn into_component_moves_props_and_children() {
+        let mut ast = base_parse(r#"<div :a="1"><span/></div>"#, None);
+        let Some(TemplateChildNode::Element(element)) = ast.children.pop() else {
+            unreachable!();
+        };
+        let props = element.props().clone();
+        let children = element.children().clone();
+
+        let ElementNode::Component(component) = element.into_component() else {
+            unreachable!();
+        };
+        assert_eq!(component.tag, "div");
+        assert_eq!(component.props, props);
+        assert_eq!(component.children, children);
+        assert!(matches!(
+            &component.props[..],
+            [BaseElementProps::Directive(dir)]
+                if matches!(&dir.exp, Some(ExpressionNode::Simple(exp)) if exp.content == "1")
+        ));
+    }
+
+    #[test]
+    fn parse_component_props() {
+        let ast = base_parse(r#"<Foo :a="1"/>"#, None);
+        let [TemplateChildNode::Element(ElementNode::Component(component))] =
+            ast.children.as_slice()
+        else {
+            unreachable!();
+        };
+        assert!(matches!(
+            &component.props[..],
+            [BaseElementProps::Directive(dir)]
+                if dir.name == "bind"
+                    && matches!(&dir.exp, Some(ExpressionNode::Simple(exp)) if exp.content == "1")
+        ));
+    }
+}
@@ -3,16 +3,18 @@ mod test_utils;
 #[cfg(test)]
 mod compiler_codegen {
     use crate::test_utils::{create_element_with_codegen, gen_flag_text};
+    use insta::assert_snapshot;
     use vue_compiler_core::{
-        ArrayExpression, ArrayExpressionElement, CacheExpression, CallArgument, CallCallee,
-        CallExpression, CodegenMode, CodegenOptions, CodegenResult, CompoundExpressionNode,
-        CompoundExpressionNodeChild, CreateComment, CreateElementVNode, CreateVNode,
-        ExpressionNode, ForCodegenNode, ForNode, ForParseResult, ForRenderListExpression, Fragment,
-        IfCodegenNode, IfConditionalExpression, IfNode, InterpolationNode, JSChildNode,
-        ObjectExpression, Property, PropsExpression, RenderList, ResolveComponent,
-        ResolveDirective, RootCodegenNode, RootNode, SSRCodegenNode, SimpleExpressionNode,
-        SourceLocation, TemplateChildNode, TemplateLiteral, TemplateLiteralElement,
-        ToDisplayString, VNodeCallChildren, generate,
+        ArrayExpression, ArrayExpressionElement, BaseCompileSource, CacheExpression, CallArgument,
+        CallCallee, CallExpression, CodegenMode, CodegenOptions, CodegenResult, CompilerOptions,
+        CompoundExpressionNode, CompoundExpressionNodeChild, CreateComment, CreateElementVNode,
+        CreateVNode, ErrorCodes, ExpressionNode, ForCodegenNode, ForNode, ForNodeCodegenNode,
+        ForParseResult, ForRenderListExpression, Fragment, IfCodegenNode, IfConditionalExpression,
+        IfNode, InterpolationNode, JSChildNode, ObjectExpression, Property, PropsExpression,
+        RenderList, ResolveComponent, ResolveDirective, RootCodegenNode, RootNode, SSRCodegenNode,
+        SimpleExpressionNode, SourceLocation, TemplateChildNode, TemplateLiteral,
+        TemplateLiteralElement, ToDisplayString, VNodeCall, VNodeCallChildren, VNodeCallTag,
+        base_compile, base_parse, generate,
     };
     use vue_compiler_shared::PatchFlags;
 
@@ -201,6 +203,39 @@ mod compiler_codegen {
         assert!(code.contains("const _hoisted_2 = { id: \"foo\" }"));
     }
 
+    #[test]
+    fn object_expression_property_keys() {
+        let property = |key: &str, is_static: bool| {
+            Property::new(
+                ExpressionNode::new_simple(key, Some(is_static), None, None),
+                JSChildNode::Simple(SimpleExpressionNode::new("v", Some(false), None, None)),
+            )
+        };
+        let root = {
+            let mut root = RootNode::new(Vec::new(), None);
+            root.codegen_node = Some(RootCodegenNode::JSChild(JSChildNode::Object(
+                ObjectExpression::new(
+                    vec![
+                        property("data-x", true),
+                        property("0", true),
+                        property("class", true),
+                        property("foo", false),
+                    ],
+                    None,
+                ),
+            )));
+            root
+        };
+        let CodegenResult { code, .. } = generate(root, CodegenOptions::default());
+        // non-identifier and numeric keys are quoted
+        assert!(code.contains(r#""data-x": v,"#));
+        assert!(code.contains(r#""0": v,"#));
+        // reserved words are valid property names
+        assert!(code.contains("class: v,"));
+        // dynamic keys are computed
+        assert!(code.contains("[foo]: v"));
+    }
+
     #[test]
     fn temps() {
         let root = {
@@ -305,6 +340,65 @@ mod compiler_codegen {
         )));
     }
 
+    #[test]
+    fn deeply_nested_compound_expression() {
+        let simple = |content: &str| {
+            CompoundExpressionNodeChild::Simple(SimpleExpressionNode::new(
+                content,
+                Some(false),
+                Some(SourceLocation::loc_stub()),
+                None,
+            ))
+        };
+        let string = |content: &str| CompoundExpressionNodeChild::String(content.to_string());
+        let root = {
+            let mut root = RootNode::new(Vec::new(), None);
+            root.codegen_node = Some(RootCodegenNode::TemplateChild(
+                TemplateChildNode::new_compound(
+                    vec![
+                        simple("a"),
+                        string(" + "),
+                        CompoundExpressionNodeChild::Compound(CompoundExpressionNode::new(
+                            vec![
+                                simple("b"),
+                                string(" + "),
+                                CompoundExpressionNodeChild::Compound(CompoundExpressionNode::new(
+                                    vec![simple("c"), string(" + "), simple("d")],
+                                    None,
+                                )),
+                            ],
+                            None,
+                        )),
+                        string(" + "),
+                        simple("e"),
+                    ],
+                    None,
+                ),
+            ));
+            root
+        };
+        let CodegenResult { code, .. } = generate(root, CodegenOptions::default());
+        assert!(code.contains("return a + b + c + d + e\n"));
+    }
+
+    #[test]
+    fn missing_codegen_node() {
+        let generate_untransformed = |dev: bool| {
+            let mut root = base_parse("<div/>", None);
+            root.codegen_node = Some(RootCodegenNode::TemplateChild(root.children[0].clone()));
+            let mut options = CodegenOptions::default();
+            options.global_compile_time_constants.__dev__ = dev;
+            generate(root, options).diagnostics
+        };
+
+        let diagnostics = generate_untransformed(true);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, ErrorCodes::XCodegenNodeMissing);
+        assert_eq!(diagnostics[0].loc.as_ref().unwrap().source, "<div/>");
+
+        assert!(generate_untransformed(false).is_empty());
+    }
+
     #[test]
     fn if_node() {
         let root = {
@@ -378,7 +472,9 @@ mod compiler_codegen {
                         index: None,
                         finalized: false,
                     },
-                    codegen_node: Some(ForCodegenNode {
+                    user_key: None,
+                    is_once: false,
+                    codegen_node: Some(ForNodeCodegenNode::ForCodegen(Box::new(ForCodegenNode {
                         tag: Fragment.to_string(),
                         children: ForRenderListExpression::new(
                             CallCallee::Symbol(RenderList.to_string()),
@@ -389,7 +485,7 @@ mod compiler_codegen {
                         disable_tracking: true,
                         is_component: false,
                         loc: SourceLocation::loc_stub(),
-                    }),
+                    }))),
                     loc: SourceLocation::loc_stub(),
                 },
             )));
@@ -425,7 +521,9 @@ mod compiler_codegen {
                         index: None,
                         finalized: false,
                     },
-                    codegen_node: Some(ForCodegenNode {
+                    user_key: None,
+                    is_once: false,
+                    codegen_node: Some(ForNodeCodegenNode::ForCodegen(Box::new(ForCodegenNode {
                         tag: Fragment.to_string(),
                         children: ForRenderListExpression::new(
                             CallCallee::Symbol(RenderList.to_string()),
@@ -436,7 +534,7 @@ mod compiler_codegen {
                         disable_tracking: false,
                         is_component: false,
                         loc: SourceLocation::loc_stub(),
-                    }),
+                    }))),
                     loc: SourceLocation::loc_stub(),
                 },
             )));
@@ -595,6 +693,45 @@ mod compiler_codegen {
         ));
     }
 
+    fn generate_array(elements: Vec<ArrayExpressionElement>) -> String {
+        let mut root = RootNode::new(Vec::new(), None);
+        root.codegen_node = Some(RootCodegenNode::JSChild(JSChildNode::Array(
+            ArrayExpression::new(elements, None),
+        )));
+        generate(root, CodegenOptions::default()).code
+    }
+
+    #[test]
+    fn array_of_vnode_calls_is_multiline() {
+        let vnode_call = |tag: &str| {
+            ArrayExpressionElement::VNodeCall(VNodeCall {
+                tag: VNodeCallTag::String(format!("\"{tag}\"")),
+                props: None,
+                children: None,
+                patch_flag: None,
+                directives: None,
+                is_block: false,
+                disable_tracking: false,
+                is_component: false,
+                loc: SourceLocation::loc_stub(),
+            })
+        };
+        assert_snapshot!(generate_array(vec![vnode_call("a"), vnode_call("b")]));
+    }
+
+    #[test]
+    fn array_of_strings_is_inline() {
+        let string = |content: &str| {
+            ArrayExpressionElement::Simple(SimpleExpressionNode::new(
+                content,
+                Some(true),
+                None,
+                None,
+            ))
+        };
+        assert_snapshot!(generate_array(vec![string("a"), string("b")]));
+    }
+
     #[test]
     fn conditional_expression() {
         let root = {
@@ -705,6 +842,36 @@ mod compiler_codegen {
         );
     }
 
+    #[test]
+    /// CacheExpression w/ needArraySpread: true
+    fn cache_expression_w_need_array_spread_true() {
+        let root = {
+            let mut root = RootNode::new(Vec::new(), None);
+            let mut cache = CacheExpression::new(
+                1,
+                JSChildNode::Simple(SimpleExpressionNode::new("foo", Some(false), None, None)),
+                None,
+                None,
+            );
+            cache.need_array_spread = true;
+            root.codegen_node = Some(RootCodegenNode::JSChild(JSChildNode::Cache(Box::new(
+                cache,
+            ))));
+            root
+        };
+
+        let CodegenResult { code, .. } = generate(
+            root,
+            CodegenOptions {
+                mode: Some(CodegenMode::Module),
+                prefix_identifiers: Some(true),
+                ..Default::default()
+            },
+        );
+
+        assert!(code.contains("[...(_cache[1] || (_cache[1] = foo))]"));
+    }
+
     #[test]
     fn template_literal() {
         let root = {
@@ -753,4 +920,46 @@ export function ssrRender(_ctx, _push, _parent, _attrs) {
             )
         );
     }
+
+    #[test]
+    fn vnode_call_omits_trailing_null_args() {
+        let compile = |template: &str| {
+            base_compile(
+                BaseCompileSource::String(template.to_string()),
+                CompilerOptions::default(),
+            )
+            .code
+        };
+
+        let code = compile("<div/>");
+        assert!(code.contains(r#"_createElementBlock("div"))"#), "{code}");
+
+        let code = compile(r#"<div id="a"/>"#);
+        assert!(
+            code.contains(r#"_createElementBlock("div", { id: "a" }))"#),
+            "{code}"
+        );
+
+        // null is only kept as a placeholder for the following arguments
+        let code = compile("<div><span/></div>");
+        assert!(
+            code.contains(r#"_createElementBlock("div", null, ["#),
+            "{code}"
+        );
+        assert!(code.contains(r#"_createElementVNode("span")"#), "{code}");
+    }
+
+    #[test]
+    fn code_capacity_is_reserved_from_the_template() {
+        let text = "x".repeat(10_000);
+        let template = format!("<div>{text}</div>");
+        let CodegenResult { code, .. } = base_compile(
+            BaseCompileSource::String(template.clone()),
+            CompilerOptions::default(),
+        );
+
+        assert!(code.contains(&format!(r#"_createElementBlock("div", null, "{text}"))"#)));
+        // a buffer grown on demand would stay below three times the template
+        assert!(code.capacity() >= template.len() * 3);
+    }
 }
@@ -1,3 +1,5 @@
+mod test_utils;
+
 #[cfg(test)]
 mod compiler_integration_tests {
     use insta::assert_snapshot;
@@ -41,4 +43,299 @@ mod compiler_integration_tests {
 
         assert_snapshot!(code);
     }
+
+    #[test]
+    fn module_mode_fragment_root() {
+        let options = CompilerOptions {
+            mode: Some(CodegenMode::Module),
+            ..Default::default()
+        };
+
+        let CodegenResult { code, .. } =
+            compile(BaseCompileSource::String("<a/><b/>".to_string()), options);
+
+        assert!(code.starts_with(
+            "import { createElementVNode as _createElementVNode, Fragment as _Fragment,"
+        ));
+        assert!(code.contains("_createElementBlock(_Fragment, null, ["));
+        assert_snapshot!(code);
+    }
+
+    #[test]
+    fn text_and_interpolation_chain() {
+        let CodegenResult { code, .. } = compile(
+            BaseCompileSource::String("a {{b}} c {{d}} e".to_string()),
+            CompilerOptions::default(),
+        );
+        assert!(
+            code.contains(
+                r#"return "a " + _toDisplayString(b) + " c " + _toDisplayString(d) + " e""#
+            )
+        );
+
+        // prefixed interpolations are compounds nested in the text compound
+        let CodegenResult { code, .. } = compile(
+            BaseCompileSource::String("a {{b + c.d}} c {{x?.y}} e".to_string()),
+            CompilerOptions {
+                prefix_identifiers: Some(true),
+                ..Default::default()
+            },
+        );
+        assert!(code.contains(
+            r#"return "a " + _toDisplayString(_ctx.b + _ctx.c.d) + " c " + _toDisplayString(_ctx.x?.y) + " e""#
+        ));
+    }
+}
+
+#[cfg(test)]
+mod compiler_options {
+    use crate::test_utils::TestErrorHandlingOptions;
+    use vue_compiler_core::{
+        BaseCompileSource, CodegenMode, CodegenResult, CompilerOptions, ErrorCodes,
+        base_compile as compile,
+    };
+
+    #[test]
+    fn split_shares_options() {
+        let mut options = CompilerOptions {
+            prefix_identifiers: Some(true),
+            is_custom_element: Some(Box::new(|tag| Some(tag == "comp"))),
+            ..Default::default()
+        };
+        options.global_compile_time_constants.__dev__ = true;
+
+        let (parser_options, transform_options, codegen_options) = options.split();
+
+        assert_eq!(parser_options.prefix_identifiers, Some(true));
+        assert_eq!(transform_options.prefix_identifiers, Some(true));
+        assert_eq!(codegen_options.prefix_identifiers, Some(true));
+
+        assert!(parser_options.global_compile_time_constants.__dev__);
+        assert!(transform_options.global_compile_time_constants.__dev__);
+        assert!(codegen_options.global_compile_time_constants.__dev__);
+
+        let comp = "comp".to_string();
+        let div = "div".to_string();
+        let is_custom_element = parser_options.is_custom_element.unwrap();
+        assert_eq!(is_custom_element(&comp), Some(true));
+        assert_eq!(is_custom_element(&div), Some(false));
+        let is_custom_element = transform_options.is_custom_element.unwrap();
+        assert_eq!(is_custom_element(&comp), Some(true));
+    }
+
+    #[test]
+    fn use_create_vnode_only() {
+        let options = CompilerOptions {
+            mode: Some(CodegenMode::Module),
+            use_create_vnode_only: Some(true),
+            ..Default::default()
+        };
+        let CodegenResult { code, ast, .. } = compile(
+            BaseCompileSource::String("<div><span/></div>".to_string()),
+            options,
+        );
+
+        assert!(!code.contains("createElement"));
+        assert!(code.starts_with(
+            r#"import { createVNode as _createVNode, openBlock as _openBlock, createBlock as _createBlock } from "vue""#
+        ));
+        assert!(code.contains(r#"_createBlock("div", null, ["#));
+        assert!(code.contains(r#"_createVNode("span")"#));
+        assert_eq!(ast.helpers.len(), 3);
+    }
+
+    #[test]
+    fn separate_preamble() {
+        let compile_separated = |mode: CodegenMode| {
+            let CodegenResult { code, preamble, .. } = compile(
+                BaseCompileSource::String("<div>{{x}}</div>".to_string()),
+                CompilerOptions {
+                    mode: Some(mode),
+                    separate_preamble: Some(true),
+                    ..Default::default()
+                },
+            );
+            (code, preamble)
+        };
+
+        let (code, preamble) = compile_separated(CodegenMode::Function);
+        assert_eq!(preamble, "const _Vue = Vue\n");
+        assert!(code.starts_with("return function render(_ctx, _cache) {"));
+        assert!(code.contains("const { toDisplayString: _toDisplayString"));
+
+        let (code, preamble) = compile_separated(CodegenMode::Module);
+        assert!(preamble.starts_with("import { toDisplayString as _toDisplayString"));
+        assert!(code.starts_with("export function render(_ctx, _cache) {"));
+
+        // without the option the preamble stays at the start of the code
+        let CodegenResult { code, preamble, .. } = compile(
+            BaseCompileSource::String("<div>{{x}}</div>".to_string()),
+            CompilerOptions::default(),
+        );
+        assert!(preamble.is_empty());
+        assert!(code.starts_with("const _Vue = Vue\n\nreturn function render"));
+    }
+
+    #[test]
+    fn unsupported_options_in_browser_build() {
+        let compile_errors = |options: CompilerOptions, browser: bool| {
+            let error_handling_options = TestErrorHandlingOptions::new();
+            let mut options = CompilerOptions {
+                error_handling_options: Box::new(error_handling_options.clone()),
+                ..options
+            };
+            options.global_compile_time_constants.__browser__ = browser;
+            compile(
+                BaseCompileSource::String("<div>{{ x }}</div>".to_string()),
+                options,
+            );
+            error_handling_options
+                .errors()
+                .into_iter()
+                .map(|e| e.code)
+                .collect::<Vec<_>>()
+        };
+        let prefixed = || CompilerOptions {
+            prefix_identifiers: Some(true),
+            ..Default::default()
+        };
+        let module = || CompilerOptions {
+            mode: Some(CodegenMode::Module),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            compile_errors(prefixed(), true),
+            [ErrorCodes::XPrefixIdNotSupported]
+        );
+        assert_eq!(
+            compile_errors(module(), true),
+            [ErrorCodes::XModuleModeNotSupported]
+        );
+        assert!(compile_errors(CompilerOptions::default(), true).is_empty());
+        assert!(compile_errors(prefixed(), false).is_empty());
+        assert!(compile_errors(module(), false).is_empty());
+    }
+
+    #[test]
+    fn compat_v_if_v_for_precedence() {
+        let error_handling_options = TestErrorHandlingOptions::new();
+        let mut options = CompilerOptions {
+            compat: Some(true),
+            ..Default::default()
+        };
+        options.global_compile_time_constants.__dev__ = true;
+        options.error_handling_options = Box::new(error_handling_options.clone());
+        compile(
+            BaseCompileSource::String(
+                r#"<div><li v-for="i in list" :key="i" v-if="ok"/></div>"#.to_string(),
+            ),
+            options,
+        );
+
+        let warnings = error_handling_options.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, ErrorCodes::XVIfVForPrecedence);
+    }
+
+    #[test]
+    fn keep_comments_in_prod() {
+        let compile_comment = |comments| {
+            let options = CompilerOptions {
+                comments,
+                ..Default::default()
+            };
+            let CodegenResult { code, .. } = compile(
+                BaseCompileSource::String("<div><!-- anchor --></div>".to_string()),
+                options,
+            );
+            code
+        };
+
+        assert!(!compile_comment(None).contains("createCommentVNode"));
+        assert!(compile_comment(Some(true)).contains(r#"_createCommentVNode(" anchor ")"#));
+    }
+}
+
+#[cfg(test)]
+mod codegen_whitespace {
+    use insta::assert_snapshot;
+    use vue_compiler_core::{
+        BaseCompileSource, CodegenMode, CodegenResult, CompilerOptions, base_compile as compile,
+    };
+
+    fn compile_with(template: &str, mode: CodegenMode, prefix_identifiers: bool) -> String {
+        let mut options = CompilerOptions {
+            mode: Some(mode),
+            prefix_identifiers: Some(prefix_identifiers),
+            ..Default::default()
+        };
+        options.global_compile_time_constants.__dev__ = true;
+
+        let CodegenResult { code, .. } =
+            compile(BaseCompileSource::String(template.to_string()), options);
+        code
+    }
+
+    #[test]
+    fn function_mode_interpolation() {
+        assert_snapshot!(compile_with(
+            "<div>{{ msg }}</div>",
+            CodegenMode::Function,
+            false
+        ));
+    }
+
+    #[test]
+    fn function_mode_with_prefix_identifiers() {
+        assert_snapshot!(compile_with(
+            "<div>{{ msg }}</div>",
+            CodegenMode::Function,
+            true
+        ));
+    }
+
+    #[test]
+    fn no_leading_newline_without_preamble() {
+        let code = compile_with("hello", CodegenMode::Function, false);
+        assert!(code.starts_with("return function render(_ctx, _cache) {\n"));
+
+        let code = compile_with("hello", CodegenMode::Module, true);
+        assert!(code.starts_with("export function render(_ctx, _cache) {\n"));
+    }
+
+    #[test]
+    fn no_blank_line_runs_or_trailing_whitespace() {
+        for template in [
+            "<div>{{ msg }}</div>",
+            "<Comp v-foo/>",
+            "<div v-if=\"ok\"/>",
+        ] {
+            for (mode, prefix_identifiers) in [
+                (CodegenMode::Function, false),
+                (CodegenMode::Function, true),
+                (CodegenMode::Module, true),
+            ] {
+                let code = compile_with(template, mode, prefix_identifiers);
+                assert!(!code.contains("\n\n\n"), "{code}");
+                assert!(code.lines().all(|line| line.trim_end() == line), "{code}");
+            }
+        }
+    }
+
+    #[test]
+    fn multi_line_expression() {
+        // dynamic expressions are emitted verbatim, static ones are escaped
+        let code = compile_with(
+            "<div :onClick=\"() => {\n  foo()\n}\" title=\"a\nb\"/>",
+            CodegenMode::Function,
+            true,
+        );
+        assert!(
+            code.contains("onClick: () => {\n  _ctx.foo()\n},"),
+            "{code}"
+        );
+        assert!(code.contains(r#"title: "a\nb""#), "{code}");
+        assert!(code.ends_with("}))\n}"), "{code}");
+    }
 }
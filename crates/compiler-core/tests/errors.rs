@@ -0,0 +1,243 @@
+mod test_utils;
+
+use test_utils::TestErrorHandlingOptions;
+
+#[cfg(test)]
+mod compiler_error {
+    use super::TestErrorHandlingOptions;
+    use std::collections::HashSet;
+    use vue_compiler_core::{
+        CompilerError, ErrorCodes, ExpressionNode, ParserOptions, Position, SourceLocation,
+        TemplateChildNode, base_parse,
+    };
+    use vue_compiler_shared::generate_code_frame;
+
+    #[test]
+    fn message() {
+        assert_eq!(ErrorCodes::XInvalidEndTag.message(), "Invalid end tag.");
+        assert_eq!(
+            ErrorCodes::XMissingInterpolationEnd.message(),
+            "Interpolation end sign was not found."
+        );
+    }
+
+    #[test]
+    fn display_with_location() {
+        let error_handling_options = TestErrorHandlingOptions::new();
+        base_parse(
+            "some text</div>",
+            Some(ParserOptions {
+                error_handling_options: Box::new(error_handling_options.clone()),
+                ..Default::default()
+            }),
+        );
+        let errors = error_handling_options.errors();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code, ErrorCodes::XInvalidEndTag);
+        assert_eq!(errors[0].to_string(), "Invalid end tag. (1:10)");
+    }
+
+    #[test]
+    fn display_without_location() {
+        let error = CompilerError::new(ErrorCodes::XMissingEndTag, None);
+        assert_eq!(error.to_string(), "Element is missing end tag.");
+
+        let error = CompilerError::new(
+            ErrorCodes::DuplicateAttribute,
+            Some(SourceLocation {
+                start: Position {
+                    offset: 12,
+                    line: 2,
+                    column: 3,
+                },
+                end: Position {
+                    offset: 12,
+                    line: 2,
+                    column: 3,
+                },
+                source: String::new(),
+            }),
+        );
+        assert_eq!(error.to_string(), "Duplicate attribute. (2:3)");
+    }
+
+    #[test]
+    fn code_frame() {
+        let source = "<div>\n  <p>\n    text</span>\n  </p>\n</div>";
+        let errors = parse_errors(source);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code, ErrorCodes::XInvalidEndTag);
+        assert_eq!(
+            errors[0].code_frame(source),
+            [
+                "1  |  <div>",
+                "2  |    <p>",
+                "3  |      text</span>",
+                "   |          ^",
+                "4  |    </p>",
+                "5  |  </div>",
+            ]
+            .join("\n")
+        );
+
+        let error = CompilerError::new(ErrorCodes::XInvalidEndTag, None);
+        assert_eq!(error.code_frame(source), "");
+    }
+
+    #[test]
+    fn multi_line_code_frame() {
+        let source = "a\nbc\nd";
+        let error = CompilerError::new(
+            ErrorCodes::XMissingEndTag,
+            Some(SourceLocation {
+                start: Position {
+                    offset: 2,
+                    line: 2,
+                    column: 1,
+                },
+                end: Position {
+                    offset: 7,
+                    line: 3,
+                    column: 2,
+                },
+                source: "bc\nd".to_string(),
+            }),
+        );
+        assert_eq!(
+            error.code_frame(source),
+            ["1  |  a", "2  |  bc", "   |  ^^", "3  |  d", "   |  ^"].join("\n")
+        );
+    }
+
+    #[test]
+    fn crlf_code_frame() {
+        // ranges starting at the `\n` of a `\r\n` pair are underlined at the
+        // end of their line
+        assert_eq!(
+            generate_code_frame("a\r\nb", 2, 3),
+            ["1  |  a", "   |   ^", "2  |  b"].join("\n")
+        );
+        assert_eq!(
+            generate_code_frame("中文\r\nx", 4, 6),
+            ["1  |  中文", "   |   ^", "2  |  x"].join("\n")
+        );
+        assert_eq!(
+            generate_code_frame("a\r\nb\r\nc", 1, 7),
+            [
+                "1  |  a", "   |   ^", "2  |  b", "   |  ^", "3  |  c", "   |  ^"
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn dedupe_in_hash_set() {
+        let error_handling_options = TestErrorHandlingOptions::new();
+        for _ in 0..2 {
+            base_parse(
+                "some text</div>",
+                Some(ParserOptions {
+                    error_handling_options: Box::new(error_handling_options.clone()),
+                    ..Default::default()
+                }),
+            );
+        }
+        let errors = error_handling_options.errors();
+        assert_eq!(errors.len(), 2);
+        assert!(errors.iter().all(|e| e.code == ErrorCodes::XInvalidEndTag));
+
+        let mut set = HashSet::new();
+        set.extend(errors);
+        assert_eq!(set.len(), 1);
+        // a different location is a different error
+        set.insert(CompilerError::new(ErrorCodes::XInvalidEndTag, None));
+        assert_eq!(set.len(), 2);
+    }
+
+    fn parse_errors(template: &str) -> Vec<CompilerError> {
+        let error_handling_options = TestErrorHandlingOptions::new();
+        base_parse(
+            template,
+            Some(ParserOptions {
+                prefix_identifiers: Some(true),
+                error_handling_options: Box::new(error_handling_options.clone()),
+                ..Default::default()
+            }),
+        );
+        error_handling_options.errors()
+    }
+
+    #[test]
+    fn invalid_expression() {
+        for template in [
+            "{{ a + }}",
+            "{{ (a }}",
+            "{{ a] }}",
+            "{{ 'a }}",
+            "<div :id=\"* a\"/>",
+            "<div @click=\"foo(\"/>",
+            "{{ a++ / }}",
+        ] {
+            let errors = parse_errors(template);
+
+            assert_eq!(errors.len(), 1, "{template}");
+            assert_eq!(errors[0].code, ErrorCodes::XInvalidExpression);
+            assert!(
+                errors[0]
+                    .message
+                    .starts_with("Error parsing JavaScript expression: ")
+            );
+        }
+
+        let errors = parse_errors("{{ a + }}");
+        let Some(loc) = &errors[0].loc else {
+            unreachable!();
+        };
+        assert_eq!(loc.source, "a +");
+    }
+
+    #[test]
+    fn valid_expression() {
+        for template in [
+            "{{ a++ }}",
+            "{{ 1. }}",
+            "{{ a.b(c, [d]) ? { e } : `${f}` }}",
+            "{{ a / b }}",
+            "{{ a.replace(/[)]/g, '(') }}",
+            "<div @click=\"a = 1; b()\"/>",
+            "<div v-for=\"({ a }, i) in list\"/>",
+            // postfix operators end an operand, `/` is a division
+            "{{ a++ / 2 }}",
+            "{{ a-- / b / c }}",
+            "{{ foo! }}",
+            "{{ foo!.bar / 2 }}",
+            "{{ a !== b }}",
+        ] {
+            assert_eq!(parse_errors(template), vec![], "{template}");
+        }
+    }
+
+    #[test]
+    fn expression_ast_valid() {
+        let ast = base_parse(
+            "{{ a + }}{{ a + b }}{{ a }}",
+            Some(ParserOptions {
+                prefix_identifiers: Some(true),
+                ..Default::default()
+            }),
+        );
+        let ast_valid: Vec<_> = ast
+            .children
+            .iter()
+            .map(|child| match child {
+                TemplateChildNode::Interpolation(node) => match &node.content {
+                    ExpressionNode::Simple(exp) => exp.ast_valid,
+                    _ => unreachable!(),
+                },
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(ast_valid, [Some(false), Some(true), None]);
+    }
+}
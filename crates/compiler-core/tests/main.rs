@@ -1 +1,2 @@
+mod test_utils;
 mod transforms;
@@ -1,35 +1,13 @@
-use std::{cell::RefCell, sync::Arc};
-use vue_compiler_core::{CompilerError, ErrorHandlingOptions};
+mod test_utils;
 
-#[derive(Debug, Clone)]
-struct TestErrorHandlingOptions {
-    errors: Arc<RefCell<Vec<CompilerError>>>,
-}
-
-impl TestErrorHandlingOptions {
-    fn new() -> Self {
-        Self {
-            errors: Default::default(),
-        }
-    }
-
-    fn try_unwrap(self) -> Vec<CompilerError> {
-        Arc::try_unwrap(self.errors).unwrap().into_inner()
-    }
-}
-
-impl ErrorHandlingOptions for TestErrorHandlingOptions {
-    fn on_error(&mut self, error: CompilerError) {
-        self.errors.borrow_mut().push(error);
-    }
-}
+use test_utils::TestErrorHandlingOptions;
 
 #[cfg(test)]
 mod text {
-    use std::{cell::RefCell, sync::Arc};
+    use super::TestErrorHandlingOptions;
     use vue_compiler_core::{
-        CompilerError, ErrorCodes, ErrorHandlingOptions, ParserOptions, Position, SourceLocation,
-        TemplateChildNode, base_parse,
+        CompilerError, ErrorCodes, ParserOptions, Position, SourceLocation, TemplateChildNode,
+        base_parse,
     };
 
     #[test]
@@ -60,32 +38,17 @@ mod text {
 
     #[test]
     fn simple_text_with_invalid_end_tag() {
-        #[derive(Debug)]
-        struct TestErrorHandlingOptions {
-            errors: Arc<RefCell<Vec<CompilerError>>>,
-        }
-
-        impl ErrorHandlingOptions for TestErrorHandlingOptions {
-            fn on_error(&mut self, error: CompilerError) {
-                self.errors.borrow_mut().push(error);
-            }
-        }
-
-        let errors: Arc<RefCell<Vec<CompilerError>>> = Default::default();
-        let error_handling_options = TestErrorHandlingOptions {
-            errors: errors.clone(),
-        };
-
+        let error_handling_options = TestErrorHandlingOptions::new();
         let ast = base_parse(
             "some text</div>",
             Some(ParserOptions {
-                error_handling_options: Box::new(error_handling_options),
+                error_handling_options: Box::new(error_handling_options.clone()),
                 ..Default::default()
             }),
         );
         let text = ast.children.first();
 
-        let errors = Arc::try_unwrap(errors).unwrap().into_inner();
+        let errors = error_handling_options.errors();
         assert_eq!(
             errors,
             vec![CompilerError::new(
@@ -389,8 +352,10 @@ mod interpolation {
 
 #[cfg(test)]
 mod comment {
+    use super::TestErrorHandlingOptions;
     use vue_compiler_core::{
-        ParserOptions, Position, SourceLocation, TemplateChildNode, base_parse,
+        CompilerError, ErrorCodes, ParserOptions, Position, SourceLocation, TemplateChildNode,
+        base_parse,
     };
 
     #[test]
@@ -425,6 +390,169 @@ mod comment {
         );
     }
 
+    /// nested comments
+    #[test]
+    fn nested_comment() {
+        let error_handling_options = TestErrorHandlingOptions::new();
+        let ast = base_parse(
+            "<!-- a <!-- b -->",
+            Some(ParserOptions {
+                comments: Some(true),
+                error_handling_options: Box::new(error_handling_options.clone()),
+                ..Default::default()
+            }),
+        );
+
+        assert_eq!(
+            ast.children.first(),
+            Some(&TemplateChildNode::new_comment(
+                " a <!-- b ",
+                SourceLocation {
+                    start: Position {
+                        offset: 0,
+                        line: 1,
+                        column: 1,
+                    },
+                    end: Position {
+                        offset: 17,
+                        line: 1,
+                        column: 18,
+                    },
+                    source: "<!-- a <!-- b -->".to_string(),
+                },
+            ))
+        );
+        assert_eq!(
+            error_handling_options.errors(),
+            vec![CompilerError::new(
+                ErrorCodes::NestedComment,
+                Some(SourceLocation {
+                    start: Position {
+                        offset: 7,
+                        line: 1,
+                        column: 8,
+                    },
+                    end: Position {
+                        offset: 7,
+                        line: 1,
+                        column: 8,
+                    },
+                    source: String::new(),
+                }),
+            )]
+        );
+    }
+
+    /// incorrectly closed comments
+    #[test]
+    fn incorrectly_closed_comment() {
+        let error_handling_options = TestErrorHandlingOptions::new();
+        let ast = base_parse(
+            "<!-- a --!><div/>",
+            Some(ParserOptions {
+                comments: Some(true),
+                error_handling_options: Box::new(error_handling_options.clone()),
+                ..Default::default()
+            }),
+        );
+
+        assert_eq!(ast.children.len(), 2);
+        assert_eq!(
+            ast.children.first(),
+            Some(&TemplateChildNode::new_comment(
+                " a ",
+                SourceLocation {
+                    start: Position {
+                        offset: 0,
+                        line: 1,
+                        column: 1,
+                    },
+                    end: Position {
+                        offset: 11,
+                        line: 1,
+                        column: 12,
+                    },
+                    source: "<!-- a --!>".to_string(),
+                },
+            ))
+        );
+        assert_eq!(
+            error_handling_options.errors(),
+            vec![CompilerError::new(
+                ErrorCodes::IncorrectlyClosedComment,
+                Some(SourceLocation {
+                    start: Position {
+                        offset: 7,
+                        line: 1,
+                        column: 8,
+                    },
+                    end: Position {
+                        offset: 7,
+                        line: 1,
+                        column: 8,
+                    },
+                    source: String::new(),
+                }),
+            )]
+        );
+    }
+
+    /// abruptly closed empty comments
+    #[test]
+    fn abrupt_closing_of_empty_comment() {
+        for source in ["<!-->", "<!--->"] {
+            let error_handling_options = TestErrorHandlingOptions::new();
+            let ast = base_parse(
+                source,
+                Some(ParserOptions {
+                    comments: Some(true),
+                    error_handling_options: Box::new(error_handling_options.clone()),
+                    ..Default::default()
+                }),
+            );
+            let end = Position {
+                offset: source.len(),
+                line: 1,
+                column: source.len() + 1,
+            };
+
+            assert_eq!(
+                ast.children.first(),
+                Some(&TemplateChildNode::new_comment(
+                    "",
+                    SourceLocation {
+                        start: Position {
+                            offset: 0,
+                            line: 1,
+                            column: 1,
+                        },
+                        end,
+                        source: source.to_string(),
+                    },
+                ))
+            );
+            assert_eq!(
+                error_handling_options.errors(),
+                vec![CompilerError::new(
+                    ErrorCodes::AbruptClosingOfEmptyComment,
+                    Some(SourceLocation {
+                        start: Position {
+                            offset: 0,
+                            line: 1,
+                            column: 1,
+                        },
+                        end: Position {
+                            offset: 0,
+                            line: 1,
+                            column: 1,
+                        },
+                        source: String::new(),
+                    }),
+                )]
+            );
+        }
+    }
+
     #[test]
     fn simple_comment() {
         let ast = base_parse(
@@ -512,6 +640,7 @@ mod element {
                         },
                         source: "<div>hello</div>".to_string(),
                     },
+                    inner_loc: None,
                 }
             )))
         );
@@ -546,6 +675,7 @@ mod element {
                         },
                         source: "<div></div>".to_string(),
                     },
+                    inner_loc: None,
                 }
             )))
         );
@@ -580,6 +710,7 @@ mod element {
                         },
                         source: "<div/>".to_string(),
                     },
+                    inner_loc: None,
                 }
             )))
         );
@@ -620,6 +751,7 @@ mod element {
                         },
                         source: "<img>".to_string(),
                     },
+                    inner_loc: None,
                 }
             )))
         );
@@ -660,6 +792,7 @@ mod element {
                         },
                         source: "<img/>".to_string(),
                     },
+                    inner_loc: None,
                 }
             )))
         );
@@ -805,9 +938,13 @@ mod element {
         let ast = base_parse(
             "<div></div><comp></comp>",
             Some(ParserOptions {
-                is_built_in_component: Some(Box::new(
-                    |tag| if tag == "comp" { Some(()) } else { None },
-                )),
+                is_built_in_component: Some(Box::new(|tag| {
+                    if tag == "comp" {
+                        Some(tag.clone())
+                    } else {
+                        None
+                    }
+                })),
                 ..Default::default()
             }),
         );
@@ -900,6 +1037,7 @@ mod element {
                         },
                         source: "<div id></div>".to_string(),
                     },
+                    inner_loc: None,
                 }
             )))
         );
@@ -978,6 +1116,7 @@ mod element {
                         },
                         source: r#"<div id=""></div>"#.to_string(),
                     },
+                    inner_loc: None,
                 }
             )))
         );
@@ -1056,6 +1195,7 @@ mod element {
                         },
                         source: "<div id=''></div>".to_string(),
                     },
+                    inner_loc: None,
                 }
             )))
         );
@@ -1134,6 +1274,7 @@ mod element {
                         },
                         source: r#"<div id=">'"></div>"#.to_string(),
                     },
+                    inner_loc: None,
                 }
             )))
         );
@@ -1212,6 +1353,7 @@ mod element {
                         },
                         source: "<div id='>\"'></div>".to_string(),
                     },
+                    inner_loc: None,
                 }
             )))
         );
@@ -1290,6 +1432,7 @@ mod element {
                         },
                         source: "<div id=a/></div>".to_string(),
                     },
+                    inner_loc: None,
                 }
             )))
         );
@@ -1448,6 +1591,19 @@ mod element {
                         },
                         source: r#"<script setup lang="ts" generic="T extends Record<string,string>"></script>"#.to_string(),
                     },
+                    inner_loc: Some(SourceLocation {
+                        start: Position {
+                            offset: 66,
+                            line: 1,
+                            column: 67,
+                        },
+                        end: Position {
+                            offset: 66,
+                            line: 1,
+                            column: 67,
+                        },
+                        source: String::new(),
+                    }),
                 }
             )))
         );
@@ -1649,11 +1805,70 @@ mod element {
                         },
                         source: r#"<div id=a class="c" inert style=''></div>"#.to_string(),
                     },
+                    inner_loc: None,
                 }
             )))
         );
     }
 
+    #[test]
+    fn many_attributes() {
+        let attrs: Vec<String> = (0..50)
+            .map(|i| match i % 3 {
+                0 => format!(r#"a{i}="{i}""#),
+                1 => format!(r#":b{i}="x{i}""#),
+                _ => format!("c{i}"),
+            })
+            .collect();
+        let ast = base_parse(&format!("<div {}/>", attrs.join(" ")), None);
+        let Some(TemplateChildNode::Element(el)) = ast.children.first() else {
+            unreachable!();
+        };
+
+        assert_eq!(el.props().len(), attrs.len());
+        for (i, (prop, source)) in el.props().iter().zip(&attrs).enumerate() {
+            assert_eq!(&prop.loc().source, source);
+            match prop {
+                BaseElementProps::Attribute(attr) if i % 3 == 0 => {
+                    assert_eq!(attr.name, format!("a{i}"));
+                    assert_eq!(attr.value.as_ref().unwrap().content, i.to_string());
+                }
+                BaseElementProps::Directive(dir) if i % 3 == 1 => {
+                    assert_eq!(dir.name, "bind");
+                    let Some(ExpressionNode::Simple(exp)) = &dir.exp else {
+                        unreachable!();
+                    };
+                    assert_eq!(exp.content, format!("x{i}"));
+                }
+                BaseElementProps::Attribute(attr) => {
+                    assert_eq!(attr.name, format!("c{i}"));
+                    assert_eq!(attr.value, None);
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn class_is_condensed_but_style_is_kept() {
+        let ast = base_parse(r#"<div class=" a  b " style=" color: red "/>"#, None);
+        let Some(TemplateChildNode::Element(el)) = ast.children.first() else {
+            unreachable!();
+        };
+        let values: Vec<_> = el
+            .props()
+            .iter()
+            .map(|prop| {
+                let BaseElementProps::Attribute(attr) = prop else {
+                    unreachable!();
+                };
+                attr.value.as_ref().unwrap().content.as_str()
+            })
+            .collect();
+        // style is left untouched like the runtime parsing of inline styles
+        assert_eq!(values, ["a b", " color: red "]);
+    }
+
     #[test]
     fn directive_with_no_value() {
         let ast = base_parse("<div v-if/>", None);
@@ -2223,7 +2438,7 @@ mod element {
                 ..Default::default()
             }),
         );
-        let errors = error_handling_options.try_unwrap();
+        let errors = error_handling_options.errors();
         assert_eq!(
             errors,
             vec![CompilerError::new(
@@ -2288,6 +2503,36 @@ mod element {
         )
     }
 
+    /// directive with no name but an argument or modifiers
+    #[test]
+    fn directive_with_no_name_and_arg_or_modifiers() {
+        for source in ["<div v-:x/>", "<div v-.mod/>"] {
+            let error_handling_options = TestErrorHandlingOptions::new();
+            let ast = base_parse(
+                source,
+                Some(ParserOptions {
+                    error_handling_options: Box::new(error_handling_options.clone()),
+                    ..Default::default()
+                }),
+            );
+            let errors = error_handling_options.errors();
+            assert_eq!(errors.len(), 1, "{source}");
+            assert_eq!(
+                errors[0].code,
+                ErrorCodes::XMissingDirectiveName,
+                "{source}"
+            );
+            let Some(TemplateChildNode::Element(el)) = ast.children.first() else {
+                panic!("expected an element for {source}");
+            };
+            assert_eq!(el.props().len(), 1, "{source}");
+            let Some(BaseElementProps::Attribute(attr)) = el.props().first() else {
+                panic!("expected an attribute for {source}");
+            };
+            assert_eq!(attr.name, "v-", "{source}");
+        }
+    }
+
     /// v-bind shorthand
     #[test]
     fn v_bind_shorthand() {
@@ -2688,8 +2933,10 @@ mod element {
 
 #[cfg(test)]
 mod edge_cases {
+    use super::TestErrorHandlingOptions;
     use vue_compiler_core::{
-        GlobalCompileTimeConstants, ParserOptions, TemplateChildNode, base_parse,
+        BaseElementProps, ElementNode, ErrorCodes, GlobalCompileTimeConstants, Namespaces,
+        ParseMode, ParserOptions, RootNode, TemplateChildNode, base_parse,
     };
 
     #[test]
@@ -2704,6 +2951,26 @@ mod edge_cases {
         }
     }
 
+    #[test]
+    fn non_ascii_text() {
+        let ast = base_parse("<div>中文{{ a }}</div>", None);
+
+        let Some(TemplateChildNode::Element(element)) = ast.children.first() else {
+            unreachable!();
+        };
+        assert_eq!(element.loc().source, "<div>中文{{ a }}</div>");
+        assert!(matches!(
+            &element.children()[0],
+            TemplateChildNode::Text(text)
+            if text.content == "中文" && text.loc.start.offset == 5 && text.loc.end.offset == 11
+        ));
+        assert!(matches!(
+            &element.children()[1],
+            TemplateChildNode::Interpolation(interpolation)
+            if interpolation.loc.source == "{{ a }}"
+        ));
+    }
+
     #[test]
     fn self_closing_multiple_tag() {
         let ast = base_parse(
@@ -2747,16 +3014,384 @@ mod edge_cases {
         };
         assert!(matches!(el.children()[1], TemplateChildNode::Comment(_)));
     }
+
+    fn parse_with_error_codes(template: &str) -> (RootNode, Vec<ErrorCodes>) {
+        let error_handling_options = TestErrorHandlingOptions::new();
+        let ast = base_parse(
+            template,
+            Some(ParserOptions {
+                error_handling_options: Box::new(error_handling_options.clone()),
+                ..Default::default()
+            }),
+        );
+        let codes = error_handling_options
+            .errors()
+            .into_iter()
+            .map(|error| error.code)
+            .collect();
+        (ast, codes)
+    }
+
+    fn prop_names(el: &ElementNode) -> Vec<&str> {
+        el.props()
+            .iter()
+            .map(|prop| match prop {
+                BaseElementProps::Attribute(attr) => attr.name.as_str(),
+                BaseElementProps::Directive(dir) => dir.raw_name.as_deref().unwrap_or(&dir.name),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn end_tag_inside_open_tag() {
+        // `</` before an attribute name ends the unfinished open tag
+        let (ast, codes) = parse_with_error_codes("<div foo </div>");
+        let TemplateChildNode::Element(el) = &ast.children[0] else {
+            unreachable!();
+        };
+        assert_eq!(el.tag(), "div");
+        assert_eq!(prop_names(el), vec!["foo", "<", "div"]);
+        assert_eq!(
+            codes,
+            vec![
+                ErrorCodes::UnexpectedSolidusInTag,
+                ErrorCodes::XMissingEndTag
+            ]
+        );
+
+        let (ast, codes) = parse_with_error_codes("<div </div>");
+        let TemplateChildNode::Element(el) = &ast.children[0] else {
+            unreachable!();
+        };
+        assert_eq!(el.loc().source, "<div </div>");
+        assert!(el.props().is_empty());
+        assert!(codes.is_empty());
+    }
+
+    #[test]
+    fn lt_inside_attribute_name() {
+        // `<` is part of the attribute name, the tag goes on
+        let (ast, codes) = parse_with_error_codes("<a x</b><c>");
+        assert_eq!(ast.children.len(), 1);
+        let TemplateChildNode::Element(el) = &ast.children[0] else {
+            unreachable!();
+        };
+        assert_eq!(prop_names(el), vec!["x<", "b"]);
+        assert!(matches!(&el.children()[..], [TemplateChildNode::Element(c)] if c.tag() == "c"));
+        assert_eq!(
+            codes,
+            vec![
+                ErrorCodes::UnexpectedCharacterInAttributeName,
+                ErrorCodes::UnexpectedSolidusInTag,
+                ErrorCodes::XMissingEndTag,
+                ErrorCodes::XMissingEndTag,
+            ]
+        );
+
+        let (ast, codes) = parse_with_error_codes("<div foo</div>");
+        let TemplateChildNode::Element(el) = &ast.children[0] else {
+            unreachable!();
+        };
+        assert_eq!(prop_names(el), vec!["foo<", "div"]);
+        assert_eq!(
+            codes,
+            vec![
+                ErrorCodes::UnexpectedCharacterInAttributeName,
+                ErrorCodes::UnexpectedSolidusInTag,
+                ErrorCodes::XMissingEndTag,
+            ]
+        );
+    }
+
+    #[test]
+    fn every_unexpected_character_in_attribute_name() {
+        let error_handling_options = TestErrorHandlingOptions::new();
+        base_parse(
+            r#"<div a"b'c=1></div>"#,
+            Some(ParserOptions {
+                error_handling_options: Box::new(error_handling_options.clone()),
+                ..Default::default()
+            }),
+        );
+        let errors = error_handling_options.errors();
+        let offsets: Vec<_> = errors
+            .iter()
+            .map(|error| {
+                assert_eq!(error.code, ErrorCodes::UnexpectedCharacterInAttributeName);
+                error.loc.as_ref().map(|loc| loc.start.offset)
+            })
+            .collect();
+        assert_eq!(offsets, vec![Some(6), Some(8)]);
+    }
+
+    #[test]
+    fn sfc_root_template_with_preprocessor_lang() {
+        for template in [
+            r#"<template lang="pug">a < b</template>"#,
+            "<template lang=pug>a < b</template>",
+            r#"<template lang="pug"><div>a</template>"#,
+        ] {
+            let error_handling_options = TestErrorHandlingOptions::new();
+            let ast = base_parse(
+                template,
+                Some(ParserOptions {
+                    parse_mode: ParseMode::SFC,
+                    error_handling_options: Box::new(error_handling_options.clone()),
+                    ..Default::default()
+                }),
+            );
+
+            assert!(error_handling_options.errors().is_empty());
+            let Some(TemplateChildNode::Element(element)) = ast.children.first() else {
+                unreachable!();
+            };
+            assert_eq!(element.tag(), "template");
+            let content = &template[template.find('>').unwrap() + 1..template.rfind('<').unwrap()];
+            assert!(matches!(
+                element.children().as_slice(),
+                [TemplateChildNode::Text(text)] if text.content == content
+            ));
+        }
+    }
+
+    #[test]
+    fn sfc_root_inner_loc() {
+        let ast = base_parse(
+            "<script>const a = 1</script>\n<template><div>a</div></template>",
+            Some(ParserOptions {
+                parse_mode: ParseMode::SFC,
+                ..Default::default()
+            }),
+        );
+
+        let [
+            TemplateChildNode::Element(script),
+            TemplateChildNode::Element(template),
+        ] = ast.children.as_slice()
+        else {
+            unreachable!();
+        };
+        let inner_loc = script.inner_loc().unwrap();
+        assert_eq!(inner_loc.source, "const a = 1");
+        assert_eq!(inner_loc.start.offset, 8);
+        assert_eq!(inner_loc.end.offset, 19);
+        assert_eq!(template.inner_loc().unwrap().source, "<div>a</div>");
+        let [TemplateChildNode::Element(div)] = template.children().as_slice() else {
+            unreachable!();
+        };
+        assert_eq!(div.inner_loc(), None);
+    }
+
+    #[test]
+    fn sfc_root_template_with_html_lang() {
+        let ast = base_parse(
+            r#"<template lang="html"><div>a</div></template>"#,
+            Some(ParserOptions {
+                parse_mode: ParseMode::SFC,
+                ..Default::default()
+            }),
+        );
+
+        let Some(TemplateChildNode::Element(element)) = ast.children.first() else {
+            unreachable!();
+        };
+        assert!(matches!(
+            element.children().as_slice(),
+            [TemplateChildNode::Element(div)] if div.tag() == "div"
+        ));
+    }
+
+    #[test]
+    fn eof_at_peek_site() {
+        for (template, expected) in [
+            (
+                "<div /",
+                vec![ErrorCodes::UnexpectedSolidusInTag, ErrorCodes::EOFInTag],
+            ),
+            ("<div v", vec![ErrorCodes::EOFInTag]),
+            ("<div <", vec![ErrorCodes::EOFInTag]),
+        ] {
+            let error_handling_options = TestErrorHandlingOptions::new();
+            let ast = base_parse(
+                template,
+                Some(ParserOptions {
+                    error_handling_options: Box::new(error_handling_options.clone()),
+                    ..Default::default()
+                }),
+            );
+
+            assert!(ast.children.is_empty());
+            let errors = error_handling_options.errors();
+            let codes: Vec<_> = errors.iter().map(|error| &error.code).collect();
+            assert_eq!(codes, expected.iter().collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn children_inherit_root_namespace() {
+        let ast = base_parse(
+            "<mrow><mi>x</mi><title><mi>y</mi></title></mrow>",
+            Some(ParserOptions {
+                parse_mode: ParseMode::HTML,
+                ns: Namespaces::MathML,
+                ..Default::default()
+            }),
+        );
+        let [TemplateChildNode::Element(mrow)] = ast.children.as_slice() else {
+            unreachable!();
+        };
+        assert_eq!(mrow.ns(), &(Namespaces::MathML as u32));
+        let [_, TemplateChildNode::Element(title)] = mrow.children().as_slice() else {
+            unreachable!();
+        };
+        assert_eq!(title.ns(), &(Namespaces::MathML as u32));
+        // still in XML after closing <mi>, so <title> is not RCDATA
+        assert!(matches!(
+            title.children().as_slice(),
+            [TemplateChildNode::Element(mi)] if mi.tag() == "mi"
+        ));
+    }
+
+    #[test]
+    fn eof_in_script() {
+        let error_handling_options = TestErrorHandlingOptions::new();
+        let ast = base_parse(
+            "<script>var a = 1",
+            Some(ParserOptions {
+                parse_mode: ParseMode::HTML,
+                error_handling_options: Box::new(error_handling_options.clone()),
+                ..Default::default()
+            }),
+        );
+
+        let [TemplateChildNode::Element(script)] = ast.children.as_slice() else {
+            unreachable!();
+        };
+        assert_eq!(script.tag(), "script");
+        assert_eq!(script.loc().end.offset, 17);
+        assert!(matches!(
+            script.children().as_slice(),
+            [TemplateChildNode::Text(text)] if text.content == "var a = 1"
+        ));
+
+        let errors = error_handling_options.errors();
+        let codes: Vec<_> = errors.iter().map(|error| &error.code).collect();
+        assert_eq!(
+            codes,
+            [
+                &ErrorCodes::EOFInScriptHtmlCommentLikeText,
+                &ErrorCodes::XMissingEndTag
+            ]
+        );
+        let Some(loc) = &errors[0].loc else {
+            unreachable!();
+        };
+        assert_eq!(loc.start.offset, 17);
+    }
+
+    #[test]
+    fn eof_keeps_unclosed_elements_nested() {
+        let error_handling_options = TestErrorHandlingOptions::new();
+        let ast = base_parse(
+            "<div><span>",
+            Some(ParserOptions {
+                error_handling_options: Box::new(error_handling_options.clone()),
+                ..Default::default()
+            }),
+        );
+
+        let [TemplateChildNode::Element(div)] = ast.children.as_slice() else {
+            unreachable!();
+        };
+        assert_eq!(div.tag(), "div");
+        assert!(matches!(
+            div.children().as_slice(),
+            [TemplateChildNode::Element(span)] if span.tag() == "span"
+        ));
+        let errors = error_handling_options.errors();
+        let offsets: Vec<_> = errors
+            .iter()
+            .map(|error| (&error.code, error.loc.as_ref().unwrap().start.offset))
+            .collect();
+        assert_eq!(
+            offsets,
+            [
+                (&ErrorCodes::XMissingEndTag, 5),
+                (&ErrorCodes::XMissingEndTag, 0)
+            ]
+        );
+    }
 }
 
 #[cfg(test)]
 mod decode_entities_option {
-    use vue_compiler_core::base_parse;
+    use vue_compiler_core::{BaseElementProps, TemplateChildNode, base_parse};
+
+    fn attribute_value(template: &str) -> Option<String> {
+        let ast = base_parse(template, None);
+        let Some(TemplateChildNode::Element(el)) = ast.children.first() else {
+            unreachable!();
+        };
+        let Some(BaseElementProps::Attribute(attr)) = el.props().first() else {
+            unreachable!();
+        };
+        attr.value.as_ref().map(|value| value.content.clone())
+    }
 
+    #[test]
     fn use_decode_by_default() {
         let ast = base_parse("&gt;&lt;&amp;&apos;&quot;&foo;", None);
 
         assert!(ast.children.len() == 1);
+        let Some(TemplateChildNode::Text(text)) = ast.children.first() else {
+            unreachable!();
+        };
+        assert_eq!(text.content, "><&'\"&foo;");
+    }
+
+    #[test]
+    fn decode_all_named_entities() {
+        let ast = base_parse(
+            "&Afr;&zwnj;&NotEqualTilde;&CounterClockwiseContourIntegral;",
+            None,
+        );
+        let Some(TemplateChildNode::Text(text)) = ast.children.first() else {
+            unreachable!();
+        };
+        // `&NotEqualTilde;` decodes to two code points
+        assert_eq!(text.content, "\u{1d504}\u{200c}\u{2242}\u{338}\u{2233}");
+
+        assert_eq!(
+            attribute_value(r#"<a title="&bne;&fjlig;">"#),
+            Some("=\u{20e5}fj".to_string())
+        );
+    }
+
+    #[test]
+    fn decode_attribute_value() {
+        assert_eq!(
+            attribute_value(r#"<a href="x&amp;y">"#),
+            Some("x&y".to_string())
+        );
+        assert_eq!(
+            attribute_value("<a title=&#60;&#x3E;&copy;>"),
+            Some("<>\u{a9}".to_string())
+        );
+    }
+
+    #[test]
+    fn attribute_mode_keeps_legacy_entities_before_alphanumerics() {
+        // `&copy` without a semicolon is part of a query string here
+        assert_eq!(
+            attribute_value(r#"<a href="?a=1&copy=2&amp;b">"#),
+            Some("?a=1&copy=2&b".to_string())
+        );
+        // while text content decodes it
+        let ast = base_parse("&copy=2", None);
+        let Some(TemplateChildNode::Text(text)) = ast.children.first() else {
+            unreachable!();
+        };
+        assert_eq!(text.content, "\u{a9}=2");
     }
 }
 
@@ -2790,12 +3425,74 @@ mod whitespace_management_when_adopting_strategy_condense {
             }
         }
     }
+
+    /// should condense whitespace without newline between elements into a single space
+    #[test]
+    fn should_condense_whitespace_between_elements_into_a_single_space() {
+        let ast = base_parse("<a/>   <b/>", None);
+
+        assert_eq!(ast.children.len(), 3);
+        assert!(matches!(ast.children[0], TemplateChildNode::Element(_)));
+        assert!(matches!(
+            &ast.children[1],
+            TemplateChildNode::Text(text) if text.content == " "
+        ));
+        assert!(matches!(ast.children[2], TemplateChildNode::Element(_)));
+    }
+
+    /// should remove whitespace with newline between elements
+    #[test]
+    fn should_remove_whitespace_with_newline_between_elements() {
+        let ast = base_parse("<a/>\n  <b/>", None);
+
+        assert_eq!(ast.children.len(), 2);
+        assert!(matches!(ast.children[0], TemplateChildNode::Element(_)));
+        assert!(matches!(ast.children[1], TemplateChildNode::Element(_)));
+    }
+
+    /// should NOT remove whitespaces w/ newline between interpolations
+    #[test]
+    fn should_not_remove_whitespace_with_newline_between_interpolations() {
+        for template in ["{{a}}\n{{b}}", "{{ a }} \n {{ b }}", "{{a}}   {{b}}"] {
+            let ast = base_parse(template, None);
+
+            assert_eq!(ast.children.len(), 3, "{template}");
+            assert!(matches!(
+                ast.children[0],
+                TemplateChildNode::Interpolation(_)
+            ));
+            assert!(matches!(
+                &ast.children[1],
+                TemplateChildNode::Text(text) if text.content == " "
+            ));
+            assert!(matches!(
+                ast.children[2],
+                TemplateChildNode::Interpolation(_)
+            ));
+        }
+    }
+
+    /// should NOT remove whitespaces w/ newline between interpolation and element
+    #[test]
+    fn should_not_remove_whitespace_with_newline_between_interpolation_and_element() {
+        for template in ["{{a}}\n<b/>", "<a/>\n{{b}}"] {
+            let ast = base_parse(template, None);
+
+            assert_eq!(ast.children.len(), 3, "{template}");
+            assert!(matches!(
+                &ast.children[1],
+                TemplateChildNode::Text(text) if text.content == " "
+            ));
+        }
+    }
 }
 
 /// expression parsing
 #[cfg(test)]
 mod expression_parsing {
-    use vue_compiler_core::{BaseElementProps, ParserOptions, TemplateChildNode, base_parse};
+    use vue_compiler_core::{
+        BaseElementProps, ExpressionNode, ParserOptions, TemplateChildNode, base_parse,
+    };
 
     /// v-for
     #[test]
@@ -2819,4 +3516,228 @@ mod expression_parsing {
         };
         assert!(directive.for_parse_result.is_some());
     }
+
+    /// v-for alias and source split
+    #[test]
+    fn v_for_alias_and_source() {
+        for (template, alias, source, source_offset) in [
+            (r#"<div v-for="x of list"/>"#, "x", "list", 17),
+            (r#"<div v-for="x in obj.in"/>"#, "x", "obj.in", 17),
+            (r#"<div v-for="(a, b) in pairs"/>"#, "a, b", "pairs", 22),
+            (r#"<div v-for="list in list"/>"#, "list", "list", 20),
+        ] {
+            let ast = base_parse(
+                template,
+                Some(ParserOptions {
+                    prefix_identifiers: Some(true),
+                    ..Default::default()
+                }),
+            );
+            let Some(TemplateChildNode::Element(el)) = ast.children.first() else {
+                unreachable!();
+            };
+            let Some(BaseElementProps::Directive(directive)) = el.props().first() else {
+                unreachable!();
+            };
+            let Some(result) = &directive.for_parse_result else {
+                panic!("{template}");
+            };
+            let Some(ExpressionNode::Simple(value)) = &result.value else {
+                unreachable!();
+            };
+            let ExpressionNode::Simple(exp) = &result.source else {
+                unreachable!();
+            };
+            assert_eq!(value.content, alias, "{template}");
+            assert_eq!(exp.content, source, "{template}");
+            assert_eq!(exp.loc.start.offset, source_offset, "{template}");
+        }
+    }
+}
+
+/// ParserOptions builder
+#[cfg(test)]
+mod parser_options_builder {
+    use super::TestErrorHandlingOptions;
+    use vue_compiler_core::{
+        ElementNode, ErrorCodes, NodeTypes, ParseMode, ParserOptions, TemplateChildNode, base_parse,
+    };
+
+    #[test]
+    fn void_tag_predicate() {
+        let error_handling_options = TestErrorHandlingOptions::new();
+        let ast = base_parse(
+            "<img>after<!--c--></div>",
+            Some(
+                ParserOptions::builder()
+                    .parse_mode(ParseMode::BASE)
+                    .void_tag(|tag| tag == "img")
+                    .comments(true)
+                    .on_error(error_handling_options.clone())
+                    .build(),
+            ),
+        );
+
+        assert_eq!(ast.children.len(), 3);
+        let Some(TemplateChildNode::Element(ElementNode::PlainElement(element))) =
+            ast.children.first()
+        else {
+            panic!("expected a plain element");
+        };
+        assert_eq!(element.tag, "img");
+        assert!(element.children.is_empty());
+        assert_eq!(ast.children[1].type_(), NodeTypes::Text);
+        assert_eq!(ast.children[2].type_(), NodeTypes::Comment);
+
+        let errors = error_handling_options.errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code, ErrorCodes::XInvalidEndTag);
+    }
+
+    #[test]
+    fn comments_follow_dev_constant() {
+        let options = ParserOptions::builder()
+            .global_compile_time_constants(vue_compiler_core::GlobalCompileTimeConstants {
+                __dev__: true,
+                ..Default::default()
+            })
+            .build();
+        assert_eq!(options.comments, Some(true));
+
+        let options = ParserOptions::builder().build();
+        assert_eq!(options.comments, Some(false));
+    }
+
+    #[test]
+    fn global_compile_time_constant_setters() {
+        let options = ParserOptions::default()
+            .with_dev(true)
+            .with_browser(true)
+            .with_test(true);
+        assert!(options.global_compile_time_constants.__dev__);
+        assert!(options.global_compile_time_constants.__browser__);
+        assert!(options.global_compile_time_constants.__test__);
+    }
+
+    #[test]
+    fn dev_only_errors_in_browser_build() {
+        let parse_errors = |options: ParserOptions| {
+            let error_handling_options = TestErrorHandlingOptions::new();
+            base_parse(
+                "</div><div",
+                Some(ParserOptions {
+                    error_handling_options: Box::new(error_handling_options.clone()),
+                    ..options
+                }),
+            );
+            error_handling_options
+                .errors()
+                .into_iter()
+                .map(|error| error.code)
+                .collect::<Vec<_>>()
+        };
+
+        // EOF errors are only reported in dev browser builds
+        assert_eq!(
+            parse_errors(ParserOptions::default().with_browser(true)),
+            vec![ErrorCodes::XInvalidEndTag]
+        );
+        assert_eq!(
+            parse_errors(ParserOptions::default().with_browser(true).with_dev(true)),
+            vec![ErrorCodes::XInvalidEndTag, ErrorCodes::EOFInTag]
+        );
+    }
+}
+
+/// store source option
+#[cfg(test)]
+mod store_source_option {
+    use vue_compiler_core::{ParserOptions, TemplateChildNode, base_parse};
+
+    #[test]
+    fn keeps_source_by_default() {
+        let ast = base_parse("<div>hello</div>", None);
+        assert_eq!(ast.source, "<div>hello</div>");
+    }
+
+    #[test]
+    fn skips_source_when_disabled() {
+        let ast = base_parse(
+            "<div>hello</div>",
+            Some(ParserOptions {
+                store_source: false,
+                ..Default::default()
+            }),
+        );
+        assert!(ast.source.is_empty());
+
+        let Some(TemplateChildNode::Element(element)) = ast.children.first() else {
+            panic!("expected an element");
+        };
+        assert_eq!(element.loc().source, "<div>hello</div>");
+        let Some(TemplateChildNode::Text(text)) = element.children().first() else {
+            panic!("expected a text node");
+        };
+        assert_eq!(text.content, "hello");
+        assert_eq!(text.loc.source, "hello");
+    }
+}
+
+#[cfg(test)]
+mod source_roundtrip {
+    use crate::test_utils::assert_source_roundtrip;
+
+    #[test]
+    fn node_locations_match_template_slices() {
+        for template in [
+            "hello world",
+            "<div></div>",
+            "<div/>",
+            "<div>\n  <span>text</span>\n</div>",
+            r#"<div id="foo" class='bar' disabled data-x=baz></div>"#,
+            r#"<div :class="{ a: b }" @click.stop.prevent="onClick($event)"/>"#,
+            r#"<div v-bind:[key]="value" #default="{ item }" v-on:custom-event.once="handler"/>"#,
+            r#"<ul><li v-for="(item, index) in items" :key="item.id">{{ item.name }}</li></ul>"#,
+            "<p>{{ a }} and {{ b + c }}</p>",
+            "{{ foo }}",
+            "<!-- comment --><div><!---->x</div>",
+            "<div v-if=\"ok\">a</div><p v-else-if=\"other\">b</p><span v-else>c</span>",
+            "<textarea>  <b>raw</b>  </textarea>",
+            "a &amp; b &lt;c&gt;",
+            "<div>\r\n  {{ msg }}\r\n</div>",
+            "<p>é<b/></p>",
+        ] {
+            assert_source_roundtrip(template);
+        }
+    }
+
+    #[test]
+    fn locations_are_finalized_in_malformed_templates() {
+        for template in [
+            "<div",
+            "<div id=\"a\"",
+            "<div id=x",
+            "<div disabled",
+            "<div :a=\"b",
+            "<div v-if",
+            "<div><span>",
+            "<div></span></div>",
+            "<div>a</div></div>",
+            "</div>",
+            "{{ a",
+            "<!-- abc",
+            "<!-->",
+            "<!--a--!>",
+            "<!foo",
+            "<?xml x?>",
+            "<div v-=\"x\"/>",
+            "<div .foo=\"x\"/>",
+            "<div id=\"a\" id=\"b\"/>",
+            "<template #foo.bar=\"x\"/>",
+            "<div v-pre :a=\"b\">{{ x }}</div>",
+            "<script>a",
+        ] {
+            assert_source_roundtrip(template);
+        }
+    }
 }
@@ -0,0 +1,66 @@
+//! Parses a large template under a counting allocator, in its own test binary
+//! so that other tests don't disturb the numbers.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(allocated, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[cfg(test)]
+mod large_template {
+    use super::{ALLOCATED, PEAK};
+    use std::sync::atomic::Ordering;
+    use vue_compiler_core::{ParserOptions, base_parse};
+
+    #[test]
+    fn parse_one_megabyte_template() {
+        let text = "x".repeat(10_000);
+        let template = format!("<p>{text}</p>\n").repeat(100);
+        assert!(template.len() > 1_000_000);
+
+        let before = ALLOCATED.load(Ordering::Relaxed);
+        PEAK.store(before, Ordering::Relaxed);
+        let ast = base_parse(
+            &template,
+            Some(ParserOptions {
+                store_source: false,
+                ..Default::default()
+            }),
+        );
+        let peak = PEAK.load(Ordering::Relaxed) - before;
+
+        assert_eq!(ast.children.len(), 100);
+        // the AST itself holds about three copies of the text: the text content
+        // and the locations of the text and its element. The tokenizer borrows
+        // the input instead of adding a copy of its own.
+        assert!(
+            peak < template.len() * 6,
+            "peak {peak} for {} bytes of input",
+            template.len()
+        );
+    }
+}
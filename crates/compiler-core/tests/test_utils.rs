@@ -1,9 +1,48 @@
+// shared between test crates, each of which only uses some of the helpers
+#![allow(dead_code)]
+
+use std::{cell::RefCell, rc::Rc};
 use vue_compiler_core::{
-    ElementNode, Namespaces, PlainElementNode, PlainElementNodeCodegenNode, PropsExpression,
-    SourceLocation, VNodeCall, VNodeCallChildren, VNodeCallTag,
+    BaseElementProps, CompilerError, ElementNode, ErrorHandlingOptions, ExpressionNode, Namespaces,
+    ParserOptions, PlainElementNode, PlainElementNodeCodegenNode, PropsExpression, SourceLocation,
+    TemplateChildNode, VNodeCall, VNodeCallChildren, VNodeCallTag, base_parse,
 };
 use vue_compiler_shared::PatchFlags;
 
+/// Collects the errors and warnings reported through the options it is
+/// cloned into.
+#[derive(Debug, Clone, Default)]
+pub struct TestErrorHandlingOptions {
+    errors: Rc<RefCell<Vec<CompilerError>>>,
+    warnings: Rc<RefCell<Vec<CompilerError>>>,
+}
+
+impl TestErrorHandlingOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes the errors collected so far.
+    pub fn errors(&self) -> Vec<CompilerError> {
+        self.errors.take()
+    }
+
+    /// Takes the warnings collected so far.
+    pub fn warnings(&self) -> Vec<CompilerError> {
+        self.warnings.take()
+    }
+}
+
+impl ErrorHandlingOptions for TestErrorHandlingOptions {
+    fn on_warn(&mut self, warning: CompilerError) {
+        self.warnings.borrow_mut().push(warning);
+    }
+
+    fn on_error(&mut self, error: CompilerError) {
+        self.errors.borrow_mut().push(error);
+    }
+}
+
 pub fn create_element_with_codegen(
     tag: impl Into<VNodeCallTag>,
     props: Option<PropsExpression>,
@@ -21,6 +60,7 @@ pub fn create_element_with_codegen(
             props,
             children,
             patch_flag,
+            directives: None,
             is_block: false,
             disable_tracking: false,
             is_component: false,
@@ -28,9 +68,108 @@ pub fn create_element_with_codegen(
         })),
         ssr_codegen_node: None,
         loc: SourceLocation::loc_stub(),
+        inner_loc: None,
     })
 }
 
 pub fn gen_flag_text(flag: PatchFlags) -> String {
     format!("{} /* {} */", flag, flag.as_str())
 }
+
+/// Parses `template` and checks that the `loc` of every node, prop and
+/// expression matches the template slice at its offsets, with consistent
+/// lines and columns.
+pub fn assert_source_roundtrip(template: &str) {
+    let ast = base_parse(
+        template,
+        Some(ParserOptions {
+            comments: Some(true),
+            prefix_identifiers: Some(true),
+            ..Default::default()
+        }),
+    );
+    assert_loc(template, &ast.loc, "root");
+    assert_children(template, &ast.children);
+}
+
+fn assert_children(template: &str, children: &[TemplateChildNode]) {
+    for child in children {
+        assert_loc(template, child.loc(), "node");
+        match child {
+            TemplateChildNode::Element(el) => {
+                if let Some(inner_loc) = el.inner_loc() {
+                    assert_loc(template, inner_loc, "element inner");
+                }
+                for prop in el.props() {
+                    assert_prop(template, prop);
+                }
+                assert_children(template, el.children());
+            }
+            TemplateChildNode::Interpolation(node) => {
+                assert_expression(template, &node.content, "interpolation content");
+            }
+            _ => {}
+        }
+    }
+}
+
+fn assert_prop(template: &str, prop: &BaseElementProps) {
+    match prop {
+        BaseElementProps::Attribute(attr) => {
+            assert_loc(template, &attr.loc, "attribute");
+            assert_loc(template, &attr.name_loc, "attribute name");
+            if let Some(value) = &attr.value {
+                assert_loc(template, &value.loc, "attribute value");
+            }
+        }
+        BaseElementProps::Directive(dir) => {
+            assert_loc(template, &dir.loc, "directive");
+            if let Some(arg) = &dir.arg {
+                assert_expression(template, arg, "directive arg");
+            }
+            if let Some(exp) = &dir.exp {
+                assert_expression(template, exp, "directive exp");
+            }
+            for modifier in &dir.modifiers {
+                assert_loc(template, &modifier.loc, "directive modifier");
+            }
+            if let Some(result) = &dir.for_parse_result {
+                assert_expression(template, &result.source, "v-for source");
+                for alias in [&result.value, &result.key, &result.index]
+                    .into_iter()
+                    .flatten()
+                {
+                    assert_expression(template, alias, "v-for alias");
+                }
+            }
+        }
+    }
+}
+
+fn assert_expression(template: &str, exp: &ExpressionNode, what: &str) {
+    assert_loc(template, exp.loc(), what);
+}
+
+fn assert_loc(template: &str, loc: &SourceLocation, what: &str) {
+    let (start, end) = (&loc.start, &loc.end);
+    assert!(
+        start.offset <= end.offset && end.offset <= template.len(),
+        "{what} {loc:?} out of bounds in {template:?}"
+    );
+    assert_eq!(
+        loc.source,
+        template[start.offset..end.offset],
+        "{what} source mismatch in {template:?}"
+    );
+    for pos in [start, end] {
+        let before = &template[..pos.offset];
+        let line = before.matches('\n').count() + 1;
+        // columns count bytes, like offsets
+        let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+        assert_eq!(
+            (pos.line, pos.column),
+            (line, column),
+            "{what} position {pos:?} mismatch in {template:?}"
+        );
+    }
+}
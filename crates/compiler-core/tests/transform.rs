@@ -0,0 +1,475 @@
+#[cfg(test)]
+mod transform {
+    use std::cell::RefCell;
+    use vue_compiler_core::{
+        BaseCompileSource, CodegenMode, CodegenResult, CompilerOptions, ElementNode, ElementTypes,
+        IfCodegenNode, JSChildNode, NodeTransformState, NodeTypes, ParserOptions,
+        PlainElementNodeCodegenNode, PropsExpression, RootCodegenNode, SimpleExpressionNode,
+        TemplateChildNode, TextNode, TransformAncestor, TransformContext, TransformControl,
+        TransformNode, base_compile, base_parse, generate, get_base_transform_preset, transform,
+        transform_element,
+    };
+
+    #[test]
+    fn transform_pre_parsed_root() {
+        let mut ast = base_parse(r#"<div v-if="a"/>"#, None);
+        let (_, mut transform_options, _) = CompilerOptions::default().split();
+        let (node_transforms, directive_transforms) = get_base_transform_preset(false);
+        transform_options.node_transforms = Some(node_transforms);
+        transform_options.directive_transforms = Some(directive_transforms);
+        transform(&mut ast, transform_options);
+
+        assert_eq!(ast.transformed, Some(true));
+        let Some(RootCodegenNode::TemplateChild(TemplateChildNode::If(node))) = &ast.codegen_node
+        else {
+            unreachable!();
+        };
+        let Some(IfCodegenNode::IfConditional(conditional)) = &node.codegen_node else {
+            unreachable!();
+        };
+        assert!(matches!(
+            &conditional.test,
+            JSChildNode::Simple(test) if test.content == "a"
+        ));
+        assert!(matches!(conditional.consequent, JSChildNode::VNodeCall(_)));
+    }
+
+    thread_local! {
+        static VISITED: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    #[derive(Debug)]
+    struct ReplaceX;
+
+    impl NodeTransformState for ReplaceX {
+        fn transform(
+            &mut self,
+            node: &mut TransformNode,
+            _context: &mut TransformContext,
+        ) -> TransformControl {
+            let TransformNode::TemplateChild(TemplateChildNode::Element(node)) = node else {
+                return TransformControl::Continue;
+            };
+            if node.tag() != "x" {
+                return TransformControl::Continue;
+            }
+            TransformControl::Replaced(Box::new(TemplateChildNode::Text(TextNode::new(
+                "replaced",
+                node.loc().clone(),
+            ))))
+        }
+    }
+
+    fn replace_x(
+        _node: &TransformNode,
+        _context: &mut TransformContext,
+    ) -> Option<Box<dyn NodeTransformState>> {
+        Some(Box::new(ReplaceX))
+    }
+
+    #[derive(Debug)]
+    struct RecordElement;
+
+    impl NodeTransformState for RecordElement {
+        fn transform(
+            &mut self,
+            node: &mut TransformNode,
+            _context: &mut TransformContext,
+        ) -> TransformControl {
+            if let TransformNode::TemplateChild(TemplateChildNode::Element(node)) = node {
+                VISITED
+                    .with_borrow_mut(|visited| visited.push(format!("transform {}", node.tag())));
+            }
+            TransformControl::Continue
+        }
+
+        fn exit(&mut self, node: &mut TransformNode, _context: &mut TransformContext) {
+            if let TransformNode::TemplateChild(TemplateChildNode::Element(node)) = node {
+                VISITED.with_borrow_mut(|visited| visited.push(format!("exit {}", node.tag())));
+            }
+        }
+    }
+
+    fn record_element(
+        _node: &TransformNode,
+        _context: &mut TransformContext,
+    ) -> Option<Box<dyn NodeTransformState>> {
+        Some(Box::new(RecordElement))
+    }
+
+    #[test]
+    fn replaced_node_skips_remaining_transforms() {
+        VISITED.with_borrow_mut(Vec::clear);
+        let mut ast = base_parse("<div><x><span/></x></div>", None);
+        let (_, mut transform_options, _) = CompilerOptions::default().split();
+        transform_options.node_transforms = Some(vec![replace_x, record_element]);
+        transform(&mut ast, transform_options);
+
+        let TemplateChildNode::Element(ElementNode::PlainElement(div)) = &ast.children[0] else {
+            unreachable!();
+        };
+        assert!(matches!(
+            &div.children[..],
+            [TemplateChildNode::Text(text)] if text.content == "replaced"
+        ));
+        VISITED.with_borrow(|visited| {
+            assert_eq!(visited, &["transform div", "exit div"]);
+        });
+    }
+
+    #[derive(Debug)]
+    struct UseMyHelper;
+
+    impl NodeTransformState for UseMyHelper {
+        fn transform(
+            &mut self,
+            _node: &mut TransformNode,
+            context: &mut TransformContext,
+        ) -> TransformControl {
+            assert_eq!(context.register_helper("myHelper"), "_myHelper");
+            TransformControl::Continue
+        }
+    }
+
+    fn use_my_helper(
+        node: &TransformNode,
+        _context: &mut TransformContext,
+    ) -> Option<Box<dyn NodeTransformState>> {
+        matches!(node, TransformNode::TemplateChild(_))
+            .then(|| Box::new(UseMyHelper) as Box<dyn NodeTransformState>)
+    }
+
+    #[test]
+    fn register_helper() {
+        let options = CompilerOptions {
+            mode: Some(CodegenMode::Module),
+            node_transforms: Some(vec![use_my_helper]),
+            ..Default::default()
+        };
+        let CodegenResult { ast, code, .. } =
+            base_compile(BaseCompileSource::String("<a/><b/>".to_string()), options);
+
+        // registered once per node, imported once
+        assert_eq!(
+            ast.helpers
+                .iter()
+                .filter(|helper| *helper == "myHelper")
+                .count(),
+            1
+        );
+        assert!(code.starts_with("import { myHelper as _myHelper, "));
+    }
+
+    #[derive(Debug)]
+    struct HoistProps;
+
+    impl NodeTransformState for HoistProps {
+        fn exit(&mut self, node: &mut TransformNode, context: &mut TransformContext) {
+            let TransformNode::TemplateChild(TemplateChildNode::Element(
+                ElementNode::PlainElement(node),
+            )) = node
+            else {
+                return;
+            };
+            let Some(PlainElementNodeCodegenNode::VNodeCall(vnode_call)) = &mut node.codegen_node
+            else {
+                return;
+            };
+            let Some(PropsExpression::Object(props)) = &mut vnode_call.props else {
+                return;
+            };
+            for prop in &mut props.properties {
+                let value = std::mem::replace(
+                    &mut prop.value,
+                    JSChildNode::Simple(SimpleExpressionNode::constant("")),
+                );
+                prop.value = JSChildNode::Simple(context.hoist(value));
+            }
+        }
+    }
+
+    fn hoist_props(
+        _node: &TransformNode,
+        _context: &mut TransformContext,
+    ) -> Option<Box<dyn NodeTransformState>> {
+        Some(Box::new(HoistProps))
+    }
+
+    #[test]
+    fn hoist() {
+        let mut ast = base_parse(r#"<div id="foo" class="bar"/>"#, None);
+        let (_, mut transform_options, codegen_options) = CompilerOptions {
+            mode: Some(CodegenMode::Module),
+            ..Default::default()
+        }
+        .split();
+        transform_options.node_transforms = Some(vec![hoist_props, transform_element]);
+        transform(&mut ast, transform_options);
+
+        assert_eq!(ast.hoists.len(), 2);
+        let code = generate(ast, codegen_options).code;
+        assert!(code.contains("const _hoisted_1 = \"foo\"\nconst _hoisted_2 = \"bar\"\n"));
+        assert!(code.contains(
+            r#"_createElementBlock("div", {
+    id: _hoisted_1,
+    class: _hoisted_2
+  })"#
+        ));
+    }
+
+    #[test]
+    fn hoist_appends_to_existing_hoists() {
+        let mut ast = base_parse(r#"<div id="foo" class="bar"/>"#, None);
+        ast.hoists = vec![Some(JSChildNode::Simple(SimpleExpressionNode::constant(
+            "baz",
+        )))];
+        let (_, mut transform_options, codegen_options) = CompilerOptions {
+            mode: Some(CodegenMode::Module),
+            ..Default::default()
+        }
+        .split();
+        transform_options.node_transforms = Some(vec![hoist_props, transform_element]);
+        transform(&mut ast, transform_options);
+
+        assert_eq!(ast.hoists.len(), 3);
+        let code = generate(ast, codegen_options).code;
+        assert!(code.contains(
+            "const _hoisted_1 = \"baz\"\nconst _hoisted_2 = \"foo\"\nconst _hoisted_3 = \"bar\"\n"
+        ));
+    }
+
+    #[test]
+    fn hoist_skips_hoisted_references() {
+        let mut ast = base_parse(r#"<div id="foo" class="bar"/>"#, None);
+        let (_, mut transform_options, _) = CompilerOptions::default().split();
+        // the second hoist_props revisits the `_hoisted_N` references
+        transform_options.node_transforms = Some(vec![hoist_props, hoist_props, transform_element]);
+        transform(&mut ast, transform_options);
+        assert_eq!(ast.hoists.len(), 2);
+    }
+
+    #[derive(Debug)]
+    struct CacheTwice;
+
+    impl NodeTransformState for CacheTwice {
+        fn exit(&mut self, _node: &mut TransformNode, context: &mut TransformContext) {
+            let first = context.cache(
+                JSChildNode::Simple(SimpleExpressionNode::constant("a")),
+                false,
+            );
+            assert_eq!(first.index, 0);
+            assert!(!first.need_pause_tracking);
+            assert!(!first.in_v_once);
+
+            let second = context.cache(
+                JSChildNode::Simple(SimpleExpressionNode::constant("b")),
+                true,
+            );
+            assert_eq!(second.index, 1);
+            assert!(second.need_pause_tracking);
+            assert!(second.in_v_once);
+        }
+    }
+
+    fn cache_twice(
+        node: &TransformNode,
+        _context: &mut TransformContext,
+    ) -> Option<Box<dyn NodeTransformState>> {
+        matches!(node, TransformNode::Root(_))
+            .then(|| Box::new(CacheTwice) as Box<dyn NodeTransformState>)
+    }
+
+    #[test]
+    fn cache() {
+        let mut ast = base_parse("<div/>", None);
+        let (_, mut transform_options, _) = CompilerOptions::default().split();
+        transform_options.node_transforms = Some(vec![cache_twice]);
+        transform(&mut ast, transform_options);
+
+        assert_eq!(ast.cached.len(), 2);
+        assert!(matches!(
+            &ast.cached[1],
+            Some(cache_exp) if cache_exp.index == 1 && cache_exp.in_v_once
+        ));
+    }
+
+    thread_local! {
+        static ANCESTORS: RefCell<Vec<Vec<TransformAncestor>>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn record_ancestors(
+        node: &TransformNode,
+        context: &mut TransformContext,
+    ) -> Option<Box<dyn NodeTransformState>> {
+        match node {
+            TransformNode::Root(_) => assert_eq!(context.parent(), None),
+            TransformNode::TemplateChild(TemplateChildNode::Text(_)) => {
+                assert_eq!(context.parent(), context.ancestors().last());
+                ANCESTORS.with_borrow_mut(|ancestors| ancestors.push(context.ancestors().to_vec()));
+            }
+            _ => {}
+        }
+        None
+    }
+
+    #[test]
+    fn ancestors() {
+        ANCESTORS.with_borrow_mut(Vec::clear);
+        let mut ast = base_parse("<div><Comp><b>a</b></Comp></div>b", None);
+        let (_, mut transform_options, _) = CompilerOptions::default().split();
+        transform_options.node_transforms = Some(vec![record_ancestors]);
+        transform(&mut ast, transform_options);
+
+        let root = TransformAncestor {
+            type_: NodeTypes::Root,
+            tag: None,
+            tag_type: None,
+        };
+        let element = |tag: &str, tag_type| TransformAncestor {
+            type_: NodeTypes::Element,
+            tag: Some(tag.to_string()),
+            tag_type: Some(tag_type),
+        };
+        ANCESTORS.with_borrow(|ancestors| {
+            assert_eq!(
+                ancestors,
+                &[
+                    vec![
+                        root.clone(),
+                        element("div", ElementTypes::Element),
+                        element("Comp", ElementTypes::Component),
+                        element("b", ElementTypes::Element),
+                    ],
+                    // popped again after leaving the <div>
+                    vec![root.clone()],
+                ]
+            );
+        });
+    }
+
+    fn remove_comment(
+        node: &TransformNode,
+        context: &mut TransformContext,
+    ) -> Option<Box<dyn NodeTransformState>> {
+        if matches!(
+            node,
+            TransformNode::TemplateChild(TemplateChildNode::Comment(_))
+        ) {
+            context.remove_node();
+        }
+        None
+    }
+
+    #[test]
+    fn remove_node() {
+        VISITED.with_borrow_mut(Vec::clear);
+        let mut ast = base_parse(
+            "<a/><!--x--><!--y--><b><!--z--></b><!--w--><c/>",
+            Some(ParserOptions {
+                comments: Some(true),
+                ..Default::default()
+            }),
+        );
+        let (_, mut transform_options, _) = CompilerOptions::default().split();
+        transform_options.node_transforms = Some(vec![remove_comment, record_element]);
+        transform(&mut ast, transform_options);
+
+        // removed nodes are not transformed, the following siblings still are
+        VISITED.with_borrow(|visited| {
+            assert_eq!(
+                visited,
+                &[
+                    "transform a",
+                    "exit a",
+                    "transform b",
+                    "exit b",
+                    "transform c",
+                    "exit c"
+                ]
+            );
+        });
+        assert_eq!(ast.children.len(), 3);
+        let TemplateChildNode::Element(b) = &ast.children[1] else {
+            unreachable!();
+        };
+        assert!(b.children().is_empty());
+    }
+
+    #[test]
+    fn remove_node_before_structural_directives() {
+        let (mut node_transforms, directive_transforms) = get_base_transform_preset(false);
+        node_transforms.insert(0, remove_comment);
+        let options = CompilerOptions {
+            comments: Some(true),
+            node_transforms: Some(node_transforms),
+            directive_transforms: Some(directive_transforms),
+            ..Default::default()
+        };
+        let CodegenResult { ast, .. } = base_compile(
+            BaseCompileSource::String(
+                r#"<!--x--><div v-if="a"/><!--y--><p v-else/><!--z--><i v-for="i in list" :key="i"/>"#
+                    .to_string(),
+            ),
+            options,
+        );
+
+        assert!(matches!(
+            &ast.children[..],
+            [TemplateChildNode::If(if_node), TemplateChildNode::For(for_node)]
+                if if_node.branches.len() == 2
+                    && if_node.codegen_node.is_some()
+                    && for_node.codegen_node.is_some()
+        ));
+    }
+
+    #[derive(Debug)]
+    struct RecordText;
+
+    impl NodeTransformState for RecordText {
+        fn transform(
+            &mut self,
+            node: &mut TransformNode,
+            _context: &mut TransformContext,
+        ) -> TransformControl {
+            if let TransformNode::TemplateChild(TemplateChildNode::Text(text)) = node {
+                VISITED.with_borrow_mut(|visited| visited.push(text.content.clone()));
+            }
+            TransformControl::Continue
+        }
+    }
+
+    fn replace_comment(
+        node: &TransformNode,
+        context: &mut TransformContext,
+    ) -> Option<Box<dyn NodeTransformState>> {
+        if let TransformNode::TemplateChild(TemplateChildNode::Comment(comment)) = node {
+            context.replace_node(TemplateChildNode::Text(TextNode::new(
+                comment.content.clone(),
+                comment.loc.clone(),
+            )));
+        }
+        Some(Box::new(RecordText))
+    }
+
+    #[test]
+    fn replace_node() {
+        VISITED.with_borrow_mut(Vec::clear);
+        let mut ast = base_parse(
+            "<!--x-->",
+            Some(ParserOptions {
+                comments: Some(true),
+                ..Default::default()
+            }),
+        );
+        let (_, mut transform_options, _) = CompilerOptions::default().split();
+        transform_options.node_transforms = Some(vec![replace_comment]);
+        transform(&mut ast, transform_options);
+
+        // the remaining transforms see the new node
+        VISITED.with_borrow(|visited| assert_eq!(visited, &["x"]));
+        assert!(matches!(
+            &ast.children[..],
+            [TemplateChildNode::Text(text)] if text.content == "x"
+        ));
+    }
+}
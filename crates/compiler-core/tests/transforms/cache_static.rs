@@ -0,0 +1,169 @@
+#[cfg(test)]
+mod compiler_hoist_static {
+    use vue_compiler_core::{
+        BaseCompileSource, CodegenMode, CodegenResult, CompilerOptions, ElementNode, JSChildNode,
+        PlainElementNodeCodegenNode, TemplateChildNode, TextCallCodegenNode, base_compile,
+    };
+    use vue_compiler_shared::PatchFlags;
+
+    fn compile_with_hoist_static(template: &str) -> CodegenResult {
+        base_compile(
+            BaseCompileSource::String(template.to_string()),
+            CompilerOptions {
+                mode: Some(CodegenMode::Module),
+                prefix_identifiers: Some(true),
+                hoist_static: Some(true),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn should_not_hoist_root_node() {
+        let CodegenResult { ast, .. } = compile_with_hoist_static("<div/>");
+        assert!(ast.hoists.is_empty());
+    }
+
+    #[test]
+    fn hoist_static_element_subtree() {
+        let CodegenResult { ast, code, .. } =
+            compile_with_hoist_static(r#"<div :id="x"><span class="a"><b>hi</b></span></div>"#);
+
+        assert_eq!(ast.hoists.len(), 1);
+        let Some(JSChildNode::VNodeCall(hoisted)) = &ast.hoists[0] else {
+            unreachable!();
+        };
+        assert_eq!(hoisted.patch_flag, Some(PatchFlags::Hoisted));
+        assert!(code.contains(
+            r#"const _hoisted_1 = /*@__PURE__*/_createElementVNode("span", { class: "a" }, [
+  /*@__PURE__*/_createElementVNode("b", null, "hi")
+], -1)"#
+        ));
+        assert!(code.contains(
+            r#"_createElementBlock("div", { id: _ctx.x }, [
+    _hoisted_1
+  ])"#
+        ));
+    }
+
+    #[test]
+    fn should_not_hoist_again_on_second_transform() {
+        let options = || CompilerOptions {
+            hoist_static: Some(true),
+            ..Default::default()
+        };
+        let template = r#"<div :id="x"><p><span class="a">hi</span>{{ y }}</p></div>"#;
+        let first = base_compile(BaseCompileSource::String(template.to_string()), options());
+        assert_eq!(first.ast.hoists.len(), 1);
+        let second = base_compile(BaseCompileSource::RootNode(Box::new(first.ast)), options());
+
+        assert_eq!(second.ast.hoists.len(), 1);
+        assert_eq!(second.code, first.code);
+    }
+
+    #[test]
+    fn pure_annotation_on_hoisted_vnode_call() {
+        let CodegenResult { code, .. } =
+            compile_with_hoist_static(r#"<div :id="x"><span>hi</span></div>"#);
+
+        assert!(code.contains(
+            r#"const _hoisted_1 = /*@__PURE__*/_createElementVNode("span", null, "hi", -1)"#
+        ));
+        // only hoisted nodes are annotated, not the render function body
+        let render = &code[code.find("export function render").unwrap()..];
+        assert!(!render.contains("__PURE__"));
+    }
+
+    #[test]
+    fn should_not_hoist_single_static_text_child() {
+        let CodegenResult { ast, code, .. } =
+            compile_with_hoist_static(r#"<div :id="x">hello</div>"#);
+
+        assert!(ast.hoists.is_empty());
+        assert!(code.contains(r#"_createElementBlock("div", { id: _ctx.x }, "hello")"#));
+    }
+
+    #[test]
+    fn hoist_static_svg_as_plain_vnode() {
+        let CodegenResult { ast, code, .. } =
+            compile_with_hoist_static(r#"<div><svg><path d="x"/></svg>{{a}}</div>"#);
+
+        assert_eq!(ast.hoists.len(), 1);
+        let Some(JSChildNode::VNodeCall(hoisted)) = &ast.hoists[0] else {
+            unreachable!();
+        };
+        assert!(!hoisted.is_block);
+        assert!(code.contains(
+            r#"const _hoisted_1 = /*@__PURE__*/_createElementVNode("svg", null, [
+  /*@__PURE__*/_createElementVNode("path", { d: "x" })
+], -1)"#
+        ));
+        assert!(code.contains(
+            r#"_createElementBlock("div", null, [
+    _hoisted_1,"#
+        ));
+    }
+
+    #[test]
+    fn hoist_static_text_among_dynamic_siblings() {
+        let CodegenResult { ast, code, .. } =
+            compile_with_hoist_static(r#"<div><i :id="x"/>text</div>"#);
+
+        assert_eq!(ast.hoists.len(), 1);
+        assert!(code.contains(r#"const _hoisted_1 = /*@__PURE__*/_createTextVNode("text")"#));
+        assert!(code.contains(
+            r#"_createElementBlock("div", null, [
+    _createElementVNode("i", { id: _ctx.x }),
+    _hoisted_1
+  ])"#
+        ));
+    }
+
+    #[test]
+    fn should_not_hoist_static_text_among_static_siblings() {
+        let CodegenResult { ast, .. } = compile_with_hoist_static("<span>a</span>text");
+
+        assert_eq!(ast.hoists.len(), 1);
+        let [
+            TemplateChildNode::Element(ElementNode::PlainElement(span)),
+            TemplateChildNode::TextCall(text),
+        ] = ast.children.as_slice()
+        else {
+            unreachable!();
+        };
+        assert!(matches!(
+            span.codegen_node,
+            Some(PlainElementNodeCodegenNode::Simple(_))
+        ));
+        assert!(matches!(text.codegen_node, TextCallCodegenNode::Call(_)));
+    }
+
+    #[test]
+    fn should_not_hoist_element_with_dynamic_props_or_children() {
+        for template in [
+            r#"<div><p :id="x"/></div>"#,
+            r#"<div><p>{{ x }}</p></div>"#,
+            r#"<div><p ref="p"/></div>"#,
+            r#"<div><p v-bind="x"/></div>"#,
+            r#"<div><Comp/></div>"#,
+        ] {
+            let CodegenResult { ast, .. } = compile_with_hoist_static(template);
+            assert!(ast.hoists.is_empty(), "{template}");
+        }
+    }
+
+    #[test]
+    fn hoist_inside_v_if_and_v_for() {
+        let CodegenResult { ast, code, .. } = compile_with_hoist_static(
+            r#"<div><p v-if="a"><i/>{{ a }}</p><p v-for="i in list" :key="i"><b/>{{ i }}</p></div>"#,
+        );
+
+        assert_eq!(ast.hoists.len(), 2);
+        assert!(code.contains(
+            r#"const _hoisted_1 = /*@__PURE__*/_createElementVNode("i", null, null, -1)"#
+        ));
+        assert!(code.contains(
+            r#"const _hoisted_2 = /*@__PURE__*/_createElementVNode("b", null, null, -1)"#
+        ));
+    }
+}
@@ -1 +1,9 @@
+mod cache_static;
+mod noop_directive;
+mod transform_element;
+mod transform_expression;
+mod transform_slot_outlet;
+mod v_bind;
+mod v_for;
 mod v_if;
+mod v_once;
@@ -0,0 +1,24 @@
+#[cfg(test)]
+mod compiler_noop_directive {
+    use vue_compiler_core::{
+        BaseCompileSource, CodegenResult, CompilerOptions, JSChildNode, RootCodegenNode,
+        base_compile,
+    };
+
+    #[test]
+    fn strip_v_cloak() {
+        let CodegenResult { code, ast, .. } = base_compile(
+            BaseCompileSource::String("<div v-cloak/>".to_string()),
+            CompilerOptions::default(),
+        );
+
+        let Some(RootCodegenNode::JSChild(JSChildNode::VNodeCall(node))) = ast.codegen_node else {
+            unreachable!();
+        };
+        assert!(node.directives.is_none());
+        assert!(node.props.is_none());
+        assert!(ast.directives.is_empty());
+        assert!(!code.contains("cloak"));
+        assert!(code.contains(r#"_createElementBlock("div")"#));
+    }
+}
@@ -0,0 +1,616 @@
+#[cfg(test)]
+mod compiler_transform_element {
+    use crate::test_utils::TestErrorHandlingOptions;
+    use vue_compiler_core::{
+        CompilerError, CompilerOptions, ParseMode, RootNode, VNodeCall, base_parse as parse,
+        transform, transform_element,
+    };
+
+    fn parse_with_element_transform(template: &str) -> (RootNode, VNodeCall) {
+        let (parser_options, mut transform_options, _) = CompilerOptions::default().into();
+        let mut ast = parse(template, Some(parser_options));
+
+        transform_options.node_transforms = Some(vec![transform_element]);
+        transform(&mut ast, transform_options);
+
+        let Some(vue_compiler_core::RootCodegenNode::JSChild(
+            vue_compiler_core::JSChildNode::VNodeCall(node),
+        )) = ast.codegen_node.clone()
+        else {
+            unreachable!();
+        };
+        (ast, node)
+    }
+
+    fn element_warnings(template: &str) -> Vec<CompilerError> {
+        element_warnings_in(template, None)
+    }
+
+    fn element_warnings_in(template: &str, parse_mode: Option<ParseMode>) -> Vec<CompilerError> {
+        let error_handling_options = TestErrorHandlingOptions::new();
+        let mut options = CompilerOptions {
+            parse_mode,
+            ..Default::default()
+        };
+        options.global_compile_time_constants.__dev__ = true;
+        options.error_handling_options = Box::new(error_handling_options.clone());
+        let (parser_options, mut transform_options, _) = options.into();
+        let mut ast = parse(template, Some(parser_options));
+        transform_options.node_transforms = Some(vec![transform_element]);
+        transform(&mut ast, transform_options);
+        error_handling_options.warnings()
+    }
+
+    mod component_resolution {
+        use super::parse_with_element_transform;
+        use vue_compiler_core::{
+            BaseCompileSource, CallArgument, CallCallee, CodegenMode, CompilerOptions, JSChildNode,
+            ResolveComponent, ResolveDynamicComponent, Teleport, VNodeCallTag, base_compile,
+        };
+
+        #[test]
+        fn resolve_component_from_is_vue_prefix() {
+            let (root, node) = parse_with_element_transform(r#"<div is="vue:foo"/>"#);
+
+            assert!(root.helpers.contains(&ResolveComponent.to_string()));
+            assert_eq!(root.components, vec!["foo".to_string()]);
+            assert_eq!(node.tag, VNodeCallTag::String("_component_foo".to_string()));
+            assert!(node.is_component);
+            // the is attribute is consumed by the tag resolution
+            assert!(node.props.is_none());
+        }
+
+        #[test]
+        fn resolve_dynamic_component() {
+            let (root, node) = parse_with_element_transform(r#"<component :is="cmp"/>"#);
+
+            assert!(root.helpers.contains(&ResolveDynamicComponent.to_string()));
+            assert!(root.components.is_empty());
+            let VNodeCallTag::Call(call) = &node.tag else {
+                unreachable!();
+            };
+            assert_eq!(
+                call.callee,
+                CallCallee::Symbol(ResolveDynamicComponent.to_string())
+            );
+            assert!(matches!(
+                &call.arguments[..],
+                [CallArgument::JSChild(JSChildNode::Simple(exp))]
+                if exp.content == "cmp" && !exp.is_static
+            ));
+            assert!(node.is_block);
+            assert!(node.props.is_none());
+        }
+
+        #[test]
+        fn resolve_dynamic_component_with_static_is() {
+            let (_, node) = parse_with_element_transform(r#"<component is="foo"/>"#);
+
+            let VNodeCallTag::Call(call) = &node.tag else {
+                unreachable!();
+            };
+            assert!(matches!(
+                &call.arguments[..],
+                [CallArgument::JSChild(JSChildNode::Simple(exp))]
+                if exp.content == "foo" && exp.is_static
+            ));
+            assert!(node.props.is_none());
+        }
+
+        #[test]
+        fn resolve_built_in_component() {
+            let (root, node) = parse_with_element_transform(r##"<Teleport to="#app"/>"##);
+
+            assert!(root.helpers.contains(&Teleport.to_string()));
+            assert!(!root.helpers.contains(&ResolveComponent.to_string()));
+            assert!(root.components.is_empty());
+            assert_eq!(node.tag, VNodeCallTag::Symbol(Teleport.to_string()));
+            assert!(node.is_block);
+
+            let code = base_compile(
+                BaseCompileSource::String(r##"<Teleport to="#app"/>"##.to_string()),
+                CompilerOptions::default(),
+            )
+            .code;
+            assert!(code.contains("Teleport: _Teleport"));
+            assert!(code.contains(r##"_createBlock(_Teleport, { to: "#app" })"##));
+        }
+
+        #[test]
+        fn codegen_dynamic_component() {
+            let code = base_compile(
+                BaseCompileSource::String(r#"<component :is="cmp"/>"#.to_string()),
+                CompilerOptions {
+                    mode: Some(CodegenMode::Module),
+                    prefix_identifiers: Some(true),
+                    ..Default::default()
+                },
+            )
+            .code;
+            assert!(code.contains(
+                "return (_openBlock(), _createBlock(_resolveDynamicComponent(_ctx.cmp)))"
+            ));
+        }
+    }
+
+    mod keep_alive {
+        use super::{element_warnings, parse_with_element_transform};
+        use vue_compiler_core::{
+            ComponentNodeCodegenNode, ElementNode, ErrorCodes, KeepAlive, TemplateChildNode,
+            VNodeCallChildren, VNodeCallTag,
+        };
+        use vue_compiler_shared::PatchFlags;
+
+        #[test]
+        fn force_keep_alive_and_its_child_into_blocks() {
+            let (root, node) = parse_with_element_transform("<KeepAlive><Comp/></KeepAlive>");
+
+            assert!(root.helpers.contains(&KeepAlive.to_string()));
+            assert_eq!(node.tag, VNodeCallTag::Symbol(KeepAlive.to_string()));
+            assert!(node.is_block);
+            assert_eq!(node.patch_flag, Some(PatchFlags::DynamicSlots));
+            let Some(VNodeCallChildren::TemplateChildNodeList(children)) = &node.children else {
+                unreachable!();
+            };
+            assert_eq!(children.len(), 1);
+            let TemplateChildNode::Element(ElementNode::Component(child)) = &children[0] else {
+                unreachable!();
+            };
+            let Some(ComponentNodeCodegenNode::VNodeCall(child)) = &child.codegen_node else {
+                unreachable!();
+            };
+            assert_eq!(
+                child.tag,
+                VNodeCallTag::String("_component_Comp".to_string())
+            );
+            assert!(child.is_block);
+        }
+
+        #[test]
+        fn single_component_child_does_not_warn() {
+            assert!(element_warnings("<KeepAlive><Comp/></KeepAlive>").is_empty());
+        }
+
+        #[test]
+        fn warn_on_text_child() {
+            let warnings = element_warnings("<KeepAlive>text</KeepAlive>");
+            assert_eq!(warnings.len(), 1);
+            assert_eq!(warnings[0].code, ErrorCodes::XKeepAliveInvalidChildren);
+        }
+
+        #[test]
+        fn warn_on_multiple_children() {
+            let warnings = element_warnings("<KeepAlive><Foo/><Bar/></KeepAlive>");
+            assert_eq!(warnings.len(), 1);
+            assert_eq!(warnings[0].code, ErrorCodes::XKeepAliveInvalidChildren);
+            let loc = warnings[0].loc.as_ref().unwrap();
+            assert_eq!(loc.start.offset, 11);
+            assert_eq!(loc.end.offset, 23);
+        }
+    }
+
+    mod runtime_directives {
+        use super::parse_with_element_transform;
+        use vue_compiler_core::{
+            BaseCompileSource, CodegenNode, CompilerOptions, ExpressionNode, JSChildNode,
+            ResolveDirective, WithDirectives, base_compile,
+        };
+        use vue_compiler_shared::PatchFlags;
+
+        #[test]
+        fn directive_with_arg_and_modifiers() {
+            let (root, node) = parse_with_element_transform(r#"<div v-focus:x.m="e"/>"#);
+
+            assert!(root.helpers.contains(&ResolveDirective.to_string()));
+            assert!(root.helpers.contains(&WithDirectives.to_string()));
+            assert_eq!(root.directives, vec!["focus".to_string()]);
+            assert_eq!(node.patch_flag, Some(PatchFlags::NeedPatch));
+
+            let directives = node.directives.unwrap();
+            assert_eq!(directives.elements.len(), 1);
+            let CodegenNode::Array(dir_args) = &directives.elements[0] else {
+                unreachable!();
+            };
+            assert_eq!(dir_args.elements.len(), 4);
+            assert!(matches!(
+                &dir_args.elements[0],
+                CodegenNode::Simple(node) if node.content == "_directive_focus" && !node.is_static
+            ));
+            assert!(matches!(
+                &dir_args.elements[1],
+                CodegenNode::Simple(node) if node.content == "e" && !node.is_static
+            ));
+            assert!(matches!(
+                &dir_args.elements[2],
+                CodegenNode::Simple(node) if node.content == "x" && node.is_static
+            ));
+            let CodegenNode::Object(modifiers) = &dir_args.elements[3] else {
+                unreachable!();
+            };
+            assert_eq!(modifiers.properties.len(), 1);
+            assert!(matches!(
+                &modifiers.properties[0].key,
+                ExpressionNode::Simple(key) if key.content == "m" && key.is_static
+            ));
+            assert!(matches!(
+                &modifiers.properties[0].value,
+                JSChildNode::Simple(value) if value.content == "true" && !value.is_static
+            ));
+        }
+
+        #[test]
+        fn fill_missing_args_with_void() {
+            let (_, node) = parse_with_element_transform(r#"<div v-foo.m />"#);
+            let directives = node.directives.unwrap();
+            let CodegenNode::Array(dir_args) = &directives.elements[0] else {
+                unreachable!();
+            };
+            assert_eq!(dir_args.elements.len(), 4);
+            assert!(matches!(
+                &dir_args.elements[1],
+                CodegenNode::Simple(node) if node.content == "void 0"
+            ));
+            assert!(matches!(
+                &dir_args.elements[2],
+                CodegenNode::Simple(node) if node.content == "void 0"
+            ));
+        }
+
+        #[test]
+        fn ignore_built_in_directives() {
+            let (root, node) =
+                parse_with_element_transform(r#"<div v-show="ok" v-cloak v-once v-text="t"/>"#);
+            assert!(node.directives.is_none());
+            assert!(root.directives.is_empty());
+        }
+
+        #[test]
+        fn codegen_with_directives() {
+            let code = base_compile(
+                BaseCompileSource::String(r#"<div v-focus:x.m="e"/>"#.to_string()),
+                CompilerOptions::default(),
+            )
+            .code;
+            assert!(code.contains("const _directive_focus = _resolveDirective(\"focus\")"));
+            assert!(code.contains(
+                r#"return _withDirectives((_openBlock(), _createElementBlock("div", null, null, 512)), [
+      [
+        _directive_focus,
+        e,
+        "x",
+        { m: true }
+      ]
+    ])"#
+            ));
+        }
+    }
+
+    mod static_props {
+        use super::parse_with_element_transform;
+        use vue_compiler_core::{
+            BaseCompileSource, CompilerOptions, ExpressionNode, JSChildNode, PropsExpression,
+            attr_to_prop, base_compile, base_parse as parse,
+        };
+
+        fn static_props(node: &vue_compiler_core::VNodeCall) -> Vec<(String, String)> {
+            let Some(PropsExpression::Object(props)) = &node.props else {
+                unreachable!();
+            };
+            props
+                .properties
+                .iter()
+                .map(|prop| {
+                    let ExpressionNode::Simple(key) = &prop.key else {
+                        unreachable!();
+                    };
+                    let JSChildNode::Simple(value) = &prop.value else {
+                        unreachable!();
+                    };
+                    assert!(value.is_static);
+                    (key.content.clone(), value.content.clone())
+                })
+                .collect()
+        }
+
+        #[test]
+        fn valueless_and_quoted_attributes() {
+            let (_, node) = parse_with_element_transform(r#"<div id="x" disabled/>"#);
+            assert_eq!(
+                static_props(&node),
+                vec![
+                    ("id".to_string(), "x".to_string()),
+                    ("disabled".to_string(), String::new()),
+                ]
+            );
+
+            let code = base_compile(
+                BaseCompileSource::String(r#"<div id="x" disabled/>"#.to_string()),
+                CompilerOptions::default(),
+            )
+            .code;
+            assert!(code.contains(
+                r#"_createElementBlock("div", {
+      id: "x",
+      disabled: ""
+    })"#
+            ));
+        }
+
+        #[test]
+        fn normalize_static_class_and_style() {
+            let ast = parse(
+                r#"<div class="  a
+  b " style=" color: red;  width: 1px "/>"#,
+                None,
+            );
+            let vue_compiler_core::TemplateChildNode::Element(el) = &ast.children[0] else {
+                unreachable!();
+            };
+            let values: Vec<String> = el
+                .props()
+                .iter()
+                .map(|prop| {
+                    let vue_compiler_core::BaseElementProps::Attribute(attr) = prop else {
+                        unreachable!();
+                    };
+                    let JSChildNode::Simple(value) = attr_to_prop(attr).value else {
+                        unreachable!();
+                    };
+                    value.content
+                })
+                .collect();
+            assert_eq!(values, vec!["a b", "color: red; width: 1px"]);
+        }
+    }
+
+    mod props_order {
+        use vue_compiler_core::{
+            BaseCompileSource, CodegenResult, CompilerOptions, ExpressionNode, JSChildNode,
+            PropsExpression, RootCodegenNode, base_compile,
+        };
+
+        fn prop_keys(template: &str) -> Vec<String> {
+            let CodegenResult { ast, .. } = base_compile(
+                BaseCompileSource::String(template.to_string()),
+                CompilerOptions::default(),
+            );
+            let Some(RootCodegenNode::JSChild(JSChildNode::VNodeCall(node))) = ast.codegen_node
+            else {
+                unreachable!();
+            };
+            let Some(PropsExpression::Object(props)) = node.props else {
+                unreachable!();
+            };
+            props
+                .properties
+                .iter()
+                .map(|prop| {
+                    let ExpressionNode::Simple(key) = &prop.key else {
+                        unreachable!();
+                    };
+                    key.content.clone()
+                })
+                .collect()
+        }
+
+        #[test]
+        fn follow_source_order() {
+            assert_eq!(prop_keys(r#"<div :b="2" :a="1"/>"#), ["b", "a"]);
+            assert_eq!(
+                prop_keys(r#"<div id="x" :title="t" :key="k" ref="r" :a="1"/>"#),
+                ["id", "title", "key", "ref", "a"]
+            );
+        }
+
+        #[test]
+        fn merged_duplicates_keep_first_position() {
+            assert_eq!(
+                prop_keys(r#"<div class="a" :b="1" :class="c" :a="2"/>"#),
+                ["class", "b", "a"]
+            );
+        }
+    }
+
+    mod merge_props {
+        use super::parse_with_element_transform;
+        use vue_compiler_core::{
+            BaseCompileSource, CallArgument, CallCallee, CompilerOptions, ExpressionNode,
+            GuardReactiveProps, JSChildNode, MergeProps, NormalizeProps, PropsExpression,
+            base_compile,
+        };
+        use vue_compiler_shared::PatchFlags;
+
+        fn static_class(arg: &CallArgument) -> Option<String> {
+            let CallArgument::JSChild(JSChildNode::Object(object)) = arg else {
+                return None;
+            };
+            let [prop] = object.properties.as_slice() else {
+                return None;
+            };
+            match (&prop.key, &prop.value) {
+                (ExpressionNode::Simple(key), JSChildNode::Simple(value))
+                    if key.content == "class" =>
+                {
+                    Some(value.content.clone())
+                }
+                _ => None,
+            }
+        }
+
+        #[test]
+        fn keep_duplicate_class_around_object_v_bind() {
+            let (root, node) =
+                parse_with_element_transform(r#"<div class="a" v-bind="o" class="b"/>"#);
+            assert!(root.helpers.contains(&MergeProps.to_string()));
+            let Some(PropsExpression::Call(call)) = &node.props else {
+                unreachable!();
+            };
+            assert_eq!(call.callee, CallCallee::Symbol(MergeProps.to_string()));
+            let args = &call.arguments;
+            assert_eq!(args.len(), 3);
+            assert_eq!(static_class(&args[0]), Some("a".to_string()));
+            assert!(matches!(
+                &args[1],
+                CallArgument::JSChild(JSChildNode::Simple(exp)) if exp.content == "o"
+            ));
+            assert_eq!(static_class(&args[2]), Some("b".to_string()));
+            assert_eq!(node.patch_flag, Some(PatchFlags::FullProps));
+        }
+
+        #[test]
+        fn single_object_v_bind() {
+            let (_, node) = parse_with_element_transform(r#"<div v-bind="o"/>"#);
+            let Some(PropsExpression::Call(call)) = &node.props else {
+                unreachable!();
+            };
+            assert_eq!(call.callee, CallCallee::Symbol(NormalizeProps.to_string()));
+            let [CallArgument::JSChild(JSChildNode::Call(guard))] = call.arguments.as_slice()
+            else {
+                unreachable!();
+            };
+            assert_eq!(
+                guard.callee,
+                CallCallee::Symbol(GuardReactiveProps.to_string())
+            );
+        }
+
+        #[test]
+        fn merge_duplicate_class_into_array() {
+            let code = base_compile(
+                BaseCompileSource::String(r#"<div class="a" :class="b"/>"#.to_string()),
+                CompilerOptions::default(),
+            )
+            .code;
+            assert!(code.contains(
+                r#"_createElementBlock("div", {
+      class: _normalizeClass(["a", b])
+    }, null, 2)"#
+            ));
+        }
+
+        #[test]
+        fn merge_duplicate_style_into_array() {
+            let code = base_compile(
+                BaseCompileSource::String(r#"<div style="color: red" :style="b"/>"#.to_string()),
+                CompilerOptions::default(),
+            )
+            .code;
+            assert!(code.contains(
+                r#"_createElementBlock("div", {
+      style: _normalizeStyle(["color: red", b])
+    }, null, 4)"#
+            ));
+        }
+    }
+
+    mod refs {
+        use vue_compiler_core::{BaseCompileSource, CompilerOptions, base_compile};
+
+        fn compile(template: &str) -> String {
+            base_compile(
+                BaseCompileSource::String(template.to_string()),
+                CompilerOptions::default(),
+            )
+            .code
+        }
+
+        #[test]
+        fn ref_for_inside_v_for() {
+            let code = compile(r#"<div v-for="i in l" ref="r"/>"#);
+            assert!(code.contains(
+                r#"_createElementBlock("div", {
+        ref_for: true,
+        ref: "r"
+      }, null, 512)"#
+            ));
+        }
+
+        #[test]
+        fn no_ref_for_outside_v_for() {
+            let code = compile(r#"<div ref="r"/>"#);
+            assert!(!code.contains("ref_for"));
+            assert!(code.contains(r#"_createElementBlock("div", { ref: "r" }, null, 512)"#));
+        }
+    }
+
+    mod normalize_class_and_style {
+        use vue_compiler_core::{BaseCompileSource, CompilerOptions, base_compile};
+
+        fn compile(template: &str) -> String {
+            base_compile(
+                BaseCompileSource::String(template.to_string()),
+                CompilerOptions::default(),
+            )
+            .code
+        }
+
+        #[test]
+        fn bound_class() {
+            let code = compile(r#"<div :class="{ a: x }"/>"#);
+            assert!(code.contains("normalizeClass: _normalizeClass"));
+            assert!(code.contains("class: _normalizeClass({ a: x })"));
+            assert!(code.contains("}, null, 2)"));
+        }
+
+        #[test]
+        fn bound_style() {
+            let code = compile(r#"<div :style="{ color: c }"/>"#);
+            assert!(code.contains("normalizeStyle: _normalizeStyle"));
+            assert!(code.contains("style: _normalizeStyle({ color: c })"));
+            assert!(code.contains("}, null, 4)"));
+        }
+
+        #[test]
+        fn static_class_and_style_are_not_wrapped() {
+            let code = compile(r#"<div class="a" style="color: red"/>"#);
+            assert!(!code.contains("_normalizeClass"));
+            assert!(!code.contains("_normalizeStyle"));
+        }
+    }
+
+    mod template_placement {
+        use super::{element_warnings, element_warnings_in};
+        use vue_compiler_core::{ErrorCodes, ParseMode};
+
+        #[test]
+        fn warn_on_nested_template_without_structural_directive() {
+            let warnings = element_warnings("<div><template>x</template></div>");
+            assert_eq!(warnings.len(), 1);
+            assert_eq!(warnings[0].code, ErrorCodes::XInvalidTemplatePlacement);
+            assert_eq!(
+                warnings[0].loc.as_ref().unwrap().source,
+                "<template>x</template>"
+            );
+        }
+
+        #[test]
+        fn warn_on_root_template_outside_sfc() {
+            let warnings = element_warnings("<template>x</template>");
+            assert_eq!(warnings.len(), 1);
+            assert_eq!(warnings[0].code, ErrorCodes::XInvalidTemplatePlacement);
+        }
+
+        #[test]
+        fn sfc_root_templates_do_not_warn() {
+            assert!(element_warnings_in("<template>x</template>", Some(ParseMode::SFC)).is_empty());
+            // only the root level ones are SFC blocks
+            let warnings = element_warnings_in(
+                "<template><template>x</template></template>",
+                Some(ParseMode::SFC),
+            );
+            assert_eq!(warnings.len(), 1);
+            assert_eq!(warnings[0].code, ErrorCodes::XInvalidTemplatePlacement);
+        }
+
+        #[test]
+        fn structural_templates_do_not_warn() {
+            for template in [
+                "<div><template v-if=\"ok\">x</template></div>",
+                "<div><template v-for=\"i in list\">x</template></div>",
+                "<Comp><template #foo>x</template></Comp>",
+            ] {
+                assert!(element_warnings(template).is_empty(), "{template}");
+            }
+        }
+    }
+}
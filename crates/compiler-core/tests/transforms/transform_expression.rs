@@ -0,0 +1,205 @@
+#[cfg(test)]
+mod compiler_transform_expression {
+    use vue_compiler_core::{
+        BaseCompileSource, CodegenResult, CompilerOptions, ConstantTypes, ElementNode,
+        ExpressionNode, JSChildNode, RootCodegenNode, TemplateChildNode, VNodeCall, base_compile,
+    };
+    use vue_compiler_shared::PatchFlags;
+
+    fn compile_with_prefix(template: &str) -> (VNodeCall, ExpressionNode) {
+        let CodegenResult { ast, .. } = base_compile(
+            BaseCompileSource::String(template.to_string()),
+            CompilerOptions {
+                prefix_identifiers: Some(true),
+                ..Default::default()
+            },
+        );
+        let Some(RootCodegenNode::JSChild(JSChildNode::VNodeCall(node))) = ast.codegen_node else {
+            unreachable!();
+        };
+        let TemplateChildNode::Element(ElementNode::PlainElement(element)) = &ast.children[0]
+        else {
+            unreachable!();
+        };
+        let TemplateChildNode::Interpolation(interpolation) = &element.children[0] else {
+            unreachable!();
+        };
+        (node, interpolation.content.clone())
+    }
+
+    #[test]
+    fn constant_interpolation() {
+        let (node, content) = compile_with_prefix("<div>{{ 1 + 1 }}</div>");
+        assert!(matches!(
+            content,
+            ExpressionNode::Simple(content)
+            if content.content == "1 + 1" && content.const_type == ConstantTypes::CanStringify
+        ));
+        assert_eq!(node.patch_flag, None);
+    }
+
+    #[test]
+    fn dynamic_interpolation() {
+        let (node, content) = compile_with_prefix("<div>{{ foo }}</div>");
+        assert!(matches!(
+            content,
+            ExpressionNode::Simple(content)
+            if content.content == "_ctx.foo" && content.const_type == ConstantTypes::NotConstant
+        ));
+        assert_eq!(node.patch_flag, Some(PatchFlags::Text));
+    }
+
+    fn compile_code(template: &str) -> String {
+        base_compile(
+            BaseCompileSource::String(template.to_string()),
+            CompilerOptions {
+                prefix_identifiers: Some(true),
+                ..Default::default()
+            },
+        )
+        .code
+    }
+
+    #[test]
+    fn optional_chaining() {
+        for (template, expected) in [
+            ("{{ a?.b }}", "_toDisplayString(_ctx.a?.b)"),
+            ("{{ a?.[k] }}", "_toDisplayString(_ctx.a?.[_ctx.k])"),
+            ("{{ a?.b?.(c) }}", "_toDisplayString(_ctx.a?.b?.(_ctx.c))"),
+        ] {
+            let code = compile_code(template);
+            assert!(code.contains(expected), "{template}\n{code}");
+        }
+    }
+
+    #[test]
+    fn nullish_coalescing() {
+        for (template, expected) in [
+            ("{{ a ?? b }}", "_toDisplayString(_ctx.a ?? _ctx.b)"),
+            (
+                "{{ a?.b ?? c.d }}",
+                "_toDisplayString(_ctx.a?.b ?? _ctx.c.d)",
+            ),
+        ] {
+            let code = compile_code(template);
+            assert!(code.contains(expected), "{template}\n{code}");
+        }
+    }
+
+    #[test]
+    fn v_for_aliases() {
+        let code = compile_code(r#"<div v-for="(item, i) in list">{{ item + i + foo }}</div>"#);
+        assert!(
+            code.contains("_renderList(_ctx.list, (item, i) => {"),
+            "{code}"
+        );
+        assert!(
+            code.contains("_toDisplayString(item + i + _ctx.foo)"),
+            "{code}"
+        );
+
+        let code = compile_code(
+            r#"<div v-for="({ id, name: label = id }) in list" :id="id">{{ label + name }}</div>"#,
+        );
+        assert!(code.contains("id: id"), "{code}");
+        assert!(
+            code.contains("_toDisplayString(label + _ctx.name)"),
+            "{code}"
+        );
+
+        // aliases are only in scope within the loop
+        let code = compile_code(r#"<div><p v-for="item in list">{{ item }}</p>{{ item }}</div>"#);
+        assert!(code.contains("_toDisplayString(item)"), "{code}");
+        assert!(code.contains("_toDisplayString(_ctx.item)"), "{code}");
+    }
+
+    #[test]
+    fn regex_literals() {
+        for (template, expected) in [
+            (
+                "{{ /a+b/.test(x) }}",
+                "_toDisplayString(/a+b/.test(_ctx.x))",
+            ),
+            (
+                "{{ x.replace(/[/]y/g, z) }}",
+                "_toDisplayString(_ctx.x.replace(/[/]y/g, _ctx.z))",
+            ),
+            ("{{ !/c/.test(d) }}", "_toDisplayString(!/c/.test(_ctx.d))"),
+            // divisions
+            (
+                "{{ a / b / c }}",
+                "_toDisplayString(_ctx.a / _ctx.b / _ctx.c)",
+            ),
+            ("{{ a++ / b }}", "_toDisplayString(_ctx.a++ / _ctx.b)"),
+            ("{{ (a) / b }}", "_toDisplayString((_ctx.a) / _ctx.b)"),
+        ] {
+            let code = compile_code(template);
+            assert!(code.contains(expected), "{template}\n{code}");
+        }
+    }
+
+    #[test]
+    fn comments() {
+        for (template, expected) in [
+            (
+                "{{ a /* b c */ + d }}",
+                "_toDisplayString(_ctx.a /* b c */ + _ctx.d)",
+            ),
+            ("<div :id=\"a // b\nc\"/>", "id: _ctx.a // b\n_ctx.c"),
+        ] {
+            let code = compile_code(template);
+            assert!(code.contains(expected), "{template}\n{code}");
+        }
+    }
+
+    #[test]
+    fn function_scopes() {
+        for (template, expected) in [
+            // params are only in scope within the function body
+            (
+                "{{ [1].map(x => x)[0] + x }}",
+                "_toDisplayString([1].map(x => x)[0] + _ctx.x)",
+            ),
+            (
+                "{{ foo(x => y => x + y, x) }}",
+                "_toDisplayString(_ctx.foo(x => y => x + y, _ctx.x))",
+            ),
+            // default values of params
+            (
+                "{{ ((y = x) => y)() }}",
+                "_toDisplayString(((y = _ctx.x) => y)())",
+            ),
+            (
+                "{{ a.map((x, { b, c: [d = e] } = f) => x + b + d) }}",
+                "_toDisplayString(_ctx.a.map((x, { b, c: [d = _ctx.e] } = _ctx.f) => x + b + d))",
+            ),
+            // variable declarations
+            (
+                r#"<div :id="foo(() => { let y = 1; return y + z }) + y"/>"#,
+                "id: _ctx.foo(() => { let y = 1; return y + _ctx.z }) + _ctx.y",
+            ),
+            (
+                "{{ (() => { if (a) { b } else { c } })() }}",
+                "_toDisplayString((() => { if (_ctx.a) { _ctx.b } else { _ctx.c } })())",
+            ),
+        ] {
+            let code = compile_code(template);
+            assert!(code.contains(expected), "{template}\n{code}");
+        }
+    }
+
+    #[test]
+    fn v_for_alias_defaults() {
+        let code = compile_code(
+            r#"<div v-for="({ foo = bar, baz: [qux = quux] }) in list">{{ foo + qux + baz }}</div>"#,
+        );
+        assert!(
+            code.contains("({ foo = _ctx.bar, baz: [qux = _ctx.quux] }) => {"),
+            "{code}"
+        );
+        assert!(
+            code.contains("_toDisplayString(foo + qux + _ctx.baz)"),
+            "{code}"
+        );
+    }
+}
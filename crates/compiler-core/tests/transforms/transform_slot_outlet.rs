@@ -0,0 +1,180 @@
+#[cfg(test)]
+mod compiler_transform_slot_outlet {
+    use crate::test_utils::TestErrorHandlingOptions;
+    use vue_compiler_core::{
+        BaseCompileSource, CallArgument, CallCallee, CallExpression, CodegenResult,
+        CompilerOptions, ElementNode, ErrorCodes, ExpressionNode, JSChildNode, RenderSlot,
+        RootNode, SlotOutletNodeCodegenNode, TemplateChildNode, base_compile as compile,
+        base_parse as parse, get_base_transform_preset, transform,
+    };
+
+    fn parse_with_slot_outlet_transform(template: &str) -> RootNode {
+        let options = CompilerOptions {
+            prefix_identifiers: Some(true),
+            ..Default::default()
+        };
+        let (parser_options, mut transform_options, _) = options.into();
+        let mut ast = parse(template, Some(parser_options));
+        let (node_transforms, directive_transforms) = get_base_transform_preset(true);
+        transform_options.node_transforms = Some(node_transforms);
+        transform_options.directive_transforms = Some(directive_transforms);
+        transform(&mut ast, transform_options);
+        ast
+    }
+
+    fn render_slot_call(root: &RootNode) -> CallExpression {
+        let TemplateChildNode::Element(ElementNode::SlotOutlet(node)) = &root.children[0] else {
+            unreachable!();
+        };
+        let Some(SlotOutletNodeCodegenNode::RenderSlotCall(call)) = node.codegen_node.clone()
+        else {
+            unreachable!();
+        };
+        call
+    }
+
+    #[test]
+    fn default_slot_outlet() {
+        let ast = parse_with_slot_outlet_transform("<slot/>");
+        let call = render_slot_call(&ast);
+
+        assert!(ast.helpers.contains(&RenderSlot.to_string()));
+        assert_eq!(call.callee, CallCallee::Symbol(RenderSlot.to_string()));
+        assert_eq!(
+            call.arguments,
+            vec![
+                CallArgument::String("_ctx.$slots".to_string()),
+                CallArgument::String("\"default\"".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn statically_named_slot_outlet() {
+        let call = render_slot_call(&parse_with_slot_outlet_transform(r#"<slot name="foo" />"#));
+
+        assert_eq!(call.arguments.len(), 2);
+        assert_eq!(
+            call.arguments[1],
+            CallArgument::String("\"foo\"".to_string())
+        );
+    }
+
+    #[test]
+    fn dynamically_named_slot_outlet() {
+        let call = render_slot_call(&parse_with_slot_outlet_transform(r#"<slot :name="foo" />"#));
+
+        assert_eq!(call.arguments.len(), 2);
+        assert!(matches!(
+            &call.arguments[1],
+            CallArgument::JSChild(JSChildNode::Simple(exp)) if exp.content == "_ctx.foo"
+        ));
+    }
+
+    #[test]
+    fn slot_outlet_with_props() {
+        let call = render_slot_call(&parse_with_slot_outlet_transform(
+            r#"<slot foo="bar" :baz="qux" />"#,
+        ));
+
+        assert_eq!(call.arguments.len(), 3);
+        let CallArgument::JSChild(JSChildNode::Object(props)) = &call.arguments[2] else {
+            unreachable!();
+        };
+        assert_eq!(props.properties.len(), 2);
+    }
+
+    #[test]
+    fn slot_outlet_with_camelized_props() {
+        let call = render_slot_call(&parse_with_slot_outlet_transform(
+            r#"<slot foo-bar="x" :baz-qux="y" />"#,
+        ));
+
+        let CallArgument::JSChild(JSChildNode::Object(props)) = &call.arguments[2] else {
+            unreachable!();
+        };
+        let keys = props
+            .properties
+            .iter()
+            .filter_map(|prop| match &prop.key {
+                ExpressionNode::Simple(key) => Some(key.content.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec!["fooBar", "bazQux"]);
+    }
+
+    #[test]
+    fn slot_outlet_with_v_bind_object() {
+        let call = render_slot_call(&parse_with_slot_outlet_transform(
+            r#"<slot v-bind="obj" foo-bar="x" />"#,
+        ));
+
+        assert_eq!(call.arguments.len(), 3);
+        let CallArgument::JSChild(JSChildNode::Call(merge)) = &call.arguments[2] else {
+            unreachable!();
+        };
+        assert_eq!(merge.arguments.len(), 2);
+    }
+
+    #[test]
+    fn default_slot_outlet_with_fallback() {
+        let call = render_slot_call(&parse_with_slot_outlet_transform("<slot><div/></slot>"));
+
+        assert_eq!(call.arguments.len(), 4);
+        assert_eq!(call.arguments[2], CallArgument::String("{}".to_string()));
+        let CallArgument::JSChild(JSChildNode::Function(fallback)) = &call.arguments[3] else {
+            unreachable!();
+        };
+        assert!(fallback.params.is_none());
+        let Some(returns) = &fallback.returns else {
+            unreachable!();
+        };
+        assert!(matches!(
+            &returns[0],
+            TemplateChildNode::Element(node) if node.tag() == "div"
+        ));
+    }
+
+    #[test]
+    fn error_on_unexpected_custom_directive() {
+        let error_handling_options = TestErrorHandlingOptions::new();
+        let options = CompilerOptions {
+            error_handling_options: Box::new(error_handling_options.clone()),
+            ..Default::default()
+        };
+        compile(
+            BaseCompileSource::String("<slot v-foo />".to_string()),
+            options,
+        );
+
+        let errors = error_handling_options.errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].code,
+            ErrorCodes::XVSlotUnexpectedDirectiveOnSlotOutlet
+        );
+    }
+
+    #[test]
+    fn codegen() {
+        let CodegenResult { code, .. } = compile(
+            BaseCompileSource::String("<slot/>".to_string()),
+            CompilerOptions::default(),
+        );
+        assert!(code.contains("return _renderSlot($slots, \"default\")"));
+
+        let CodegenResult { code, .. } = compile(
+            BaseCompileSource::String(r#"<slot name="foo" :a="b"><span/></slot>"#.to_string()),
+            CompilerOptions {
+                prefix_identifiers: Some(true),
+                ..Default::default()
+            },
+        );
+        assert!(code.contains(
+            r#"return _renderSlot(_ctx.$slots, "foo", { a: _ctx.b }, () => [
+    _createElementVNode("span")
+  ])"#
+        ));
+    }
+}
@@ -0,0 +1,150 @@
+#[cfg(test)]
+mod compiler_v_bind {
+    use crate::test_utils::TestErrorHandlingOptions;
+    use vue_compiler_core::{
+        BaseCompileSource, CodegenMode, CodegenResult, CompilerError, CompilerOptions, ErrorCodes,
+        JSChildNode, RootCodegenNode, base_compile,
+    };
+
+    fn compile_with_warnings(
+        template: &str,
+        dev: bool,
+        compat: bool,
+    ) -> (CodegenResult, Vec<CompilerError>) {
+        let error_handling_options = TestErrorHandlingOptions::new();
+        let mut options = CompilerOptions {
+            compat: Some(compat),
+            ..Default::default()
+        };
+        options.global_compile_time_constants.__dev__ = dev;
+        options.error_handling_options = Box::new(error_handling_options.clone());
+        let result = base_compile(BaseCompileSource::String(template.to_string()), options);
+        (result, error_handling_options.warnings())
+    }
+
+    fn compile_warnings(template: &str, dev: bool) -> Vec<CompilerError> {
+        compile_with_warnings(template, dev, false).1
+    }
+
+    #[test]
+    fn sync_modifier() {
+        let warnings = compile_warnings(r#"<Comp :title.sync="t"/>"#, true);
+
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, ErrorCodes::XVBindSyncModifier);
+        let Some(loc) = &warnings[0].loc else {
+            unreachable!();
+        };
+        assert_eq!(loc.source, r#":title.sync="t""#);
+    }
+
+    #[test]
+    fn sync_modifier_in_prod() {
+        let warnings = compile_warnings(r#"<Comp :title.sync="t"/>"#, false);
+
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn without_sync_modifier() {
+        let warnings = compile_warnings(r#"<Comp :title.prop="t"/>"#, true);
+
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn sync_modifier_compat() {
+        let (CodegenResult { code, .. }, warnings) =
+            compile_with_warnings(r#"<Comp :x.sync="y"/>"#, true, true);
+
+        assert!(warnings.is_empty());
+        assert!(code.contains(r#"x: y,"#));
+        assert!(code.contains(r#""onUpdate:x": $event => ((y) = $event)"#));
+    }
+
+    #[test]
+    fn sync_modifier_compat_with_dynamic_arg() {
+        let (CodegenResult { code, .. }, _) =
+            compile_with_warnings(r#"<Comp :[x].sync="y"/>"#, false, true);
+
+        assert!(code.contains(r#"["onUpdate:" + x]: $event => ((y) = $event)"#));
+    }
+
+    fn compile_with_errors(template: &str) -> (CodegenResult, Vec<CompilerError>) {
+        let error_handling_options = TestErrorHandlingOptions::new();
+        let options = CompilerOptions {
+            prefix_identifiers: Some(true),
+            error_handling_options: Box::new(error_handling_options.clone()),
+            ..Default::default()
+        };
+        let result = base_compile(BaseCompileSource::String(template.to_string()), options);
+        (result, error_handling_options.errors())
+    }
+
+    #[test]
+    fn boolean_attribute() {
+        // the bound value is passed as is, the runtime coerces it
+        let (CodegenResult { code, .. }, _) =
+            compile_with_warnings(r#"<input :disabled="x"/>"#, false, false);
+        assert!(code.contains(r#"_createElementBlock("input", { disabled: x })"#));
+
+        let (CodegenResult { code, ast, .. }, _) =
+            compile_with_warnings("<input disabled/>", false, false);
+        assert!(code.contains(r#"_createElementBlock("input", { disabled: "" })"#));
+        let Some(RootCodegenNode::JSChild(JSChildNode::VNodeCall(node))) = ast.codegen_node else {
+            unreachable!();
+        };
+        assert_eq!(node.patch_flag, None);
+    }
+
+    #[test]
+    fn same_name_shorthand() {
+        let (CodegenResult { code, .. }, errors) =
+            compile_with_errors("<input :disabled :aria-label v-bind:id/>");
+
+        assert!(errors.is_empty());
+        assert!(code.contains("disabled: _ctx.disabled,"));
+        assert!(code.contains(r#""aria-label": _ctx.ariaLabel,"#));
+        assert!(code.contains("id: _ctx.id"));
+    }
+
+    #[test]
+    fn same_name_shorthand_modes() {
+        let compile = |mode, prefix_identifiers| {
+            base_compile(
+                BaseCompileSource::String("<div :count/>".to_string()),
+                CompilerOptions {
+                    mode: Some(mode),
+                    prefix_identifiers: Some(prefix_identifiers),
+                    ..Default::default()
+                },
+            )
+            .code
+        };
+
+        let code = compile(CodegenMode::Module, true);
+        assert!(code.contains(r#"_createElementBlock("div", { count: _ctx.count })"#));
+        // resolved through `with (_ctx)` without prefixing
+        let code = compile(CodegenMode::Function, false);
+        assert!(code.contains(r#"_createElementBlock("div", { count: count })"#));
+    }
+
+    #[test]
+    fn same_name_shorthand_with_dynamic_arg() {
+        let (CodegenResult { code, .. }, errors) = compile_with_errors("<div :[x]/>");
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code, ErrorCodes::XVBindInvalidSameNameArgument);
+        let Some(loc) = &errors[0].loc else {
+            unreachable!();
+        };
+        assert_eq!(loc.source, "[x]");
+        assert!(code.contains(r#"{ [_ctx.x]: "" }"#));
+
+        // prefixed member expressions are compound arguments
+        let (CodegenResult { code, .. }, errors) = compile_with_errors("<div :[a.b]/>");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code, ErrorCodes::XVBindInvalidSameNameArgument);
+        assert!(code.contains(r#"{ [_ctx.a.b]: "" }"#), "{code}");
+    }
+}
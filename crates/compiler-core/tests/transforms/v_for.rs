@@ -0,0 +1,412 @@
+#[cfg(test)]
+mod compiler_v_for {
+    use vue_compiler_core::{
+        CompilerOptions, ForNode, RootNode, TemplateChildNode, base_parse as parse, transform,
+        transform_element, transform_for, transform_if,
+    };
+
+    struct ForTransformResult {
+        root: RootNode,
+        node: ForNode,
+    }
+
+    fn transform_with_for(mut ast: RootNode, prefix_identifiers: bool) -> ForTransformResult {
+        let (_, mut transform_options, _) = CompilerOptions::default().into();
+        transform_options.prefix_identifiers = Some(prefix_identifiers);
+        transform_options.node_transforms =
+            Some(vec![transform_if, transform_for, transform_element]);
+        transform(&mut ast, transform_options);
+
+        let TemplateChildNode::For(node) = ast.children[0].clone() else {
+            unreachable!();
+        };
+        ForTransformResult { root: ast, node }
+    }
+
+    fn parse_with_for_transform(template: &str, prefix_identifiers: bool) -> ForTransformResult {
+        transform_with_for(parse(template, None), prefix_identifiers)
+    }
+
+    mod prefix_identifiers {
+        use super::{ForTransformResult, parse_with_for_transform, transform_with_for};
+        use vue_compiler_core::{
+            BaseElementProps, CompoundExpressionNodeChild, ExpressionNode, TemplateChildNode,
+            base_parse as parse,
+        };
+
+        fn content(exp: &Option<ExpressionNode>) -> Option<&str> {
+            match exp {
+                Some(ExpressionNode::Simple(exp)) => Some(exp.content.as_str()),
+                _ => None,
+            }
+        }
+
+        #[test]
+        fn prefix_source_and_keep_aliases_bare() {
+            let ForTransformResult { node, .. } =
+                parse_with_for_transform(r#"<div v-for="item in items"/>"#, true);
+
+            assert!(node.parse_result.finalized);
+            assert_eq!(content(&Some(node.source.clone())), Some("_ctx.items"));
+            assert_eq!(content(&node.value_alias), Some("item"));
+            assert_eq!(content(&Some(node.parse_result.source)), Some("_ctx.items"));
+            assert_eq!(content(&node.parse_result.value), Some("item"));
+        }
+
+        #[test]
+        fn prefix_complex_source() {
+            let ForTransformResult { node, .. } = parse_with_for_transform(
+                r#"<div v-for="item in list.filter(x => x.ok && max > x.n).slice(0, { count }.count)"/>"#,
+                true,
+            );
+
+            let ExpressionNode::Compound(source) = node.source else {
+                panic!("expected a compound expression");
+            };
+            let source: String = source
+                .children
+                .iter()
+                .map(|child| match child {
+                    CompoundExpressionNodeChild::Simple(exp) => exp.content.clone(),
+                    CompoundExpressionNodeChild::String(s) => s.clone(),
+                    _ => unreachable!(),
+                })
+                .collect();
+            assert_eq!(
+                source,
+                "_ctx.list.filter(x => x.ok && _ctx.max > x.n).slice(0, { count: _ctx.count }.count)"
+            );
+            assert_eq!(content(&node.value_alias), Some("item"));
+        }
+
+        #[test]
+        fn no_prefix_without_option() {
+            let ForTransformResult { node, .. } =
+                parse_with_for_transform(r#"<div v-for="item in items"/>"#, false);
+
+            assert!(node.parse_result.finalized);
+            assert_eq!(content(&Some(node.source)), Some("items"));
+            assert_eq!(content(&node.value_alias), Some("item"));
+        }
+
+        #[test]
+        fn finalize_only_once() {
+            let mut ast = parse(r#"<div v-for="item in items"/>"#, None);
+            // simulate a parse result finalized by an earlier pass
+            let TemplateChildNode::Element(el) = &mut ast.children[0] else {
+                unreachable!();
+            };
+            let Some(BaseElementProps::Directive(dir)) = el.props_mut().first_mut() else {
+                unreachable!();
+            };
+            let Some(parse_result) = &mut dir.for_parse_result else {
+                unreachable!();
+            };
+            let ExpressionNode::Simple(source) = &mut parse_result.source else {
+                unreachable!();
+            };
+            source.content = "_ctx.items".to_string();
+            parse_result.finalized = true;
+
+            let ForTransformResult { root, node } = transform_with_for(ast, true);
+            assert_eq!(content(&Some(node.source)), Some("_ctx.items"));
+            assert_eq!(content(&node.value_alias), Some("item"));
+            assert_eq!(root.children.len(), 1);
+        }
+    }
+
+    mod codegen {
+        use super::{ForTransformResult, parse_with_for_transform};
+        use vue_compiler_core::{
+            BaseCompileSource, BlockCodegenNode, CodegenMode, CodegenResult, CompilerOptions,
+            CompoundExpressionNodeChild, CreateElementVNode, ForNodeCodegenNode,
+            ForRenderListArgument, Fragment, JSChildNode, PropsExpression, TemplateChildNode,
+            VNodeCallChildren, VNodeCallTag, base_compile,
+        };
+        use vue_compiler_shared::PatchFlags;
+
+        #[test]
+        fn disable_tracking() {
+            let ForTransformResult { node, .. } =
+                parse_with_for_transform(r#"<div v-for="i in l"/>"#, false);
+            let Some(ForNodeCodegenNode::ForCodegen(codegen_node)) = node.codegen_node else {
+                unreachable!();
+            };
+            assert!(codegen_node.disable_tracking);
+
+            let CodegenResult { code, .. } = base_compile(
+                BaseCompileSource::String(r#"<div v-for="i in l"/>"#.to_string()),
+                Default::default(),
+            );
+            assert!(code.contains(
+                "(_openBlock(true), _createElementBlock(_Fragment, null, _renderList(l, (i) => {"
+            ));
+        }
+
+        #[test]
+        fn component_v_for() {
+            let CodegenResult { code, .. } = base_compile(
+                BaseCompileSource::String(r#"<Comp v-for="i in l" :key="i"/>"#.to_string()),
+                Default::default(),
+            );
+            assert!(code.contains("createBlock: _createBlock"));
+            assert!(
+                code.contains("return (_openBlock(), _createBlock(_component_Comp, { key: i }))")
+            );
+        }
+
+        #[test]
+        fn numeric_source() {
+            let CodegenResult { code, .. } = base_compile(
+                BaseCompileSource::String(r#"<li v-for="n in 10">{{ n }}</li>"#.to_string()),
+                Default::default(),
+            );
+            assert!(code.contains("_renderList(10, (n) => {"));
+
+            // the source is a literal, the alias is not prefixed in the loop
+            let CodegenResult { code, .. } = base_compile(
+                BaseCompileSource::String(r#"<li v-for="n in 10">{{ n }}</li>"#.to_string()),
+                CompilerOptions {
+                    mode: Some(CodegenMode::Module),
+                    prefix_identifiers: Some(true),
+                    ..Default::default()
+                },
+            );
+            assert!(code.contains("_renderList(10, (n) => {"), "{code}");
+            assert!(code.contains("_toDisplayString(n)"), "{code}");
+        }
+
+        #[test]
+        fn stable_fragment_child_is_not_a_block() {
+            // a constant source is a stable fragment, whose keyed child is turned
+            // from a block back into a plain vnode
+            let CodegenResult { code, ast, .. } = base_compile(
+                BaseCompileSource::String(r#"<ul><li v-for="n in 10" :key="n"/></ul>"#.to_string()),
+                CompilerOptions {
+                    prefix_identifiers: Some(true),
+                    ..Default::default()
+                },
+            );
+            assert!(code.contains(
+                "(_openBlock(), _createElementBlock(_Fragment, null, _renderList(10, (n) => {"
+            ));
+            assert!(code.contains(r#"return _createElementVNode("li", {"#));
+            assert!(ast.helpers.contains(&CreateElementVNode.to_string()));
+        }
+
+        #[test]
+        fn template_v_for_with_multiple_children() {
+            let ForTransformResult { node, .. } = parse_with_for_transform(
+                r#"<template v-for="item in items" :key="item.id"><a/><b/></template>"#,
+                true,
+            );
+
+            let Some(ForNodeCodegenNode::ForCodegen(codegen_node)) = &node.codegen_node else {
+                unreachable!();
+            };
+            let Some(ForRenderListArgument::ForIterator(iterator)) =
+                codegen_node.children.arguments.last()
+            else {
+                unreachable!();
+            };
+            // each loop renders a keyed fragment of the template's children
+            let Some(BlockCodegenNode::VNodeCall(child_block)) = &iterator.returns else {
+                unreachable!();
+            };
+            assert_eq!(child_block.tag, VNodeCallTag::Symbol(Fragment.to_string()));
+            assert!(child_block.is_block);
+            assert_eq!(child_block.patch_flag, Some(PatchFlags::StableFragment));
+            let Some(PropsExpression::Object(props)) = &child_block.props else {
+                unreachable!();
+            };
+            // the key is processed with the alias in scope
+            let JSChildNode::Compound(key) = &props.properties[0].value else {
+                unreachable!();
+            };
+            assert!(matches!(
+                key.children.as_slice(),
+                [CompoundExpressionNodeChild::Simple(item), CompoundExpressionNodeChild::String(rest)]
+                    if item.content == "item" && rest == ".id"
+            ));
+            let Some(VNodeCallChildren::TemplateChildNodeList(children)) = &child_block.children
+            else {
+                unreachable!();
+            };
+            assert_eq!(children.len(), 2);
+            assert!(matches!(&children[0], TemplateChildNode::Element(node) if node.tag() == "a"));
+            assert!(matches!(&children[1], TemplateChildNode::Element(node) if node.tag() == "b"));
+        }
+    }
+
+    mod codegen_key {
+        use vue_compiler_core::{BaseCompileSource, CodegenResult, CompilerOptions, base_compile};
+
+        fn compile(template: &str) -> String {
+            let CodegenResult { code, .. } = base_compile(
+                BaseCompileSource::String(template.to_string()),
+                CompilerOptions {
+                    prefix_identifiers: Some(true),
+                    ..Default::default()
+                },
+            );
+            code
+        }
+
+        #[test]
+        fn template_v_for_key_injection_with_single_child() {
+            let code = compile(r#"<template v-for="i in l" :key="i"><span/></template>"#);
+            assert!(
+                code.contains(r#"return (_openBlock(), _createElementBlock("span", { key: i }))"#),
+                "{code}"
+            );
+        }
+
+        #[test]
+        fn template_v_for_key_is_merged_with_child_props() {
+            let code = compile(r#"<template v-for="i in l" :key="i"><span class="a"/></template>"#);
+            assert!(
+                code.contains(
+                    "_createElementBlock(\"span\", {\n      key: i,\n      class: \"a\"\n    })"
+                ),
+                "{code}"
+            );
+
+            let code =
+                compile(r#"<template v-for="i in l" :key="i"><span v-bind="o"/></template>"#);
+            assert!(
+                code.contains("_mergeProps({\n      key: i,\n      ref_for: true\n    }, _ctx.o)"),
+                "{code}"
+            );
+        }
+
+        #[test]
+        fn slot_outlet_v_for() {
+            let code = compile(r#"<slot v-for="i in l" :key="i"/>"#);
+            assert!(
+                code.contains(r#"return _renderSlot(_ctx.$slots, "default", { key: i })"#),
+                "{code}"
+            );
+
+            let code = compile(
+                r#"<template v-for="i in l" :key="i"><slot name="a"><div>{{ i }}</div></slot></template>"#,
+            );
+            assert!(
+                code.contains(r#"return _renderSlot(_ctx.$slots, "a", { key: i }, () => ["#),
+                "{code}"
+            );
+        }
+
+        #[test]
+        fn template_v_for_key_is_prefixed() {
+            let code = compile(r#"<template v-for="i in l" :key="i + foo"><span/></template>"#);
+            assert!(code.contains("key: i + _ctx.foo\n"), "{code}");
+        }
+    }
+
+    mod errors {
+        use crate::test_utils::TestErrorHandlingOptions;
+        use vue_compiler_core::{
+            BaseCompileSource, CodegenResult, CompilerError, CompilerOptions, ErrorCodes,
+            base_compile,
+        };
+
+        fn compile_with_errors(template: &str) -> (CodegenResult, Vec<CompilerError>) {
+            let error_handling_options = TestErrorHandlingOptions::new();
+            let options = CompilerOptions {
+                error_handling_options: Box::new(error_handling_options.clone()),
+                ..Default::default()
+            };
+            let result = base_compile(BaseCompileSource::String(template.to_string()), options);
+            (result, error_handling_options.errors())
+        }
+
+        #[test]
+        fn missing_expression() {
+            let (CodegenResult { code, .. }, errors) = compile_with_errors("<div v-for/>");
+
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].code, ErrorCodes::XVForNoExpression);
+            assert!(!code.contains("renderList"));
+        }
+
+        #[test]
+        fn malformed_expression() {
+            let (CodegenResult { code, .. }, errors) = compile_with_errors(r#"<div v-for="foo"/>"#);
+
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].code, ErrorCodes::XVForMalformedExpression);
+            let Some(loc) = &errors[0].loc else {
+                unreachable!();
+            };
+            assert_eq!(loc.source, "foo");
+            // the element is rendered without the loop
+            assert!(!code.contains("renderList"));
+            assert!(code.contains(r#"_createElementBlock("div")"#));
+        }
+    }
+
+    mod fragment_flags {
+        use crate::test_utils::TestErrorHandlingOptions;
+        use vue_compiler_core::{
+            CompilerError, CompilerOptions, ErrorCodes, ForNodeCodegenNode, TemplateChildNode,
+            base_parse as parse, transform, transform_element, transform_for,
+        };
+        use vue_compiler_shared::PatchFlags;
+
+        fn fragment_flag(template: &str) -> (PatchFlags, Vec<CompilerError>) {
+            let error_handling_options = TestErrorHandlingOptions::new();
+            let mut options = CompilerOptions {
+                error_handling_options: Box::new(error_handling_options.clone()),
+                ..Default::default()
+            };
+            options.global_compile_time_constants.__dev__ = true;
+            let (parser_options, mut transform_options, _) = options.split();
+            transform_options.prefix_identifiers = Some(true);
+            transform_options.node_transforms = Some(vec![transform_for, transform_element]);
+            let mut ast = parse(template, Some(parser_options));
+            transform(&mut ast, transform_options);
+
+            let TemplateChildNode::For(node) = &ast.children[0] else {
+                unreachable!();
+            };
+            let Some(ForNodeCodegenNode::ForCodegen(codegen_node)) = &node.codegen_node else {
+                unreachable!();
+            };
+            (codegen_node.patch_flag, error_handling_options.warnings())
+        }
+
+        #[test]
+        fn keyed_fragment() {
+            let (flag, warnings) = fragment_flag(r#"<div v-for="i in l" :key="i"/>"#);
+            assert_eq!(flag, PatchFlags::KeyedFragment);
+            assert!(warnings.is_empty());
+        }
+
+        #[test]
+        fn unkeyed_fragment_warns() {
+            let (flag, warnings) = fragment_flag(r#"<div v-for="i in l"/>"#);
+            assert_eq!(flag, PatchFlags::UnkeyedFragment);
+            assert_eq!(warnings.len(), 1);
+            assert_eq!(warnings[0].code, ErrorCodes::XVForNoKey);
+        }
+
+        #[test]
+        fn unkeyed_component_fragment_warns() {
+            let (flag, warnings) = fragment_flag(r#"<Comp v-for="i in l"/>"#);
+            assert_eq!(flag, PatchFlags::UnkeyedFragment);
+            assert_eq!(warnings.len(), 1);
+            assert_eq!(warnings[0].code, ErrorCodes::XVForComponentNoKey);
+
+            let (flag, warnings) = fragment_flag(r#"<Comp v-for="i in l" :key="i"/>"#);
+            assert_eq!(flag, PatchFlags::KeyedFragment);
+            assert!(warnings.is_empty());
+        }
+
+        #[test]
+        fn stable_fragment_does_not_warn() {
+            let (flag, warnings) = fragment_flag(r#"<div v-for="i in 3"/>"#);
+            assert_eq!(flag, PatchFlags::StableFragment);
+            assert!(warnings.is_empty());
+        }
+    }
+}
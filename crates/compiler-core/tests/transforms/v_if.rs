@@ -252,7 +252,10 @@ mod compiler_v_if {
     mod codegen {
         use super::{IfTransformResult, parse_with_if_transform};
         use insta::assert_snapshot;
-        use vue_compiler_core::generate;
+        use vue_compiler_core::{
+            BaseCompileSource, CompilerOptions, base_compile, base_parse as parse, generate,
+            transform, transform_element, transform_if,
+        };
 
         #[test]
         fn basic_v_if() {
@@ -260,5 +263,197 @@ mod compiler_v_if {
                 parse_with_if_transform("<div v-if=\"ok\"/>", None, None);
             assert_snapshot!(generate(root, Default::default()).code);
         }
+
+        #[test]
+        fn template_v_if() {
+            let IfTransformResult { root, .. } =
+                parse_with_if_transform(r#"<template v-if="x"><a/><b/></template>"#, None, None);
+            assert_snapshot!(generate(root, Default::default()).code);
+        }
+
+        #[test]
+        fn v_if_v_else_if_v_else_chain() {
+            let IfTransformResult { root, .. } = parse_with_if_transform(
+                r#"<div v-if="a"/><p v-else-if="b"/><span v-else-if="c"/><i v-else/>"#,
+                None,
+                None,
+            );
+            assert_snapshot!(generate(root, Default::default()).code);
+        }
+
+        #[test]
+        fn comment_fallback() {
+            for (dev, fallback) in [
+                (true, r#": _createCommentVNode("v-if", true)"#),
+                (false, r#": _createCommentVNode("", true)"#),
+            ] {
+                let mut options = CompilerOptions::default();
+                options.global_compile_time_constants.__dev__ = dev;
+                let code = base_compile(
+                    BaseCompileSource::String(r#"<div v-if="a"/>"#.to_string()),
+                    options,
+                )
+                .code;
+                assert!(code.contains(fallback), "{code}");
+                assert!(code.contains("createCommentVNode: _createCommentVNode"));
+            }
+        }
+
+        #[test]
+        fn prefixed_conditions() {
+            let code = base_compile(
+                BaseCompileSource::String(
+                    r#"<div v-if="ok"/><p v-else-if="a.b && c"/><i v-else/>"#.to_string(),
+                ),
+                CompilerOptions {
+                    prefix_identifiers: Some(true),
+                    ..Default::default()
+                },
+            )
+            .code;
+            assert!(code.contains("return (_ctx.ok)"), "{code}");
+            assert!(code.contains(": (_ctx.a.b && _ctx.c)"), "{code}");
+        }
+
+        #[test]
+        fn else_branches_are_prefixed_once() {
+            let code = base_compile(
+                BaseCompileSource::String(
+                    r#"<div v-if="ok" :x="y"/><p v-else-if="a" :x="y"/><i v-else :x="y"/>"#
+                        .to_string(),
+                ),
+                CompilerOptions {
+                    prefix_identifiers: Some(true),
+                    ..Default::default()
+                },
+            )
+            .code;
+            assert_eq!(code.matches("x: _ctx.y").count(), 3, "{code}");
+            assert!(!code.contains("_ctx._ctx"), "{code}");
+        }
+
+        #[test]
+        fn key_injection_with_props() {
+            let compile = |template: &str| {
+                base_compile(
+                    BaseCompileSource::String(template.to_string()),
+                    CompilerOptions {
+                        prefix_identifiers: Some(true),
+                        ..Default::default()
+                    },
+                )
+                .code
+            };
+
+            let code = compile(r#"<div v-if="ok" id="foo"/>"#);
+            assert!(code.contains("key: 0,\n        id: \"foo\""), "{code}");
+
+            // the user provided key is kept
+            let code = compile(r#"<div v-if="ok" key="bar"/>"#);
+            assert!(code.contains("{ key: \"bar\" }"), "{code}");
+
+            let code = compile(r#"<div v-if="ok" v-bind="obj"/>"#);
+            assert!(
+                code.contains("_normalizeProps(_mergeProps({ key: 0 }, _ctx.obj))"),
+                "{code}"
+            );
+            assert!(!code.contains("guardReactiveProps"), "{code}");
+        }
+
+        #[test]
+        fn slot_outlet_v_if() {
+            let code = base_compile(
+                BaseCompileSource::String(
+                    r#"<slot v-if="ok"/><template v-else><slot name="a"/></template>"#.to_string(),
+                ),
+                Default::default(),
+            )
+            .code;
+            assert!(
+                code.contains(r#"? _renderSlot($slots, "default", { key: 0 })"#),
+                "{code}"
+            );
+            assert!(
+                code.contains(r#": _renderSlot($slots, "a", { key: 1 })"#),
+                "{code}"
+            );
+        }
+
+        #[test]
+        fn v_if_chain_inside_element() {
+            let mut ast = parse(
+                r#"<div><div v-if="a"/><p v-else-if="b"/><span v-else-if="c"/><i v-else/><b/></div>"#,
+                None,
+            );
+            let (_, mut transform_options, _) = CompilerOptions::default().into();
+            transform_options.node_transforms = Some(vec![transform_if, transform_element]);
+            transform(&mut ast, transform_options);
+            // the indentation is restored after the chain
+            assert_snapshot!(generate(ast, Default::default()).code);
+        }
+    }
+
+    mod with_v_for {
+        use crate::test_utils::TestErrorHandlingOptions;
+        use vue_compiler_core::{
+            CompilerOptions, ErrorCodes, TemplateChildNode, base_parse as parse, transform,
+            transform_element, transform_for, transform_if,
+        };
+
+        #[test]
+        fn v_if_takes_precedence_over_v_for() {
+            let error_handling_options = TestErrorHandlingOptions::new();
+            let mut options = CompilerOptions::default();
+            options.global_compile_time_constants.__dev__ = true;
+            options.error_handling_options = Box::new(error_handling_options.clone());
+            let (parser_options, mut transform_options, _) = options.into();
+            let mut ast = parse(
+                r#"<li v-for="i in list" :key="i" v-if="i.ok"/>"#,
+                Some(parser_options),
+            );
+            transform_options.node_transforms =
+                Some(vec![transform_if, transform_for, transform_element]);
+            transform(&mut ast, transform_options);
+
+            let warnings = error_handling_options.warnings();
+            assert_eq!(warnings.len(), 1);
+            assert_eq!(warnings[0].code, ErrorCodes::XVIfVForPrecedence);
+
+            // the v-if wraps the v-for, so its condition is evaluated outside the loop
+            assert_eq!(ast.children.len(), 1);
+            let TemplateChildNode::If(node) = &ast.children[0] else {
+                unreachable!();
+            };
+            assert_eq!(node.branches.len(), 1);
+            let branch = &node.branches[0];
+            assert_eq!(branch.children.len(), 1);
+            let TemplateChildNode::For(node) = &branch.children[0] else {
+                unreachable!();
+            };
+            assert!(matches!(
+                &node.children[0],
+                // only the key is left on the element
+                TemplateChildNode::Element(node) if node.tag() == "li" && node.props().len() == 1
+            ));
+        }
+
+        #[test]
+        fn no_warning_in_prod() {
+            let error_handling_options = TestErrorHandlingOptions::new();
+            let options = CompilerOptions {
+                error_handling_options: Box::new(error_handling_options.clone()),
+                ..Default::default()
+            };
+            let (parser_options, mut transform_options, _) = options.into();
+            let mut ast = parse(
+                r#"<li v-for="i in list" :key="i" v-if="i.ok"/>"#,
+                Some(parser_options),
+            );
+            transform_options.node_transforms =
+                Some(vec![transform_if, transform_for, transform_element]);
+            transform(&mut ast, transform_options);
+
+            assert!(error_handling_options.warnings().is_empty());
+        }
     }
 }
@@ -0,0 +1,127 @@
+#[cfg(test)]
+mod compiler_v_once {
+    use vue_compiler_core::{
+        BaseCompileSource, CodegenMode, CompilerOptions, JSChildNode, RootCodegenNode,
+        SetBlockTracking, base_compile, base_parse as parse, transform, transform_element,
+        transform_once,
+    };
+
+    #[test]
+    fn as_root_node() {
+        let mut ast = parse("<div v-once/>", None);
+        let (_, mut transform_options, _) = CompilerOptions::default().into();
+        transform_options.node_transforms = Some(vec![transform_once, transform_element]);
+        transform(&mut ast, transform_options);
+
+        assert_eq!(ast.cached.len(), 1);
+        assert!(ast.helpers.contains(&SetBlockTracking.to_string()));
+        let Some(RootCodegenNode::JSChild(JSChildNode::Cache(cache))) = &ast.codegen_node else {
+            unreachable!();
+        };
+        assert_eq!(cache.index, 0);
+        assert!(cache.in_v_once);
+        // cached vnodes are never blocks
+        let JSChildNode::VNodeCall(vnode_call) = &cache.value else {
+            unreachable!();
+        };
+        assert!(!vnode_call.is_block);
+    }
+
+    #[test]
+    fn codegen_as_root_node() {
+        let code = base_compile(
+            BaseCompileSource::String("<div v-once/>".to_string()),
+            CompilerOptions {
+                mode: Some(CodegenMode::Module),
+                ..Default::default()
+            },
+        )
+        .code;
+        assert!(code.contains(
+            r#"  return _cache[0] || (
+    _setBlockTracking(-1, true),
+    (_cache[0] = _createElementVNode("div")).cacheIndex = 0,
+    _setBlockTracking(1),
+    _cache[0]
+  )"#
+        ));
+    }
+
+    #[test]
+    fn on_nested_element() {
+        let code = base_compile(
+            BaseCompileSource::String("<div><span v-once/></div>".to_string()),
+            CompilerOptions::default(),
+        )
+        .code;
+        assert!(code.contains(r#"_createElementBlock("div", null, ["#));
+        assert!(code.contains(r#"(_cache[0] = _createElementVNode("span")).cacheIndex = 0"#));
+    }
+
+    #[test]
+    fn on_slot_outlet() {
+        let code = base_compile(
+            BaseCompileSource::String("<div><slot v-once/></div>".to_string()),
+            CompilerOptions::default(),
+        )
+        .code;
+        assert!(code.contains(r#"(_cache[0] = _renderSlot($slots, "default")).cacheIndex = 0"#));
+    }
+
+    #[test]
+    fn with_v_for() {
+        let code = base_compile(
+            BaseCompileSource::String(r#"<div v-for="i in list" v-once/>"#.to_string()),
+            CompilerOptions::default(),
+        )
+        .code;
+        // the whole list is cached, not each item
+        assert!(code.contains(
+            r#"return [...(_cache[0] || (
+      _setBlockTracking(-1, true),
+      (_cache[0] = (_openBlock(true), _createElementBlock(_Fragment, null, _renderList(list, (i) => {
+        return (_openBlock(), _createElementBlock("div"))
+      }), 256))).cacheIndex = 0,"#
+        ));
+    }
+
+    #[test]
+    fn with_v_for_renders_each_item() {
+        let code = base_compile(
+            BaseCompileSource::String(
+                r#"<div><li v-for="i in l" :key="i" v-once>{{ i }}</li><p v-once/></div>"#
+                    .to_string(),
+            ),
+            CompilerOptions::default(),
+        )
+        .code;
+        // every item renders its own `i` inside the one cached list
+        assert!(code.contains(
+            r#"(_cache[0] = (_openBlock(true), _createElementBlock(_Fragment, null, _renderList(l, (i) => {
+          return (_openBlock(), _createElementBlock("li", { key: i }, [
+            _createTextVNode(_toDisplayString(i), 1)
+          ]))
+        }), 128))).cacheIndex = 0,"#
+        ));
+        assert_eq!(code.matches("_cache[0] = ").count(), 1);
+        // the list takes the first slot, its sibling the next one
+        assert!(code.contains(r#"(_cache[1] = _createElementVNode("p")).cacheIndex = 1"#));
+    }
+
+    #[test]
+    fn with_template_v_for_spreads_cached_fragment() {
+        let code = base_compile(
+            BaseCompileSource::String(
+                r#"<template v-for="i in list" v-once><span>{{ i }}</span></template>"#
+                    .to_string(),
+            ),
+            CompilerOptions::default(),
+        )
+        .code;
+        assert!(code.contains(r#"return [...(_cache[0] || ("#));
+        assert!(code.contains(
+            r#"      _cache[0]
+    ))]"#
+        ));
+    }
+}
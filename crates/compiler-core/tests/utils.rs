@@ -0,0 +1,31 @@
+#[cfg(test)]
+mod utils {
+    use vue_compiler_core::{AssetType, is_simple_identifier, to_valid_asset_id};
+
+    #[test]
+    fn simple_identifier() {
+        assert!(is_simple_identifier("foo"));
+        assert!(is_simple_identifier("_foo$1"));
+        assert!(!is_simple_identifier("foo.bar"));
+        assert!(!is_simple_identifier("1foo"));
+        assert!(!is_simple_identifier("onUpdate:foo"));
+        assert!(!is_simple_identifier(""));
+    }
+
+    #[test]
+    fn valid_asset_id() {
+        assert_eq!(
+            to_valid_asset_id("my-comp", &AssetType::Component),
+            "_component_my_comp"
+        );
+        assert_eq!(
+            to_valid_asset_id("focus", &AssetType::Directive),
+            "_directive_focus"
+        );
+        // #4422
+        assert_eq!(
+            to_valid_asset_id("foo.bar", &AssetType::Component),
+            "_component_foo46bar"
+        );
+    }
+}
@@ -0,0 +1,42 @@
+use vue_compiler_core::{CompilerError, ErrorCodes, SourceLocation};
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum DOMErrorCodes {
+    XVHtmlNoExpression,
+    XVHtmlWithChildren,
+    XVTextNoExpression,
+    XVTextWithChildren,
+    XTransitionInvalidChildren,
+    XTransitionGroupUnkeyedChildren,
+}
+
+impl DOMErrorCodes {
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::XVHtmlNoExpression => "v-html is missing expression.",
+            Self::XVHtmlWithChildren => "v-html will override element children.",
+            Self::XVTextNoExpression => "v-text is missing expression.",
+            Self::XVTextWithChildren => "v-text will override element children.",
+            Self::XTransitionInvalidChildren => {
+                "<Transition> expects exactly one child element or component."
+            }
+            Self::XTransitionGroupUnkeyedChildren => "<TransitionGroup> children must be keyed.",
+        }
+    }
+}
+
+impl From<DOMErrorCodes> for ErrorCodes {
+    fn from(code: DOMErrorCodes) -> Self {
+        ErrorCodes::Extended {
+            code: code as u32,
+            message: code.message(),
+        }
+    }
+}
+
+pub fn create_dom_compiler_error(
+    code: DOMErrorCodes,
+    loc: Option<SourceLocation>,
+) -> CompilerError {
+    CompilerError::new(code.into(), loc)
+}
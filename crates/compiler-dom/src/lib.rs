@@ -1,9 +1,94 @@
+mod errors;
 mod parser_options;
+mod runtime_helpers;
+mod transforms;
 
-use vue_compiler_core::{ParserOptions, RootNode, base_parse};
+use std::collections::HashMap;
+use std::rc::Rc;
+use vue_compiler_core::{
+    BaseCompileSource, CodegenResult, CompilerOptions, DirectiveTransform, IsBuiltInComponent,
+    NodeTransform, ParseMode, ParserOptions, RootNode, SharedErrorHandlingOptions, base_compile,
+    base_parse,
+};
 
-pub use crate::parser_options::parser_options;
+pub use crate::errors::{DOMErrorCodes, create_dom_compiler_error};
+pub use crate::parser_options::{is_built_in_component, parser_options};
+pub use crate::runtime_helpers::*;
+pub use crate::transforms::{
+    transition::{transform_transition, transform_transition_group},
+    v_html::TransformVHtml,
+    v_text::TransformVText,
+};
 
+pub fn dom_node_transforms(dev: bool) -> Vec<NodeTransform> {
+    let mut node_transforms: Vec<NodeTransform> = vec![];
+    if dev {
+        node_transforms.push(transform_transition);
+        node_transforms.push(transform_transition_group);
+    }
+    node_transforms
+}
+
+pub fn dom_directive_transforms() -> HashMap<String, Box<dyn DirectiveTransform>> {
+    HashMap::from([
+        (
+            "html".to_string(),
+            Box::new(TransformVHtml) as Box<dyn DirectiveTransform>,
+        ),
+        (
+            "text".to_string(),
+            Box::new(TransformVText) as Box<dyn DirectiveTransform>,
+        ),
+    ])
+}
+
+pub fn compile(template: &str, mut options: CompilerOptions) -> CodegenResult {
+    // parse with the DOM parser options, reporting to the same handler as the
+    // transforms
+    let error_handling_options = SharedErrorHandlingOptions::new(options.error_handling_options);
+    options.error_handling_options = Box::new(error_handling_options.clone());
+    let is_built_in_component: Rc<IsBuiltInComponent> = options
+        .is_built_in_component
+        .take()
+        .map(Rc::<IsBuiltInComponent>::from)
+        .unwrap_or_else(|| Rc::new(is_built_in_component));
+    let share_is_built_in_component = || {
+        let is_built_in_component = is_built_in_component.clone();
+        Some(Box::new(move |tag: &String| is_built_in_component(tag)) as Box<IsBuiltInComponent>)
+    };
+    options.is_built_in_component = share_is_built_in_component();
+    let ast = parse(
+        template,
+        Some(ParserOptions {
+            prefix_identifiers: options.prefix_identifiers,
+            is_built_in_component: share_is_built_in_component(),
+            is_custom_element: options.is_custom_element.take(),
+            comments: Some(
+                options
+                    .comments
+                    .unwrap_or(options.global_compile_time_constants.__dev__),
+            ),
+            compat: options.compat,
+            parse_mode: options.parse_mode.clone().unwrap_or(ParseMode::HTML),
+            error_handling_options: Box::new(error_handling_options),
+            global_compile_time_constants: options.global_compile_time_constants,
+            ..parser_options()
+        }),
+    );
+
+    let mut node_transforms = dom_node_transforms(options.global_compile_time_constants.__dev__);
+    node_transforms.extend(options.node_transforms.take().unwrap_or_default());
+    options.node_transforms = Some(node_transforms);
+
+    let mut directive_transforms = dom_directive_transforms();
+    directive_transforms.extend(options.directive_transforms.take().unwrap_or_default());
+    options.directive_transforms = Some(directive_transforms);
+
+    base_compile(BaseCompileSource::RootNode(Box::new(ast)), options)
+}
+
+/// Parses a template with the DOM parser options, `None` uses
+/// [`parser_options`] as is.
 pub fn parse(template: &str, options: Option<ParserOptions>) -> RootNode {
-    base_parse(template, options)
+    base_parse(template, Some(options.unwrap_or_else(parser_options)))
 }
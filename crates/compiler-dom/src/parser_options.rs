@@ -1,20 +1,26 @@
+use crate::runtime_helpers::{Transition, TransitionGroup};
 use vue_compiler_core::{BaseElementProps, Namespaces, ParseMode, ParserOptions};
+use vue_compiler_shared::{is_html_tag, is_math_ml_tag, is_svg_tag, is_void_tag};
+
+pub fn is_built_in_component(tag: &String) -> Option<String> {
+    if tag == "Transition" || tag == "transition" {
+        Some(Transition.to_string())
+    } else if tag == "TransitionGroup" || tag == "transition-group" {
+        Some(TransitionGroup.to_string())
+    } else {
+        None
+    }
+}
 
 pub fn parser_options() -> ParserOptions {
     ParserOptions {
         parse_mode: ParseMode::HTML,
-        // is_native_tag: (),
-        // is_void_tag: (),
-        is_pre_tag: Box::new(|tag| tag == "pre"),
-        is_built_in_component: Some(Box::new(|tag| {
-            if tag == "Transition" || tag == "transition" {
-                Some(())
-            } else if tag == "TransitionGroup" || tag == "transition-group" {
-                Some(())
-            } else {
-                None
-            }
+        is_native_tag: Some(Box::new(|tag| {
+            is_html_tag(tag) || is_svg_tag(tag) || is_math_ml_tag(tag)
         })),
+        is_void_tag: Box::new(|tag| is_void_tag(tag)),
+        is_pre_tag: Box::new(|tag| tag == "pre"),
+        is_built_in_component: Some(Box::new(is_built_in_component)),
         // https://html.spec.whatwg.org/multipage/parsing.html#tree-construction-dispatcher
         get_namespace: Box::new(|tag, parent, root_namespace| {
             let mut ns = if let Some(parent) = parent {
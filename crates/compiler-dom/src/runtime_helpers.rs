@@ -0,0 +1,4 @@
+use vue_compiler_core::symbol;
+
+symbol!(pub struct Transition: "Transition");
+symbol!(pub struct TransitionGroup: "TransitionGroup");
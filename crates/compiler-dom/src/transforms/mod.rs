@@ -0,0 +1,3 @@
+pub mod transition;
+pub mod v_html;
+pub mod v_text;
@@ -0,0 +1,125 @@
+use crate::errors::{DOMErrorCodes, create_dom_compiler_error};
+use crate::runtime_helpers::{Transition, TransitionGroup};
+use vue_compiler_core::{
+    AttributeNode, BaseElementProps, ElementNode, ElementTypes, NodeTransformState,
+    TemplateChildNode, TransformContext, TransformNode, find_dir, find_prop,
+};
+
+pub fn transform_transition(
+    node: &TransformNode,
+    context: &mut TransformContext,
+) -> Option<Box<dyn NodeTransformState>> {
+    if let TransformNode::TemplateChild(TemplateChildNode::Element(node)) = node
+        && node.tag_type() == ElementTypes::Component
+        && context.is_built_in_component(node.tag()) == Some(Transition.to_string())
+    {
+        return Some(Box::new(TransformTransition));
+    }
+    None
+}
+
+#[derive(Debug, Clone)]
+pub struct TransformTransition;
+
+impl NodeTransformState for TransformTransition {
+    fn exit(&mut self, node: &mut TransformNode, context: &mut TransformContext) {
+        let TransformNode::TemplateChild(TemplateChildNode::Element(node)) = node else {
+            return;
+        };
+        if node.children().is_empty() {
+            return;
+        }
+
+        // warn multiple transition children
+        if has_multiple_children(node.children()) {
+            let first = &node.children()[0];
+            let last = &node.children()[node.children().len() - 1];
+            let mut loc = first.loc().clone();
+            loc.end = last.loc().end.clone();
+            loc.source = String::new();
+            context.on_warn(create_dom_compiler_error(
+                DOMErrorCodes::XTransitionInvalidChildren,
+                Some(loc),
+            ));
+        }
+
+        // check if it's a single child w/ v-show
+        // if yes, inject "persisted: true" to the transition props
+        let TemplateChildNode::Element(child) = &node.children()[0] else {
+            return;
+        };
+        let has_v_show = child
+            .props()
+            .iter()
+            .any(|p| matches!(p, BaseElementProps::Directive(dir) if dir.name == "show"));
+        if has_v_show {
+            let loc = node.loc().clone();
+            node.props_mut()
+                .push(BaseElementProps::Attribute(AttributeNode {
+                    name: "persisted".to_string(),
+                    name_loc: loc.clone(),
+                    value: None,
+                    loc,
+                }));
+        }
+    }
+}
+
+fn has_multiple_children(children: &[TemplateChildNode]) -> bool {
+    // #1352 filter out potential comment nodes.
+    let children: Vec<_> = children
+        .iter()
+        .filter(|c| match c {
+            TemplateChildNode::Comment(_) => false,
+            TemplateChildNode::Text(text) => !text.content.trim().is_empty(),
+            _ => true,
+        })
+        .collect();
+    let [child] = children[..] else {
+        return true;
+    };
+    match child {
+        TemplateChildNode::For(_) => true,
+        TemplateChildNode::If(node) => node
+            .branches
+            .iter()
+            .any(|branch| has_multiple_children(&branch.children)),
+        _ => false,
+    }
+}
+
+pub fn transform_transition_group(
+    node: &TransformNode,
+    context: &mut TransformContext,
+) -> Option<Box<dyn NodeTransformState>> {
+    if let TransformNode::TemplateChild(TemplateChildNode::Element(node)) = node
+        && node.tag_type() == ElementTypes::Component
+        && context.is_built_in_component(node.tag()) == Some(TransitionGroup.to_string())
+    {
+        // checked before the children are transformed, while v-for and v-if are
+        // still on the child elements
+        for child in node.children() {
+            if let TemplateChildNode::Element(child) = child
+                && needs_key(child)
+                && find_prop(child, "key", None, None).is_none()
+            {
+                context.on_warn(create_dom_compiler_error(
+                    DOMErrorCodes::XTransitionGroupUnkeyedChildren,
+                    Some(child.loc().clone()),
+                ));
+            }
+        }
+    }
+    None
+}
+
+fn needs_key(el: &ElementNode) -> bool {
+    match el.tag_type() {
+        // slot outlets and `<template v-slot>` are not children of their own
+        ElementTypes::Slot | ElementTypes::Template => false,
+        // v-if branches are keyed automatically and v-for warns about its own key
+        _ => ["if", "else-if", "else", "for"]
+            .iter()
+            .all(|name| find_dir(el, name, Some(true)).is_none()),
+    }
+}
@@ -0,0 +1,46 @@
+use crate::errors::{DOMErrorCodes, create_dom_compiler_error};
+use vue_compiler_core::{
+    DirectiveNode, DirectiveTransform, DirectiveTransformResult, ElementNode, ExpressionNode,
+    JSChildNode, Property, SimpleExpressionNode, TransformContext,
+};
+
+#[derive(Debug, Clone)]
+pub struct TransformVHtml;
+
+impl DirectiveTransform for TransformVHtml {
+    fn transform(
+        &mut self,
+        dir: &DirectiveNode,
+        node: &mut ElementNode,
+        context: &mut TransformContext,
+    ) -> DirectiveTransformResult {
+        if dir.exp.is_none() {
+            context.on_error(create_dom_compiler_error(
+                DOMErrorCodes::XVHtmlNoExpression,
+                Some(dir.loc.clone()),
+            ));
+        }
+        if !node.children().is_empty() {
+            context.on_warn(create_dom_compiler_error(
+                DOMErrorCodes::XVHtmlWithChildren,
+                Some(dir.loc.clone()),
+            ));
+            node.children_mut().clear();
+        }
+
+        let value = match &dir.exp {
+            Some(exp) => JSChildNode::from(exp.clone()),
+            None => JSChildNode::Simple(SimpleExpressionNode::constant("")),
+        };
+        DirectiveTransformResult {
+            props: vec![Property::new(
+                ExpressionNode::Simple(SimpleExpressionNode::constant("innerHTML")),
+                value,
+            )],
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn DirectiveTransform> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,57 @@
+use crate::errors::{DOMErrorCodes, create_dom_compiler_error};
+use vue_compiler_core::{
+    CallArgument, CallCallee, CallExpression, ConstantTypes, DirectiveNode, DirectiveTransform,
+    DirectiveTransformResult, ElementNode, ExpressionNode, JSChildNode, Property,
+    SimpleExpressionNode, ToDisplayString, TransformContext,
+};
+
+#[derive(Debug, Clone)]
+pub struct TransformVText;
+
+impl DirectiveTransform for TransformVText {
+    fn transform(
+        &mut self,
+        dir: &DirectiveNode,
+        node: &mut ElementNode,
+        context: &mut TransformContext,
+    ) -> DirectiveTransformResult {
+        if dir.exp.is_none() {
+            context.on_error(create_dom_compiler_error(
+                DOMErrorCodes::XVTextNoExpression,
+                Some(dir.loc.clone()),
+            ));
+        }
+        if !node.children().is_empty() {
+            context.on_warn(create_dom_compiler_error(
+                DOMErrorCodes::XVTextWithChildren,
+                Some(dir.loc.clone()),
+            ));
+            node.children_mut().clear();
+        }
+
+        let value = match &dir.exp {
+            Some(ExpressionNode::Simple(exp)) if exp.const_type > ConstantTypes::NotConstant => {
+                JSChildNode::Simple(exp.clone())
+            }
+            Some(exp) => {
+                let callee = context.helper(ToDisplayString.to_string());
+                JSChildNode::Call(CallExpression::new(
+                    CallCallee::Symbol(callee),
+                    Some(vec![CallArgument::JSChild(JSChildNode::from(exp.clone()))]),
+                    Some(dir.loc.clone()),
+                ))
+            }
+            None => JSChildNode::Simple(SimpleExpressionNode::constant("")),
+        };
+        DirectiveTransformResult {
+            props: vec![Property::new(
+                ExpressionNode::Simple(SimpleExpressionNode::constant("textContent")),
+                value,
+            )],
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn DirectiveTransform> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,56 @@
+mod test_utils;
+
+#[cfg(test)]
+mod compile {
+    use crate::test_utils::TestErrorHandlingOptions;
+    use vue_compiler_core::{CompilerOptions, ErrorCodes, GlobalCompileTimeConstants};
+    use vue_compiler_dom::compile;
+
+    fn dev_options() -> CompilerOptions {
+        CompilerOptions {
+            global_compile_time_constants: GlobalCompileTimeConstants {
+                __dev__: true,
+                __test__: false,
+                __browser__: false,
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn comments_option() {
+        let template = "<div><!-- c --></div>";
+        // kept in development by default
+        let code = compile(template, dev_options()).code;
+        assert!(code.contains(r#"_createCommentVNode(" c ")"#), "{code}");
+
+        let code = compile(
+            template,
+            CompilerOptions {
+                comments: Some(false),
+                ..dev_options()
+            },
+        )
+        .code;
+        assert!(!code.contains("_createCommentVNode"), "{code}");
+    }
+
+    #[test]
+    fn compat_v_if_v_for_precedence() {
+        let error_handling_options = TestErrorHandlingOptions::new();
+        let options = CompilerOptions {
+            compat: Some(true),
+            error_handling_options: Box::new(error_handling_options.clone()),
+            ..dev_options()
+        };
+        compile(
+            r#"<div><li v-for="i in list" :key="i" v-if="ok"/></div>"#,
+            options,
+        );
+
+        assert!(error_handling_options.errors().is_empty());
+        let warnings = error_handling_options.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, ErrorCodes::XVIfVForPrecedence);
+    }
+}
@@ -0,0 +1,2 @@
+mod test_utils;
+mod transforms;
@@ -0,0 +1,252 @@
+#[cfg(test)]
+mod text {
+    use vue_compiler_core::{Position, SourceLocation, TemplateChildNode};
+    use vue_compiler_dom::{parse, parser_options};
+
+    #[test]
+    fn textarea_handles_comments_elements_as_just_text() {
+        let ast = parse(
+            "<textarea>some<div>text</div>and<!--comment--></textarea>",
+            Some(parser_options()),
+        );
+        let element = ast.children.first();
+        assert!(matches!(element, Some(&TemplateChildNode::Element(_))));
+        if let Some(TemplateChildNode::Element(element)) = element {
+            let text = element.children().first();
+            assert!(element.children().len() == 1);
+            assert_eq!(
+                text,
+                Some(&TemplateChildNode::new_text(
+                    "some<div>text</div>and<!--comment-->",
+                    SourceLocation {
+                        start: Position {
+                            offset: 10,
+                            line: 1,
+                            column: 11,
+                        },
+                        end: Position {
+                            offset: 46,
+                            line: 1,
+                            column: 47,
+                        },
+                        source: "some<div>text</div>and<!--comment-->".to_string(),
+                    },
+                ))
+            );
+        }
+    }
+
+    fn text_content(template: &str) -> String {
+        let ast = parse(template, Some(parser_options()));
+        let Some(TemplateChildNode::Element(element)) = ast.children.first() else {
+            unreachable!();
+        };
+        let Some(TemplateChildNode::Text(text)) = element.children().first() else {
+            unreachable!();
+        };
+        text.content.clone()
+    }
+
+    #[test]
+    fn textarea_and_title_decode_entities() {
+        assert_eq!(text_content("<textarea>a &amp; b</textarea>"), "a & b");
+        assert_eq!(text_content("<title>a &amp; b</title>"), "a & b");
+    }
+
+    #[test]
+    fn script_and_style_keep_entities_raw() {
+        assert_eq!(text_content("<script>a &amp; b</script>"), "a &amp; b");
+        assert_eq!(text_content("<style>a &amp; b</style>"), "a &amp; b");
+    }
+}
+
+#[cfg(test)]
+mod namespaces {
+    use vue_compiler_core::{ElementNode, Namespaces, ParserOptions, TemplateChildNode};
+    use vue_compiler_dom::{parse, parser_options};
+
+    #[test]
+    fn rcdata_after_closed_svg() {
+        let ast = parse(
+            "<svg></svg><textarea><b></textarea>",
+            Some(parser_options()),
+        );
+        assert_eq!(ast.children.len(), 2);
+        let TemplateChildNode::Element(svg) = &ast.children[0] else {
+            unreachable!();
+        };
+        assert_eq!(svg.ns(), &(Namespaces::SVG as u32));
+
+        let TemplateChildNode::Element(ElementNode::PlainElement(textarea)) = &ast.children[1]
+        else {
+            unreachable!();
+        };
+        assert_eq!(textarea.ns, Namespaces::HTML as u32);
+        assert_eq!(textarea.children.len(), 1);
+        assert!(matches!(
+            &textarea.children[0],
+            TemplateChildNode::Text(text) if text.content == "<b>"
+        ));
+    }
+    #[test]
+    fn math_ml_root_namespace() {
+        let ast = parse(
+            "<mrow><mi>x</mi><title><mi>y</mi></title></mrow>",
+            Some(ParserOptions {
+                ns: Namespaces::MathML,
+                ..parser_options()
+            }),
+        );
+        let mrow = first_element(&ast.children);
+        assert_eq!(mrow.ns(), &(Namespaces::MathML as u32));
+
+        let [
+            TemplateChildNode::Element(mi),
+            TemplateChildNode::Element(title),
+        ] = mrow.children().as_slice()
+        else {
+            unreachable!();
+        };
+        assert_eq!(mi.ns(), &(Namespaces::MathML as u32));
+        // <title> is a plain MathML element, not RCDATA
+        assert_eq!(title.ns(), &(Namespaces::MathML as u32));
+        assert!(matches!(
+            title.children().as_slice(),
+            [TemplateChildNode::Element(mi)] if mi.tag() == "mi"
+        ));
+    }
+
+    fn first_element(children: &[TemplateChildNode]) -> &ElementNode {
+        let Some(TemplateChildNode::Element(element)) = children.first() else {
+            unreachable!();
+        };
+        element
+    }
+
+    #[test]
+    fn html_inside_mathml_text_integration_point() {
+        let ast = parse("<math><mtext><div/></mtext></math>", Some(parser_options()));
+        let math = first_element(&ast.children);
+        let mtext = first_element(math.children());
+        let div = first_element(mtext.children());
+        assert_eq!(math.ns(), &(Namespaces::MathML as u32));
+        assert_eq!(mtext.ns(), &(Namespaces::MathML as u32));
+        assert_eq!(div.ns(), &(Namespaces::HTML as u32));
+    }
+
+    #[test]
+    fn mathml_glyph_inside_mathml_text_integration_point() {
+        let ast = parse("<math><mi><mglyph/></mi></math>", Some(parser_options()));
+        let math = first_element(&ast.children);
+        let mglyph = first_element(first_element(math.children()).children());
+        assert_eq!(mglyph.ns(), &(Namespaces::MathML as u32));
+    }
+
+    #[test]
+    fn html_inside_svg_foreign_object() {
+        let ast = parse(
+            "<svg><foreignObject><div/></foreignObject><g/></svg>",
+            Some(parser_options()),
+        );
+        let svg = first_element(&ast.children);
+        let foreign_object = first_element(svg.children());
+        let div = first_element(foreign_object.children());
+        assert_eq!(foreign_object.ns(), &(Namespaces::SVG as u32));
+        assert_eq!(div.ns(), &(Namespaces::HTML as u32));
+        let TemplateChildNode::Element(g) = &svg.children()[1] else {
+            unreachable!();
+        };
+        assert_eq!(g.ns(), &(Namespaces::SVG as u32));
+    }
+
+    #[test]
+    fn annotation_xml_integration_point() {
+        let ast = parse(
+            r#"<math><annotation-xml><svg/></annotation-xml><annotation-xml encoding="text/html"><div/></annotation-xml></math>"#,
+            Some(parser_options()),
+        );
+        let math = first_element(&ast.children);
+        let annotation = first_element(math.children());
+        let svg = first_element(annotation.children());
+        assert_eq!(svg.ns(), &(Namespaces::SVG as u32));
+
+        let TemplateChildNode::Element(annotation) = &math.children()[1] else {
+            unreachable!();
+        };
+        let div = first_element(annotation.children());
+        assert_eq!(div.ns(), &(Namespaces::HTML as u32));
+    }
+}
+
+#[cfg(test)]
+mod native_tags {
+    use vue_compiler_core::{ElementNode, TemplateChildNode};
+    use vue_compiler_dom::{parse, parser_options};
+
+    #[test]
+    fn native_tag_and_unknown_tag() {
+        let ast = parse("<div></div><widget></widget>", Some(parser_options()));
+        assert_eq!(ast.children.len(), 2);
+        assert!(matches!(
+            &ast.children[0],
+            TemplateChildNode::Element(ElementNode::PlainElement(_))
+        ));
+        assert!(matches!(
+            &ast.children[1],
+            TemplateChildNode::Element(ElementNode::Component(_))
+        ));
+    }
+}
+
+#[cfg(test)]
+mod void_tags {
+    use vue_compiler_core::TemplateChildNode;
+    use vue_compiler_dom::{parse, parser_options};
+
+    #[test]
+    fn void_element_closes_itself() {
+        let ast = parse("<img>text", Some(parser_options()));
+        assert_eq!(ast.children.len(), 2);
+        let TemplateChildNode::Element(img) = &ast.children[0] else {
+            unreachable!();
+        };
+        assert_eq!(img.tag(), "img");
+        assert!(img.children().is_empty());
+        let TemplateChildNode::Text(text) = &ast.children[1] else {
+            unreachable!();
+        };
+        assert_eq!(text.content, "text");
+    }
+}
+
+#[cfg(test)]
+mod default_options {
+    use vue_compiler_core::{ElementNode, Namespaces, TemplateChildNode};
+    use vue_compiler_dom::parse;
+
+    #[test]
+    fn parse_without_options_uses_dom_defaults() {
+        let ast = parse("<ul>\n  <li>a</li>\n</ul><img>text<svg/>", None);
+        assert_eq!(ast.children.len(), 4);
+
+        // whitespace is condensed
+        let TemplateChildNode::Element(ul) = &ast.children[0] else {
+            unreachable!();
+        };
+        assert_eq!(ul.children().len(), 1);
+        assert!(matches!(
+            &ul.children()[0],
+            TemplateChildNode::Element(ElementNode::PlainElement(li)) if li.tag == "li"
+        ));
+        // void tags close themselves
+        let TemplateChildNode::Element(img) = &ast.children[1] else {
+            unreachable!();
+        };
+        assert!(img.children().is_empty());
+        // namespaces are resolved
+        let TemplateChildNode::Element(svg) = &ast.children[3] else {
+            unreachable!();
+        };
+        assert_eq!(svg.ns(), &(Namespaces::SVG as u32));
+    }
+}
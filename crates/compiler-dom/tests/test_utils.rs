@@ -0,0 +1,36 @@
+use std::{cell::RefCell, rc::Rc};
+use vue_compiler_core::{CompilerError, ErrorHandlingOptions};
+
+/// Collects the errors and warnings reported through the options it is
+/// cloned into.
+#[derive(Debug, Clone, Default)]
+pub struct TestErrorHandlingOptions {
+    errors: Rc<RefCell<Vec<CompilerError>>>,
+    warnings: Rc<RefCell<Vec<CompilerError>>>,
+}
+
+impl TestErrorHandlingOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes the errors collected so far.
+    pub fn errors(&self) -> Vec<CompilerError> {
+        self.errors.take()
+    }
+
+    /// Takes the warnings collected so far.
+    pub fn warnings(&self) -> Vec<CompilerError> {
+        self.warnings.take()
+    }
+}
+
+impl ErrorHandlingOptions for TestErrorHandlingOptions {
+    fn on_warn(&mut self, warning: CompilerError) {
+        self.warnings.borrow_mut().push(warning);
+    }
+
+    fn on_error(&mut self, error: CompilerError) {
+        self.errors.borrow_mut().push(error);
+    }
+}
@@ -0,0 +1,3 @@
+mod transition;
+mod v_html;
+mod v_text;
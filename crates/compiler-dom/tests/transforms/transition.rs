@@ -0,0 +1,118 @@
+#[cfg(test)]
+mod compiler_transition {
+    use crate::test_utils::TestErrorHandlingOptions;
+    use vue_compiler_core::{
+        CodegenResult, CompilerError, CompilerOptions, ErrorCodes, JSChildNode, RootCodegenNode,
+        VNodeCall, VNodeCallTag,
+    };
+    use vue_compiler_dom::{DOMErrorCodes, Transition, TransitionGroup, compile};
+
+    fn compile_transition(template: &str) -> (VNodeCall, String, Vec<CompilerError>) {
+        let error_handling_options = TestErrorHandlingOptions::new();
+        let mut options = CompilerOptions {
+            error_handling_options: Box::new(error_handling_options.clone()),
+            ..Default::default()
+        };
+        options.global_compile_time_constants.__dev__ = true;
+        let CodegenResult { ast, code, .. } = compile(template, options);
+        let Some(RootCodegenNode::JSChild(JSChildNode::VNodeCall(node))) = ast.codegen_node else {
+            unreachable!();
+        };
+        (node, code, error_handling_options.warnings())
+    }
+
+    #[test]
+    fn resolve_transition_as_built_in() {
+        let (node, code, warnings) =
+            compile_transition(r#"<Transition><div v-if="x"/></Transition>"#);
+
+        assert!(warnings.is_empty());
+        assert_eq!(node.tag, VNodeCallTag::Symbol(Transition.to_string()));
+        assert!(code.contains("Transition: _Transition"));
+        assert!(code.contains("_createBlock(_Transition, null, ["));
+    }
+
+    #[test]
+    fn resolve_transition_group_as_built_in() {
+        for template in [
+            "<TransitionGroup><div/></TransitionGroup>",
+            "<transition-group><div/></transition-group>",
+        ] {
+            let (node, code, _) = compile_transition(template);
+
+            assert!(node.is_component, "{template}");
+            assert_eq!(node.tag, VNodeCallTag::Symbol(TransitionGroup.to_string()));
+            assert!(code.contains("TransitionGroup: _TransitionGroup"));
+            assert!(!code.contains("resolveComponent"));
+        }
+    }
+
+    #[test]
+    fn transition_group_with_keyed_children() {
+        let (node, code, warnings) = compile_transition(
+            r#"<TransitionGroup><div v-for="i in l" :key="i"/></TransitionGroup>"#,
+        );
+
+        assert!(warnings.is_empty());
+        assert!(node.is_block);
+        assert!(code.contains("_createBlock(_TransitionGroup, null, ["));
+        assert!(code.contains(r#"_createElementBlock("div", { key: i })"#));
+
+        for template in [
+            r#"<TransitionGroup><p key="a"/><p v-if="x"/><p v-else/></TransitionGroup>"#,
+            r#"<TransitionGroup><template v-for="i in l" :key="i"><p/></template></TransitionGroup>"#,
+            "<TransitionGroup><slot/><!--c--> text {{ t }}</TransitionGroup>",
+        ] {
+            let (_, _, warnings) = compile_transition(template);
+            assert!(warnings.is_empty(), "{template}");
+        }
+    }
+
+    #[test]
+    fn warn_unkeyed_transition_group_children() {
+        let (_, _, warnings) =
+            compile_transition(r#"<TransitionGroup><div/><p key="a"/><Comp/></TransitionGroup>"#);
+
+        let codes: Vec<_> = warnings.iter().map(|w| &w.code).collect();
+        let code = ErrorCodes::from(DOMErrorCodes::XTransitionGroupUnkeyedChildren);
+        assert_eq!(codes, [&code, &code]);
+        assert_eq!(warnings[0].loc.as_ref().unwrap().source, "<div/>");
+        assert_eq!(warnings[1].loc.as_ref().unwrap().source, "<Comp/>");
+    }
+
+    #[test]
+    fn warn_multiple_transition_children() {
+        let (_, _, warnings) = compile_transition("<Transition><div/><div/></Transition>");
+
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].code,
+            DOMErrorCodes::XTransitionInvalidChildren.into()
+        );
+        let loc = warnings[0].loc.as_ref().unwrap();
+        assert_eq!(loc.start.offset, 12);
+        assert_eq!(loc.end.offset, 24);
+    }
+
+    #[test]
+    fn warn_v_if_branch_with_multiple_children() {
+        let (_, _, warnings) = compile_transition(
+            r#"<Transition><template v-if="x"><div/><div/></template></Transition>"#,
+        );
+
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].code,
+            DOMErrorCodes::XTransitionInvalidChildren.into()
+        );
+    }
+
+    #[test]
+    fn inject_persisted_for_single_v_show_child() {
+        let (_, code, warnings) =
+            compile_transition(r#"<Transition><div v-show="ok"/></Transition>"#);
+
+        assert!(warnings.is_empty());
+        assert!(code.contains(r#"_createBlock(_Transition, { persisted: "" }"#));
+    }
+}
@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod compiler_v_html {
+    use crate::test_utils::TestErrorHandlingOptions;
+    use vue_compiler_core::{
+        CodegenResult, CompilerOptions, ExpressionNode, JSChildNode, PropsExpression,
+        RootCodegenNode, VNodeCall,
+    };
+    use vue_compiler_dom::{DOMErrorCodes, compile};
+
+    fn compile_v_html(template: &str, options: CompilerOptions) -> (VNodeCall, String) {
+        let CodegenResult { ast, code, .. } = compile(template, options);
+        let Some(RootCodegenNode::JSChild(JSChildNode::VNodeCall(node))) = ast.codegen_node else {
+            unreachable!();
+        };
+        (node, code)
+    }
+
+    #[test]
+    fn should_convert_v_html_to_inner_html() {
+        let (node, code) = compile_v_html(r#"<div v-html="h"/>"#, CompilerOptions::default());
+        assert_eq!(node.children, None);
+        let Some(PropsExpression::Object(props)) = node.props else {
+            unreachable!();
+        };
+        assert_eq!(props.properties.len(), 1);
+        assert!(matches!(
+            &props.properties[0].key,
+            ExpressionNode::Simple(key) if key.content == "innerHTML" && key.is_static
+        ));
+        assert!(matches!(
+            &props.properties[0].value,
+            JSChildNode::Simple(value) if value.content == "h" && !value.is_static
+        ));
+        assert!(code.contains(r#"_createElementBlock("div", { innerHTML: h }"#));
+    }
+
+    #[test]
+    fn should_raise_error_and_ignore_children_when_v_html_is_present() {
+        let error_handling_options = TestErrorHandlingOptions::new();
+        let (node, code) = compile_v_html(
+            r#"<div v-html="test">hello</div>"#,
+            CompilerOptions {
+                error_handling_options: Box::new(error_handling_options.clone()),
+                ..Default::default()
+            },
+        );
+        let errors = error_handling_options.errors();
+        let warnings = error_handling_options.warnings();
+        assert!(errors.is_empty());
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, DOMErrorCodes::XVHtmlWithChildren.into());
+        assert_eq!(node.children, None);
+        assert!(!code.contains("hello"));
+    }
+
+    #[test]
+    fn should_raise_error_if_has_no_expression() {
+        let error_handling_options = TestErrorHandlingOptions::new();
+        compile_v_html(
+            "<div v-html></div>",
+            CompilerOptions {
+                error_handling_options: Box::new(error_handling_options.clone()),
+                ..Default::default()
+            },
+        );
+        let errors = error_handling_options.errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code, DOMErrorCodes::XVHtmlNoExpression.into());
+    }
+}
@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod compiler_v_text {
+    use crate::test_utils::TestErrorHandlingOptions;
+    use vue_compiler_core::{
+        CallArgument, CallCallee, CodegenResult, CompilerOptions, ExpressionNode, JSChildNode,
+        PropsExpression, RootCodegenNode, ToDisplayString, VNodeCall,
+    };
+    use vue_compiler_dom::{DOMErrorCodes, compile};
+
+    fn compile_v_text(template: &str, options: CompilerOptions) -> (VNodeCall, String) {
+        let CodegenResult { ast, code, .. } = compile(template, options);
+        let Some(RootCodegenNode::JSChild(JSChildNode::VNodeCall(node))) = ast.codegen_node else {
+            unreachable!();
+        };
+        (node, code)
+    }
+
+    #[test]
+    fn should_convert_v_text_to_text_content() {
+        let (node, code) = compile_v_text(r#"<div v-text="t"/>"#, CompilerOptions::default());
+        assert_eq!(node.children, None);
+        let Some(PropsExpression::Object(props)) = node.props else {
+            unreachable!();
+        };
+        assert_eq!(props.properties.len(), 1);
+        assert!(matches!(
+            &props.properties[0].key,
+            ExpressionNode::Simple(key) if key.content == "textContent" && key.is_static
+        ));
+        let JSChildNode::Call(value) = &props.properties[0].value else {
+            unreachable!();
+        };
+        assert_eq!(
+            value.callee,
+            CallCallee::Symbol(ToDisplayString.to_string())
+        );
+        assert!(matches!(
+            &value.arguments[..],
+            [CallArgument::JSChild(JSChildNode::Simple(arg))] if arg.content == "t"
+        ));
+        assert!(code.contains(
+            r#"_createElementBlock("div", {
+      textContent: _toDisplayString(t)
+    }"#
+        ));
+    }
+
+    #[test]
+    fn should_raise_error_and_ignore_children_when_v_text_is_present() {
+        let error_handling_options = TestErrorHandlingOptions::new();
+        let (node, code) = compile_v_text(
+            r#"<div v-text="test">hello</div>"#,
+            CompilerOptions {
+                error_handling_options: Box::new(error_handling_options.clone()),
+                ..Default::default()
+            },
+        );
+        let errors = error_handling_options.errors();
+        let warnings = error_handling_options.warnings();
+        assert!(errors.is_empty());
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, DOMErrorCodes::XVTextWithChildren.into());
+        assert_eq!(node.children, None);
+        assert!(!code.contains("hello"));
+    }
+
+    #[test]
+    fn should_raise_error_if_has_no_expression() {
+        let error_handling_options = TestErrorHandlingOptions::new();
+        compile_v_text(
+            "<div v-text></div>",
+            CompilerOptions {
+                error_handling_options: Box::new(error_handling_options.clone()),
+                ..Default::default()
+            },
+        );
+        let errors = error_handling_options.errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code, DOMErrorCodes::XVTextNoExpression.into());
+    }
+}
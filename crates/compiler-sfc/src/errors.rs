@@ -0,0 +1,31 @@
+use vue_compiler_core::{CompilerError, ErrorCodes, SourceLocation};
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum SFCErrorCodes {
+    /// a second `<template>`, `<script>` or `<script setup>` block
+    DuplicateBlock,
+}
+
+impl SFCErrorCodes {
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::DuplicateBlock => "Single file component can contain only one block of a kind.",
+        }
+    }
+}
+
+impl From<SFCErrorCodes> for ErrorCodes {
+    fn from(code: SFCErrorCodes) -> Self {
+        ErrorCodes::Extended {
+            code: code as u32,
+            message: code.message(),
+        }
+    }
+}
+
+pub fn create_sfc_compiler_error(
+    code: SFCErrorCodes,
+    loc: Option<SourceLocation>,
+) -> CompilerError {
+    CompilerError::new(code.into(), loc)
+}
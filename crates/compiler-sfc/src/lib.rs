@@ -1,4 +1,9 @@
 mod compile_template;
+mod errors;
 mod parse;
 
-pub use crate::parse::{SFCParseOptions, parse};
+pub use crate::errors::{SFCErrorCodes, create_sfc_compiler_error};
+pub use crate::parse::{
+    SFCBlock, SFCDescriptor, SFCParseOptions, SFCParseResult, SFCScriptBlock, SFCStyleBlock,
+    StringOrBool, parse,
+};
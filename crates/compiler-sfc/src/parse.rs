@@ -1,21 +1,179 @@
+use indexmap::IndexMap;
+use std::{cell::RefCell, rc::Rc};
+use vue_compiler_core::{
+    BaseElementProps, CompilerError, ElementNode, ErrorHandlingOptions, ParseMode, ParserOptions,
+    SourceLocation, TemplateChildNode,
+};
+use vue_compiler_dom::{parse as dom_parse, parser_options};
+
+use crate::errors::{SFCErrorCodes, create_sfc_compiler_error};
+
 #[derive(Debug, Default)]
 pub struct SFCParseOptions {
-    filename: Option<String>,
+    pub filename: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum StringOrBool {
+    String(String),
+    Bool(bool),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SFCBlock {
+    pub type_: String,
+    pub content: String,
+    /// The attributes of the block element, keyed by their names as written.
+    /// Attributes without a value are `true`.
+    pub attrs: IndexMap<String, StringOrBool>,
+    pub loc: SourceLocation,
+    pub lang: Option<String>,
+    /// The external file the block is imported from, its inner content is
+    /// empty.
+    pub src: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SFCScriptBlock {
+    pub block: SFCBlock,
+    /// Whether this is a `<script setup>` block.
+    pub is_setup: bool,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SFCStyleBlock {
+    pub block: SFCBlock,
+    pub scoped: bool,
+    /// `module` or `module="name"` for CSS modules.
+    pub module: Option<StringOrBool>,
 }
 
 #[derive(Debug)]
 pub struct SFCDescriptor {
     pub filename: String,
     pub source: String,
+    pub template: Option<SFCBlock>,
+    pub script: Option<SFCScriptBlock>,
+    pub script_setup: Option<SFCScriptBlock>,
+    pub styles: Vec<SFCStyleBlock>,
+    pub custom_blocks: Vec<SFCBlock>,
 }
 
 #[derive(Debug)]
 pub struct SFCParseResult {
     pub descriptor: SFCDescriptor,
+    pub errors: Vec<CompilerError>,
+}
+
+#[derive(Debug)]
+struct CollectErrors(Rc<RefCell<Vec<CompilerError>>>);
+
+impl ErrorHandlingOptions for CollectErrors {
+    fn on_error(&mut self, error: CompilerError) {
+        self.0.borrow_mut().push(error);
+    }
 }
 
 pub fn parse(source: String, options: Option<SFCParseOptions>) -> SFCParseResult {
     let SFCParseOptions { filename } = options.unwrap_or_default();
 
-    todo!()
+    let errors: Rc<RefCell<Vec<CompilerError>>> = Default::default();
+    let ast = dom_parse(
+        &source,
+        Some(ParserOptions {
+            parse_mode: ParseMode::SFC,
+            error_handling_options: Box::new(CollectErrors(errors.clone())),
+            ..parser_options()
+        }),
+    );
+
+    let mut descriptor = SFCDescriptor {
+        filename: filename.unwrap_or_else(|| "anonymous.vue".to_string()),
+        source,
+        template: None,
+        script: None,
+        script_setup: None,
+        styles: Vec::new(),
+        custom_blocks: Vec::new(),
+    };
+
+    let mut errors = errors.take();
+    for node in &ast.children {
+        let TemplateChildNode::Element(node) = node else {
+            continue;
+        };
+        // an unclosed block, the parser has reported it
+        let Some(block) = create_block(node) else {
+            continue;
+        };
+        // duplicate blocks are reported, keeping the first one
+        match node.tag().as_str() {
+            "template" => {
+                if descriptor.template.is_some() {
+                    errors.push(create_duplicate_block_error(node, "template"));
+                } else {
+                    descriptor.template = Some(block);
+                }
+            }
+            "script" => {
+                let is_setup = block.attrs.contains_key("setup");
+                let script = if is_setup {
+                    &mut descriptor.script_setup
+                } else {
+                    &mut descriptor.script
+                };
+                if script.is_some() {
+                    let tag = if is_setup { "script setup" } else { "script" };
+                    errors.push(create_duplicate_block_error(node, tag));
+                } else {
+                    *script = Some(SFCScriptBlock { block, is_setup });
+                }
+            }
+            "style" => descriptor.styles.push(SFCStyleBlock {
+                scoped: block.attrs.contains_key("scoped"),
+                module: block.attrs.get("module").cloned(),
+                block,
+            }),
+            _ => descriptor.custom_blocks.push(block),
+        }
+    }
+
+    SFCParseResult { descriptor, errors }
+}
+
+fn create_duplicate_block_error(node: &ElementNode, tag: &str) -> CompilerError {
+    let mut error =
+        create_sfc_compiler_error(SFCErrorCodes::DuplicateBlock, Some(node.loc().clone()));
+    error.message = format!("Single file component can contain only one <{tag}> element");
+    error
+}
+
+fn create_block(node: &ElementNode) -> Option<SFCBlock> {
+    let loc = node.inner_loc()?.clone();
+    let mut block = SFCBlock {
+        type_: node.tag().clone(),
+        content: loc.source.clone(),
+        attrs: IndexMap::new(),
+        loc,
+        lang: None,
+        src: None,
+    };
+    for prop in node.props() {
+        let BaseElementProps::Attribute(prop) = prop else {
+            continue;
+        };
+        let name = prop.name.clone();
+        let value = prop.value.as_ref().map(|value| value.content.clone());
+        if name == "lang" {
+            block.lang = value.clone();
+        } else if name == "src" {
+            block.src = value.clone();
+        }
+        let value = match value {
+            Some(value) if !value.is_empty() => StringOrBool::String(value),
+            _ => StringOrBool::Bool(true),
+        };
+        block.attrs.insert(name, value);
+    }
+    Some(block)
 }
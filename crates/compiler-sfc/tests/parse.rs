@@ -0,0 +1,171 @@
+#[cfg(test)]
+mod compiler_sfc_parse {
+    use vue_compiler_core::ErrorCodes;
+    use vue_compiler_sfc::{SFCErrorCodes, SFCParseResult, StringOrBool, parse};
+
+    #[test]
+    fn script_setup() {
+        let SFCParseResult { descriptor, errors } = parse(
+            r#"<script setup lang="ts">const a = 1</script>"#.to_string(),
+            None,
+        );
+
+        assert!(errors.is_empty());
+        assert!(descriptor.script.is_none());
+        let Some(script) = descriptor.script_setup else {
+            unreachable!();
+        };
+        assert!(script.is_setup);
+        assert_eq!(script.block.lang, Some("ts".to_string()));
+        assert_eq!(script.block.content, "const a = 1");
+        assert_eq!(
+            script.block.attrs.into_iter().collect::<Vec<_>>(),
+            [
+                ("setup".to_string(), StringOrBool::Bool(true)),
+                ("lang".to_string(), StringOrBool::String("ts".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn blocks() {
+        let SFCParseResult { descriptor, errors } = parse(
+            "<template><div>{{ msg }}</div></template>\n<script>export default {}</script>\n<script setup>const msg = 1</script>\n<style>.a{}</style>\n<i18n data-locale=\"en\">{}</i18n>".to_string(),
+            None,
+        );
+
+        assert!(errors.is_empty());
+        assert_eq!(descriptor.filename, "anonymous.vue");
+        assert_eq!(
+            descriptor.template.map(|template| template.content),
+            Some("<div>{{ msg }}</div>".to_string())
+        );
+        let Some(script) = descriptor.script else {
+            unreachable!();
+        };
+        assert!(!script.is_setup);
+        assert_eq!(script.block.lang, None);
+        assert_eq!(script.block.content, "export default {}");
+        assert_eq!(
+            descriptor.script_setup.map(|script| script.block.content),
+            Some("const msg = 1".to_string())
+        );
+        assert_eq!(descriptor.styles.len(), 1);
+        assert_eq!(descriptor.styles[0].block.content, ".a{}");
+        assert!(!descriptor.styles[0].scoped);
+        assert_eq!(descriptor.styles[0].module, None);
+        assert_eq!(descriptor.custom_blocks.len(), 1);
+        assert_eq!(descriptor.custom_blocks[0].type_, "i18n");
+        assert_eq!(
+            descriptor.custom_blocks[0].attrs.get("data-locale"),
+            Some(&StringOrBool::String("en".to_string()))
+        );
+    }
+
+    #[test]
+    fn style_scoped_and_module() {
+        let SFCParseResult { descriptor, .. } = parse(
+            r#"<style scoped module="s">.a{}</style><style module>.b{}</style>"#.to_string(),
+            None,
+        );
+
+        let [scoped, module] = descriptor.styles.as_slice() else {
+            unreachable!();
+        };
+        assert!(scoped.scoped);
+        assert_eq!(scoped.module, Some(StringOrBool::String("s".to_string())));
+        assert_eq!(scoped.block.content, ".a{}");
+        assert!(!module.scoped);
+        assert_eq!(module.module, Some(StringOrBool::Bool(true)));
+    }
+
+    #[test]
+    fn src_imports() {
+        let SFCParseResult { descriptor, errors } = parse(
+            "<template src=\"./t.html\"></template>\n<script src=\"./x.js\"/>\n<style src=\"./s.css\"/>"
+                .to_string(),
+            None,
+        );
+
+        assert!(errors.is_empty());
+        let Some(template) = descriptor.template else {
+            unreachable!();
+        };
+        assert_eq!(template.src, Some("./t.html".to_string()));
+        assert_eq!(template.content, "");
+        let Some(script) = descriptor.script else {
+            unreachable!();
+        };
+        assert_eq!(script.block.src, Some("./x.js".to_string()));
+        assert_eq!(script.block.content, "");
+        assert_eq!(descriptor.styles[0].block.src, Some("./s.css".to_string()));
+        assert_eq!(descriptor.styles[0].block.content, "");
+    }
+
+    #[test]
+    fn unclosed_blocks() {
+        let SFCParseResult { descriptor, errors } = parse("<template><div>".to_string(), None);
+        assert_eq!(
+            errors.iter().map(|error| &error.code).collect::<Vec<_>>(),
+            [&ErrorCodes::XMissingEndTag, &ErrorCodes::XMissingEndTag]
+        );
+        assert_eq!(
+            descriptor.template.map(|template| template.content),
+            Some("<div>".to_string())
+        );
+
+        let SFCParseResult { descriptor, errors } = parse("<a x</b><c>".to_string(), None);
+        assert_eq!(
+            errors.last().map(|error| &error.code),
+            Some(&ErrorCodes::XMissingEndTag)
+        );
+        assert_eq!(descriptor.custom_blocks.len(), 1);
+    }
+
+    #[test]
+    fn duplicate_blocks() {
+        let SFCParseResult { descriptor, errors } = parse(
+            "<template><a/></template><template><b/></template><script>1</script><script>2</script><script setup>3</script><script setup>4</script>"
+                .to_string(),
+            None,
+        );
+
+        assert_eq!(
+            descriptor.template.map(|template| template.content),
+            Some("<a/>".to_string())
+        );
+        assert_eq!(
+            descriptor.script.map(|script| script.block.content),
+            Some("1".to_string())
+        );
+        assert_eq!(
+            descriptor.script_setup.map(|script| script.block.content),
+            Some("3".to_string())
+        );
+        let code = ErrorCodes::from(SFCErrorCodes::DuplicateBlock);
+        assert!(errors.iter().all(|error| error.code == code));
+        assert_eq!(
+            errors
+                .iter()
+                .map(|error| (
+                    error.message.as_str(),
+                    error.loc.as_ref().unwrap().source.as_str()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    "Single file component can contain only one <template> element",
+                    "<template><b/></template>"
+                ),
+                (
+                    "Single file component can contain only one <script> element",
+                    "<script>2</script>"
+                ),
+                (
+                    "Single file component can contain only one <script setup> element",
+                    "<script setup>4</script>"
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,74 @@
+/// How many lines are shown around the highlighted ones.
+const RANGE: usize = 2;
+
+/// Renders the lines of `source` around the byte range `start..end` with line
+/// numbers, underlining the range with `^` markers.
+pub fn generate_code_frame(source: &str, start: usize, end: usize) -> String {
+    let start = start.min(source.len());
+    let end = end.min(source.len());
+    if start > end {
+        return String::new();
+    }
+
+    // each line along with the length of the newline sequence ending it
+    let last = source.split('\n').count() - 1;
+    let lines: Vec<(&str, usize)> = source
+        .split('\n')
+        .enumerate()
+        .map(|(i, line)| match line.strip_suffix('\r') {
+            _ if i == last => (line, 0),
+            Some(line) => (line, 2),
+            None => (line, 1),
+        })
+        .collect();
+    // the number of characters within the first `len` bytes of `line`
+    let width = |line: &str, len: usize| {
+        line.char_indices()
+            .take_while(|(i, c)| i + c.len_utf8() <= len)
+            .count()
+    };
+
+    let mut count = 0;
+    let mut res = Vec::new();
+    for (i, (line, newline_len)) in lines.iter().enumerate() {
+        count += line.len() + newline_len;
+        // a range starting right after a newline belongs to the next line
+        if count <= start && i < lines.len() - 1 {
+            continue;
+        }
+        let mut j = i.saturating_sub(RANGE);
+        while j <= i + RANGE || end > count {
+            let Some(&(line, newline_len)) = lines.get(j) else {
+                break;
+            };
+            res.push(format!("{:<3}|  {}", j + 1, line));
+            if j == i {
+                // a range starting inside a `\r\n` pair points past the line
+                let pad = (start - (count - (line.len() + newline_len))).min(line.len());
+                let len = if end > count {
+                    line.len().saturating_sub(pad)
+                } else {
+                    end - start
+                };
+                let pad_width = width(line, pad);
+                let len = width(line, (pad + len).min(line.len()))
+                    .saturating_sub(pad_width)
+                    .max(1);
+                res.push(format!(
+                    "   |  {}{}",
+                    " ".repeat(pad_width),
+                    "^".repeat(len)
+                ));
+            } else if j > i {
+                if end > count {
+                    let len = width(line, (end - count).min(line.len())).max(1);
+                    res.push(format!("   |  {}", "^".repeat(len)));
+                }
+                count += line.len() + newline_len;
+            }
+            j += 1;
+        }
+        break;
+    }
+    res.join("\n")
+}
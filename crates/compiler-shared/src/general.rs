@@ -0,0 +1,32 @@
+const BUILT_IN_DIRECTIVES: &[&str] = &[
+    "bind", "cloak", "else-if", "else", "for", "html", "if", "model", "on", "once", "pre", "show",
+    "slot", "text", "memo",
+];
+
+/// Directives handled by the compiler itself rather than resolved at runtime.
+pub fn is_built_in_directive(key: &str) -> bool {
+    BUILT_IN_DIRECTIVES.contains(&key)
+}
+
+/// Whether `key` is an event listener prop such as `onClick`.
+pub fn is_on(key: &str) -> bool {
+    key.strip_prefix("on")
+        .and_then(|rest| rest.chars().next())
+        .is_some_and(|c| !c.is_ascii_lowercase())
+}
+
+/// Converts a hyphen-delimited string to camelCase, e.g. `foo-bar` to `fooBar`.
+pub fn camelize(str: &str) -> String {
+    let mut result = String::with_capacity(str.len());
+    let mut chars = str.chars().peekable();
+    while let Some(c) = chars.next() {
+        match chars.peek() {
+            Some(&next) if c == '-' && (next.is_ascii_alphanumeric() || next == '_') => {
+                result.push(next.to_ascii_uppercase());
+                chars.next();
+            }
+            _ => result.push(c),
+        }
+    }
+    result
+}
@@ -0,0 +1,34 @@
+const GLOBALS_ALLOWED: [&str; 27] = [
+    "Infinity",
+    "undefined",
+    "NaN",
+    "isFinite",
+    "isNaN",
+    "parseFloat",
+    "parseInt",
+    "decodeURI",
+    "decodeURIComponent",
+    "encodeURI",
+    "encodeURIComponent",
+    "Math",
+    "Number",
+    "Date",
+    "Array",
+    "Object",
+    "Boolean",
+    "String",
+    "RegExp",
+    "Map",
+    "Set",
+    "JSON",
+    "Intl",
+    "BigInt",
+    "console",
+    "Error",
+    "Symbol",
+];
+
+/// Globals that can be accessed in templates without a `_ctx.` prefix.
+pub fn is_globally_allowed(key: &str) -> bool {
+    GLOBALS_ALLOWED.contains(&key)
+}
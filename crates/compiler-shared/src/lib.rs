@@ -1,3 +1,11 @@
+mod codeframe;
+mod dom_tag_config;
+mod general;
+mod globals_allow_list;
 mod patch_flags;
 
+pub use codeframe::*;
+pub use dom_tag_config::*;
+pub use general::*;
+pub use globals_allow_list::*;
 pub use patch_flags::*;
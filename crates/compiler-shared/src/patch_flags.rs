@@ -8,6 +8,10 @@ bitflags! {
         const Text = 1;
         /// Indicates an element with dynamic class binding.
         const Class = 2;
+        /// Indicates an element with dynamic style.
+        /// The compiler pre-compiles static string styles into static objects
+        /// and detects and hoists inline static objects.
+        const Style = 1 << 2;
         /// Indicates an element with props with dynamic keys. When keys change, a full
         /// diff is always needed to remove the old key. This flag is mutually
         /// exclusive with CLASS, STYLE and PROPS.
@@ -18,10 +22,28 @@ bitflags! {
         const KeyedFragment = 1 << 7;
         /// Indicates a fragment with unkeyed children.
         const UnkeyedFragment = 1 << 8;
+        /// Indicates an element that only needs non-props patching, e.g. ref or
+        /// directives (onVnodeXXX hooks). since every patched vnode checks for refs
+        /// and onVnodeXXX hooks, it simply marks the vnode so that a parent block
+        /// will track it.
+        const NeedPatch = 1 << 9;
+        /// Indicates a component with dynamic slots (e.g. slot that references a v-for
+        /// iterated value, or dynamic slot names).
+        /// Components with this flag are always force updated.
+        const DynamicSlots = 1 << 10;
         /// Indicates a fragment that was created only because the user has placed
         /// comments at the root level of a template. This is a dev-only flag since
         /// comments are stripped in production.
         const DevRootFragment = 1 << 11;
+
+        // SPECIAL FLAGS -------------------------------------------------------------
+        // Special flags are negative integers. They are never matched against using
+        // bitwise operators (bitwise matching should only happen in branches where
+        // patchFlag > 0), and are mutually exclusive.
+
+        /// Indicates a hoisted static vnode. This is a hint for hydration to skip
+        /// the entire sub tree since static content never needs to be updated.
+        const Hoisted = -1;
     }
 }
 
@@ -30,11 +52,15 @@ impl PatchFlags {
         bitflags_match!(self, {
             &Self::Text => "TEXT",
             &Self::Class => "CLASS",
+            &Self::Style => "STYLE",
             &Self::FullProps => "FULL_PROPS",
             &Self::StableFragment => "STABLE_FRAGMENT",
             &Self::KeyedFragment => "KEYED_FRAGMENT",
             &Self::UnkeyedFragment => "UNKEYED_FRAGMENT",
+            &Self::NeedPatch => "NEED_PATCH",
+            &Self::DynamicSlots => "DYNAMIC_SLOTS",
             &Self::DevRootFragment => "DEV_ROOT_FRAGMENT",
+            &Self::Hoisted => "HOISTED",
             _ => unreachable!()
         })
     }
@@ -43,10 +69,13 @@ impl PatchFlags {
         vec![
             Self::Text,
             Self::Class,
+            Self::Style,
             Self::FullProps,
             Self::StableFragment,
             Self::KeyedFragment,
             Self::UnkeyedFragment,
+            Self::NeedPatch,
+            Self::DynamicSlots,
             Self::DevRootFragment,
         ]
     }